@@ -0,0 +1,16 @@
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("no pose is available at {0:?}")]
+    PoseUnavailable(Duration),
+    #[error("invalid argument: {0}")]
+    InvalidArgument(String),
+}
+
+impl Error {
+    pub fn invalid_argument(desc: impl Into<String>) -> Self {
+        Self::InvalidArgument(desc.into())
+    }
+}
@@ -0,0 +1,21 @@
+//! Sensor poses over time for motion compensation of lidar frames.
+//!
+//! The sensor keeps moving while it sweeps a frame, so points
+//! measured at different times are expressed in different sensor
+//! poses. A [PoseProvider] tells the sensor pose at any time, which
+//! the lidar crates use to move every point into the sensor pose at
+//! one [ReferenceTime].
+//!
+//! Poses follow the convention that a pose maps points in the sensor
+//! frame to a fixed world frame.
+
+mod error;
+mod math;
+mod pose;
+mod provider;
+mod reference;
+
+pub use error::Error;
+pub use pose::Pose;
+pub use provider::{ConstantTwist, ImuPoses, ImuSample, InterpolatedPoses, PoseProvider};
+pub use reference::ReferenceTime;
@@ -0,0 +1,91 @@
+//! Vector and quaternion helpers.
+
+pub(crate) fn add(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+pub(crate) fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub(crate) fn scale(a: [f64; 3], s: f64) -> [f64; 3] {
+    a.map(|v| v * s)
+}
+
+pub(crate) fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+pub(crate) fn norm(a: [f64; 3]) -> f64 {
+    (a[0] * a[0] + a[1] * a[1] + a[2] * a[2]).sqrt()
+}
+
+pub(crate) fn quat_exp(vector: [f64; 3]) -> [f64; 4] {
+    let theta = norm(vector);
+    let half = theta / 2.0;
+    let s = if theta < 1e-9 {
+        0.5
+    } else {
+        half.sin() / theta
+    };
+    quat_normalize([half.cos(), vector[0] * s, vector[1] * s, vector[2] * s])
+}
+
+pub(crate) fn quat_mul(a: [f64; 4], b: [f64; 4]) -> [f64; 4] {
+    let [aw, ax, ay, az] = a;
+    let [bw, bx, by, bz] = b;
+    [
+        aw * bw - ax * bx - ay * by - az * bz,
+        aw * bx + ax * bw + ay * bz - az * by,
+        aw * by - ax * bz + ay * bw + az * bx,
+        aw * bz + ax * by - ay * bx + az * bw,
+    ]
+}
+
+pub(crate) fn quat_conj([w, x, y, z]: [f64; 4]) -> [f64; 4] {
+    [w, -x, -y, -z]
+}
+
+pub(crate) fn quat_normalize(q: [f64; 4]) -> [f64; 4] {
+    let norm = q.iter().map(|v| v * v).sum::<f64>().sqrt();
+    q.map(|v| v / norm)
+}
+
+pub(crate) fn quat_rotate(q: [f64; 4], v: [f64; 3]) -> [f64; 3] {
+    let [w, x, y, z] = q;
+    let u = [x, y, z];
+    let t = scale(cross(u, v), 2.0);
+    add(add(v, scale(t, w)), cross(u, t))
+}
+
+pub(crate) fn quat_slerp(a: [f64; 4], b: [f64; 4], ratio: f64) -> [f64; 4] {
+    let mut dot: f64 = a.iter().zip(&b).map(|(a, b)| a * b).sum();
+    let b = if dot < 0.0 {
+        dot = -dot;
+        b.map(|v| -v)
+    } else {
+        b
+    };
+
+    let (wa, wb) = if dot > 0.9995 {
+        (1.0 - ratio, ratio)
+    } else {
+        let theta = dot.acos();
+        let sin = theta.sin();
+        (
+            ((1.0 - ratio) * theta).sin() / sin,
+            (ratio * theta).sin() / sin,
+        )
+    };
+
+    quat_normalize([
+        wa * a[0] + wb * b[0],
+        wa * a[1] + wb * b[1],
+        wa * a[2] + wb * b[2],
+        wa * a[3] + wb * b[3],
+    ])
+}
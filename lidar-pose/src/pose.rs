@@ -0,0 +1,160 @@
+use crate::math::{
+    add, cross, norm, quat_conj, quat_exp, quat_mul, quat_normalize, quat_rotate, quat_slerp,
+    scale, sub,
+};
+
+/// A rigid transform made of a rotation followed by a translation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pose {
+    /// The unit quaternion in `[w, x, y, z]` order.
+    pub rotation: [f64; 4],
+    /// The translation in meters.
+    pub translation: [f64; 3],
+}
+
+impl Pose {
+    pub fn identity() -> Self {
+        Self {
+            rotation: [1.0, 0.0, 0.0, 0.0],
+            translation: [0.0; 3],
+        }
+    }
+
+    /// Creates a pose from a translation in meters.
+    pub fn from_translation(translation: [f64; 3]) -> Self {
+        Self {
+            translation,
+            ..Self::identity()
+        }
+    }
+
+    /// Creates a pose from a rotation vector, that is, the rotation
+    /// axis scaled by the angle in radians.
+    pub fn from_rotation_vector(vector: [f64; 3]) -> Self {
+        Self {
+            rotation: quat_exp(vector),
+            translation: [0.0; 3],
+        }
+    }
+
+    /// Creates the pose reached by moving at a constant twist for
+    /// `secs` seconds.
+    ///
+    /// The velocities are expressed in the moving sensor frame, in
+    /// m/s and rad/s.
+    pub fn from_twist(linear: [f64; 3], angular: [f64; 3], secs: f64) -> Self {
+        let omega = scale(angular, secs);
+        let v = scale(linear, secs);
+        let theta = norm(omega);
+
+        // coefficients of the left Jacobian of SO(3)
+        let (c1, c2) = if theta < 1e-6 {
+            (0.5, 1.0 / 6.0)
+        } else {
+            let theta2 = theta * theta;
+            (
+                (1.0 - theta.cos()) / theta2,
+                (theta - theta.sin()) / (theta2 * theta),
+            )
+        };
+        let wv = cross(omega, v);
+        let wwv = cross(omega, wv);
+        let translation = add(add(v, scale(wv, c1)), scale(wwv, c2));
+
+        Self {
+            rotation: quat_exp(omega),
+            translation,
+        }
+    }
+
+    /// Composes two poses, applying `other` first.
+    pub fn compose(&self, other: &Self) -> Self {
+        Self {
+            rotation: quat_normalize(quat_mul(self.rotation, other.rotation)),
+            translation: add(self.rotate(other.translation), self.translation),
+        }
+    }
+
+    pub fn inverse(&self) -> Self {
+        let rotation = quat_conj(self.rotation);
+        let translation = scale(quat_rotate(rotation, self.translation), -1.0);
+        Self {
+            rotation,
+            translation,
+        }
+    }
+
+    /// Rotates a vector without translating it.
+    pub fn rotate(&self, vector: [f64; 3]) -> [f64; 3] {
+        quat_rotate(self.rotation, vector)
+    }
+
+    /// Transforms a point in meters.
+    pub fn transform_point(&self, point: [f64; 3]) -> [f64; 3] {
+        add(self.rotate(point), self.translation)
+    }
+
+    /// Interpolates between two poses, where `ratio` 0 gives `self`
+    /// and 1 gives `other`.
+    ///
+    /// The translation is interpolated linearly and the rotation
+    /// spherically.
+    pub fn interpolate(&self, other: &Self, ratio: f64) -> Self {
+        let translation = add(
+            self.translation,
+            scale(sub(other.translation, self.translation), ratio),
+        );
+        Self {
+            rotation: quat_slerp(self.rotation, other.rotation, ratio),
+            translation,
+        }
+    }
+}
+
+impl Default for Pose {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::FRAC_PI_2;
+
+    fn assert_close(a: [f64; 3], b: [f64; 3]) {
+        assert!(norm(sub(a, b)) < 1e-6, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn pose_algebra() {
+        let pose = Pose::from_rotation_vector([0.0, 0.0, FRAC_PI_2])
+            .compose(&Pose::from_translation([1.0, 2.0, 3.0]));
+        assert_close(pose.transform_point([1.0, 0.0, 0.0]), [-2.0, 2.0, 3.0]);
+
+        let identity = pose.compose(&pose.inverse());
+        assert_close(identity.transform_point([4.0, 5.0, 6.0]), [4.0, 5.0, 6.0]);
+
+        let half = Pose::identity().interpolate(&pose, 0.5);
+        let quarter = Pose::from_rotation_vector([0.0, 0.0, FRAC_PI_2 / 2.0]);
+        assert_close(
+            half.rotate([1.0, 0.0, 0.0]),
+            quarter.rotate([1.0, 0.0, 0.0]),
+        );
+    }
+
+    #[test]
+    fn twist_matches_integration() {
+        let linear = [2.0, 0.5, 0.0];
+        let angular = [0.0, 0.1, 0.8];
+        let exact = Pose::from_twist(linear, angular, 1.0);
+
+        let step = Pose::from_twist(linear, angular, 0.001);
+        let integrated = (0..1000).fold(Pose::identity(), |pose, _| pose.compose(&step));
+        assert_close(exact.translation, integrated.translation);
+        assert_close(
+            exact.rotate([1.0, 0.0, 0.0]),
+            integrated.rotate([1.0, 0.0, 0.0]),
+        );
+    }
+}
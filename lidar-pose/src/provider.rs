@@ -0,0 +1,234 @@
+use crate::{
+    math::{add, scale},
+    Error, Pose,
+};
+use std::time::Duration;
+
+/// Provides the sensor pose in a fixed world frame over time.
+pub trait PoseProvider {
+    /// Gets the sensor pose at `time`, or `None` if the time is not
+    /// covered.
+    fn pose_at(&self, time: Duration) -> Option<Pose>;
+
+    /// Gets the transform from the sensor frame at `time` to the
+    /// sensor frame at `reference`.
+    fn relative_pose(&self, reference: Duration, time: Duration) -> Option<Pose> {
+        let reference = self.pose_at(reference)?;
+        let pose = self.pose_at(time)?;
+        Some(reference.inverse().compose(&pose))
+    }
+}
+
+impl<P> PoseProvider for &P
+where
+    P: PoseProvider + ?Sized,
+{
+    fn pose_at(&self, time: Duration) -> Option<Pose> {
+        (**self).pose_at(time)
+    }
+
+    fn relative_pose(&self, reference: Duration, time: Duration) -> Option<Pose> {
+        (**self).relative_pose(reference, time)
+    }
+}
+
+/// Interpolates poses between timestamped samples.
+///
+/// Times outside the samples are not covered.
+#[derive(Debug, Clone)]
+pub struct InterpolatedPoses {
+    samples: Vec<(Duration, Pose)>,
+}
+
+impl InterpolatedPoses {
+    /// Creates from samples with strictly increasing times.
+    pub fn new(samples: Vec<(Duration, Pose)>) -> Result<Self, Error> {
+        if samples.is_empty() {
+            return Err(Error::invalid_argument(
+                "at least one pose sample is required",
+            ));
+        }
+        if !samples.windows(2).all(|pair| pair[0].0 < pair[1].0) {
+            return Err(Error::invalid_argument(
+                "pose sample times must be strictly increasing",
+            ));
+        }
+        Ok(Self { samples })
+    }
+
+    pub fn samples(&self) -> &[(Duration, Pose)] {
+        &self.samples
+    }
+}
+
+impl PoseProvider for InterpolatedPoses {
+    fn pose_at(&self, time: Duration) -> Option<Pose> {
+        let index = match self.samples.binary_search_by_key(&time, |(t, _)| *t) {
+            Ok(index) => return Some(self.samples[index].1),
+            Err(index) => index,
+        };
+        if index == 0 || index == self.samples.len() {
+            return None;
+        }
+
+        let (prev_time, prev) = &self.samples[index - 1];
+        let (next_time, next) = &self.samples[index];
+        let ratio = (time - *prev_time).as_secs_f64() / (*next_time - *prev_time).as_secs_f64();
+        Some(prev.interpolate(next, ratio))
+    }
+}
+
+/// Moves at a constant twist, such as the velocity estimated from
+/// odometry.
+///
+/// The velocities are expressed in the sensor frame, in m/s and
+/// rad/s. It covers any time.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ConstantTwist {
+    pub linear: [f64; 3],
+    pub angular: [f64; 3],
+}
+
+impl PoseProvider for ConstantTwist {
+    fn pose_at(&self, time: Duration) -> Option<Pose> {
+        Some(Pose::from_twist(
+            self.linear,
+            self.angular,
+            time.as_secs_f64(),
+        ))
+    }
+
+    fn relative_pose(&self, reference: Duration, time: Duration) -> Option<Pose> {
+        let secs = time.as_secs_f64() - reference.as_secs_f64();
+        Some(Pose::from_twist(self.linear, self.angular, secs))
+    }
+}
+
+/// An angular velocity sample of an IMU mounted with the sensor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImuSample {
+    pub time: Duration,
+    /// The angular velocity in rad/s in the sensor frame.
+    pub angular_velocity: [f64; 3],
+}
+
+/// Poses integrated from gyroscope samples.
+///
+/// The rotation is integrated from the angular velocities, while the
+/// translation assumes a constant linear velocity in the sensor
+/// frame. Poses between samples are interpolated.
+#[derive(Debug, Clone)]
+pub struct ImuPoses {
+    poses: InterpolatedPoses,
+}
+
+impl ImuPoses {
+    /// Integrates samples with strictly increasing times.
+    ///
+    /// `linear_velocity` is in m/s. Pass zeros to compensate the
+    /// rotation only.
+    pub fn new<I>(samples: I, linear_velocity: [f64; 3]) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = ImuSample>,
+    {
+        let mut samples = samples.into_iter();
+        let Some(first) = samples.next() else {
+            return Err(Error::invalid_argument(
+                "at least one IMU sample is required",
+            ));
+        };
+
+        let mut poses = vec![(first.time, Pose::identity())];
+        let mut prev = first;
+        for sample in samples {
+            if prev.time >= sample.time {
+                return Err(Error::invalid_argument(
+                    "IMU sample times must be strictly increasing",
+                ));
+            }
+            let secs = (sample.time - prev.time).as_secs_f64();
+            let angular = scale(add(prev.angular_velocity, sample.angular_velocity), 0.5);
+            let step = Pose::from_twist(linear_velocity, angular, secs);
+            let pose = poses.last().unwrap().1.compose(&step);
+            poses.push((sample.time, pose));
+            prev = sample;
+        }
+
+        Ok(Self {
+            poses: InterpolatedPoses::new(poses)?,
+        })
+    }
+}
+
+impl PoseProvider for ImuPoses {
+    fn pose_at(&self, time: Duration) -> Option<Pose> {
+        self.poses.pose_at(time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::{norm, sub};
+
+    fn assert_close(a: [f64; 3], b: [f64; 3]) {
+        assert!(norm(sub(a, b)) < 1e-6, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn interpolated_and_imu_poses() {
+        let angular = [0.0, 0.0, 1.5];
+        let imu = ImuPoses::new(
+            (0..=20).map(|index| ImuSample {
+                time: Duration::from_millis(10 * index),
+                angular_velocity: angular,
+            }),
+            [0.0; 3],
+        )
+        .unwrap();
+        let twist = ConstantTwist {
+            linear: [0.0; 3],
+            angular,
+        };
+
+        let time = Duration::from_micros(123_456);
+        let expect = twist.pose_at(time).unwrap();
+        let pose = imu.pose_at(time).unwrap();
+        assert_close(pose.rotate([1.0, 0.0, 0.0]), expect.rotate([1.0, 0.0, 0.0]));
+        assert!(imu.pose_at(Duration::from_millis(201)).is_none());
+
+        let poses = InterpolatedPoses::new(vec![
+            (Duration::ZERO, Pose::identity()),
+            (
+                Duration::from_secs(1),
+                Pose::from_translation([1.0, 0.0, 0.0]),
+            ),
+        ])
+        .unwrap();
+        let pose = poses.pose_at(Duration::from_millis(250)).unwrap();
+        assert_close(pose.translation, [0.25, 0.0, 0.0]);
+        assert!(poses.pose_at(Duration::from_millis(1001)).is_none());
+
+        let relative = poses
+            .relative_pose(Duration::from_secs(1), Duration::from_millis(500))
+            .unwrap();
+        assert_close(relative.translation, [-0.5, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn reject_unordered_samples() {
+        assert!(InterpolatedPoses::new(vec![]).is_err());
+        assert!(InterpolatedPoses::new(vec![
+            (Duration::from_secs(1), Pose::identity()),
+            (Duration::from_secs(1), Pose::identity()),
+        ])
+        .is_err());
+
+        let sample = |millis| ImuSample {
+            time: Duration::from_millis(millis),
+            angular_velocity: [0.0; 3],
+        };
+        assert!(ImuPoses::new([], [0.0; 3]).is_err());
+        assert!(ImuPoses::new([sample(10), sample(5)], [0.0; 3]).is_err());
+    }
+}
@@ -0,0 +1,13 @@
+use std::time::Duration;
+
+/// The time whose sensor pose the points are moved into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ReferenceTime {
+    /// The time of the earliest point.
+    #[default]
+    Start,
+    /// The time of the latest point.
+    End,
+    /// A given time on the clock of the points.
+    At(Duration),
+}
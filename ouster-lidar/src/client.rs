@@ -3,8 +3,8 @@
 use super::{
     consts::PIXELS_PER_COLUMN,
     enums::{LidarMode, MultipurposeIoMode, NmeaBaudRate, OnOffMode, Polarity, TimestampMode},
+    error::Error,
 };
-use anyhow::{anyhow, ensure, Result};
 use derivative::Derivative;
 use noisy_float::types::R64;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
@@ -150,7 +150,7 @@ pub struct CommandClient {
 }
 
 impl CommandClient {
-    pub fn connect<A>(address: A, timeout: Option<Duration>) -> Result<CommandClient>
+    pub fn connect<A>(address: A, timeout: Option<Duration>) -> Result<CommandClient, Error>
     where
         A: ToSocketAddrs,
     {
@@ -164,121 +164,105 @@ impl CommandClient {
         Ok(client)
     }
 
-    pub fn get_config_txt(&mut self) -> Result<ConfigText> {
-        self.writer.write_all(b"get_config_txt\n")?;
-        let line = self
-            .reader
-            .next()
-            .ok_or_else(|| anyhow!("Unexpected end of stream"))??;
+    pub fn get_config_txt(&mut self) -> Result<ConfigText, Error> {
+        let line = self.command("get_config_txt")?;
         let config = serde_json::from_str(&line)?;
         Ok(config)
     }
 
-    pub fn get_time_info(&mut self) -> Result<TimeInfo> {
-        self.writer.write_all(b"get_time_info\n")?;
-        let line = self
-            .reader
-            .next()
-            .ok_or_else(|| anyhow!("Unexpected end of stream"))??;
+    pub fn get_time_info(&mut self) -> Result<TimeInfo, Error> {
+        let line = self.command("get_time_info")?;
         let config = serde_json::from_str(&line)?;
         Ok(config)
     }
 
-    pub fn get_lidar_intrinsics(&mut self) -> Result<LidarIntrinsics> {
-        self.writer.write_all(b"get_lidar_intrinsics\n")?;
-        let line = self
-            .reader
-            .next()
-            .ok_or_else(|| anyhow!("Unexpected end of stream"))??;
+    pub fn get_lidar_intrinsics(&mut self) -> Result<LidarIntrinsics, Error> {
+        let line = self.command("get_lidar_intrinsics")?;
         let config = serde_json::from_str(&line)?;
         Ok(config)
     }
 
-    pub fn get_imu_intrinsics(&mut self) -> Result<ImuIntrinsics> {
-        self.writer.write_all(b"get_imu_intrinsics\n")?;
-        let line = self
-            .reader
-            .next()
-            .ok_or_else(|| anyhow!("Unexpected end of stream"))??;
+    pub fn get_imu_intrinsics(&mut self) -> Result<ImuIntrinsics, Error> {
+        let line = self.command("get_imu_intrinsics")?;
         let config = serde_json::from_str(&line)?;
         Ok(config)
     }
 
-    pub fn get_beam_intrinsics(&mut self) -> Result<BeamIntrinsics> {
-        self.writer.write_all(b"get_beam_intrinsics\n")?;
-        let line = self
-            .reader
-            .next()
-            .ok_or_else(|| anyhow!("Unexpected end of stream"))??;
+    pub fn get_beam_intrinsics(&mut self) -> Result<BeamIntrinsics, Error> {
+        let line = self.command("get_beam_intrinsics")?;
         let config = serde_json::from_str(&line)?;
         Ok(config)
     }
 
-    pub fn reinitialize(mut self) -> Result<()> {
-        self.writer.write_all(b"reinitialize\n")?;
-        let line = self
-            .reader
-            .next()
-            .ok_or_else(|| anyhow!("Unexpected end of stream"))??;
-        ensure!(line == "reinitialize", "Unexpected response {:?}", line);
+    pub fn reinitialize(mut self) -> Result<(), Error> {
+        let line = self.command("reinitialize")?;
+        expect_response("reinitialize", line)?;
         Ok(())
     }
 
-    pub fn write_config_txt(&mut self) -> Result<()> {
-        self.writer.write_all(b"write_config_txt\n")?;
-        let line = self
-            .reader
-            .next()
-            .ok_or_else(|| anyhow!("Unexpected end of stream"))??;
-        ensure!(line == "write_config_txt", "Unexpected response {:?}", line);
+    pub fn write_config_txt(&mut self) -> Result<(), Error> {
+        let line = self.command("write_config_txt")?;
+        expect_response("write_config_txt", line)?;
         Ok(())
     }
 
-    pub fn set_udp_ip(&mut self, ip: Ipv4Addr) -> Result<()> {
+    pub fn set_udp_ip(&mut self, ip: Ipv4Addr) -> Result<(), Error> {
         self.set_config_param("udp_ip", ip)?;
         Ok(())
     }
 
-    pub fn set_udp_port_lidar(&mut self, port: u16) -> Result<()> {
+    pub fn set_udp_port_lidar(&mut self, port: u16) -> Result<(), Error> {
         self.set_config_param("udp_port_lidar", port)?;
         Ok(())
     }
 
-    pub fn set_udp_port_imu(&mut self, port: u16) -> Result<()> {
+    pub fn set_udp_port_imu(&mut self, port: u16) -> Result<(), Error> {
         self.set_config_param("udp_port_imu", port)?;
         Ok(())
     }
 
-    pub fn set_lidar_mode(&mut self, mode: LidarMode) -> Result<()> {
+    pub fn set_lidar_mode(&mut self, mode: LidarMode) -> Result<(), Error> {
         self.set_config_param("lidar_mode", mode)?;
         Ok(())
     }
 
-    pub fn set_timestamp_mode(&mut self, mode: TimestampMode) -> Result<()> {
+    pub fn set_timestamp_mode(&mut self, mode: TimestampMode) -> Result<(), Error> {
         self.set_config_param("timestamp_mode", mode)?;
         Ok(())
     }
 
-    pub fn set_sync_pulse_in_polarity(&mut self, polarity: Polarity) -> Result<()> {
+    pub fn set_sync_pulse_in_polarity(&mut self, polarity: Polarity) -> Result<(), Error> {
         self.set_config_param("sync_pulse_in_polarity", polarity)?;
         Ok(())
     }
 
-    pub fn set_nmea_in_polarity(&mut self, polarity: Polarity) -> Result<()> {
+    pub fn set_nmea_in_polarity(&mut self, polarity: Polarity) -> Result<(), Error> {
         self.set_config_param("nmea_in_polarity", polarity)?;
         Ok(())
     }
 
-    fn set_config_param<T: Display>(&mut self, param: &str, arg: T) -> Result<()> {
-        let command = format!("set_config_param {} {}\n", param, arg);
-        self.writer.write_all(command.as_bytes())?;
-        let line = self
-            .reader
-            .next()
-            .ok_or_else(|| anyhow!("Unexpected end of stream"))??;
-        ensure!(line == "set_config_param", "Unexpected response {:?}", line);
+    fn set_config_param<T: Display>(&mut self, param: &str, arg: T) -> Result<(), Error> {
+        let line = self.command(&format!("set_config_param {} {}", param, arg))?;
+        expect_response("set_config_param", line)?;
         Ok(())
     }
+
+    /// Sends a command and reads the response line.
+    fn command(&mut self, command: &str) -> Result<String, Error> {
+        self.writer.write_all(format!("{}\n", command).as_bytes())?;
+        let line = self.reader.next().ok_or(Error::ConnectionClosed)??;
+        Ok(line)
+    }
+}
+
+fn expect_response(command: &str, response: String) -> Result<(), Error> {
+    if response != command {
+        return Err(Error::UnexpectedResponse {
+            command: command.to_string(),
+            response,
+        });
+    }
+    Ok(())
 }
 
 mod serde_bool_to_int {
@@ -3,8 +3,8 @@
 use super::{
     consts::{OS_1_BEAM_ALTITUDE_DEGREES, OS_1_BEAM_AZIMUTH_DEGREE_CORRECTIONS, PIXELS_PER_COLUMN},
     enums::LidarMode,
+    error::Error,
 };
-use anyhow::Result;
 use derivative::Derivative;
 use noisy_float::types::R64;
 use serde::{Deserialize, Serialize};
@@ -30,29 +30,35 @@ impl Config {
         lidar_mode: LidarMode,
     ) -> Config {
         Config {
-            beam_altitude_angles: unsafe { mem::transmute(beam_altitude_angles) },
+            beam_altitude_angles: unsafe {
+                mem::transmute::<[f64; PIXELS_PER_COLUMN], [R64; PIXELS_PER_COLUMN]>(
+                    beam_altitude_angles,
+                )
+            },
             beam_azimuth_angle_corrections: unsafe {
-                mem::transmute(beam_azimuth_angle_corrections)
+                mem::transmute::<[f64; PIXELS_PER_COLUMN], [R64; PIXELS_PER_COLUMN]>(
+                    beam_azimuth_angle_corrections,
+                )
             },
             lidar_mode,
         }
     }
 
     /// Loads config JSON file from path.
-    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Config> {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Config, Error> {
         let file = File::open(path.as_ref())?;
         let ret = Self::from_reader(file)?;
         Ok(ret)
     }
 
     /// Loads config JSON data from reader with [Read](std::io::Read) trait.
-    pub fn from_reader<R: Read>(reader: R) -> Result<Config> {
+    pub fn from_reader<R: Read>(reader: R) -> Result<Config, Error> {
         let ret = serde_json::de::from_reader(reader)?;
         Ok(ret)
     }
 
     /// Parses from JSON string.
-    pub fn from_json_str(data: &str) -> Result<Config> {
+    pub fn from_json_str(data: &str) -> Result<Config, Error> {
         let ret = serde_json::from_str(data)?;
         Ok(ret)
     }
@@ -62,13 +68,20 @@ impl Config {
         &mut self,
         beam_azimuth_angle_corrections: [f64; PIXELS_PER_COLUMN],
     ) {
-        self.beam_azimuth_angle_corrections =
-            unsafe { mem::transmute(beam_azimuth_angle_corrections) };
+        self.beam_azimuth_angle_corrections = unsafe {
+            mem::transmute::<[f64; PIXELS_PER_COLUMN], [R64; PIXELS_PER_COLUMN]>(
+                beam_azimuth_angle_corrections,
+            )
+        };
     }
 
     /// Sets `beam_altitude_angles` field.
     pub fn beam_altitude_angles(&mut self, beam_altitude_angles: [f64; PIXELS_PER_COLUMN]) {
-        self.beam_altitude_angles = unsafe { mem::transmute(beam_altitude_angles) };
+        self.beam_altitude_angles = unsafe {
+            mem::transmute::<[f64; PIXELS_PER_COLUMN], [R64; PIXELS_PER_COLUMN]>(
+                beam_altitude_angles,
+            )
+        };
     }
 
     /// Sets `lidar_mode` field.
@@ -83,9 +96,15 @@ impl Config {
         let beam_azimuth_angle_corrections = OS_1_BEAM_AZIMUTH_DEGREE_CORRECTIONS;
 
         Self {
-            beam_altitude_angles: unsafe { mem::transmute(beam_altitude_angles) },
+            beam_altitude_angles: unsafe {
+                mem::transmute::<[f64; PIXELS_PER_COLUMN], [R64; PIXELS_PER_COLUMN]>(
+                    beam_altitude_angles,
+                )
+            },
             beam_azimuth_angle_corrections: unsafe {
-                mem::transmute(beam_azimuth_angle_corrections)
+                mem::transmute::<[f64; PIXELS_PER_COLUMN], [R64; PIXELS_PER_COLUMN]>(
+                    beam_azimuth_angle_corrections,
+                )
             },
             lidar_mode: LidarMode::Mode1024x10,
         }
@@ -0,0 +1,70 @@
+//! Motion compensation of frames.
+//!
+//! The sensor keeps moving while it sweeps a frame, so points
+//! measured at different times are expressed in different sensor
+//! poses. [deskew_frame] moves every point into the sensor pose at
+//! one reference time using a [PoseProvider].
+//!
+//! Poses follow the convention that a pose maps points in the sensor
+//! frame to a fixed world frame. Times are the timestamps of the
+//! points.
+//!
+//! The poses and providers are re-exported from [lidar_pose], which
+//! is shared with the Velodyne crate.
+
+use crate::{error::Error, frame_converter::Frame, pcd_converter::Point};
+pub use lidar_pose::{
+    ConstantTwist, ImuPoses, ImuSample, InterpolatedPoses, Pose, PoseProvider, ReferenceTime,
+};
+use measurements::Length;
+
+/// Moves the points of a frame into the sensor pose at the reference
+/// time.
+///
+/// Points without a return are left as they are. It fails if the
+/// provider does not cover the time of any point, in which case the
+/// frame is left unchanged.
+pub fn deskew_frame<P>(
+    frame: &mut Frame,
+    provider: &P,
+    reference: ReferenceTime,
+) -> Result<(), Error>
+where
+    P: PoseProvider + ?Sized,
+{
+    let times = frame.points.iter().map(|point| point.timestamp);
+    let (Some(start), Some(end)) = (times.clone().min(), times.max()) else {
+        return Ok(());
+    };
+    let reference = match reference {
+        ReferenceTime::Start => start,
+        ReferenceTime::End => end,
+        ReferenceTime::At(time) => time,
+    };
+
+    // compute all transforms first to keep the frame intact on errors
+    let transforms: Vec<_> = frame
+        .points
+        .iter()
+        .map(|point| {
+            let time = point.timestamp;
+            provider
+                .relative_pose(reference, time)
+                .ok_or(lidar_pose::Error::PoseUnavailable(time))
+        })
+        .collect::<Result<_, _>>()?;
+
+    for (point, pose) in frame.points.iter_mut().zip(transforms) {
+        deskew_point(point, &pose);
+    }
+
+    Ok(())
+}
+
+fn deskew_point(point: &mut Point, pose: &Pose) {
+    if point.distance.as_meters() == 0.0 {
+        return;
+    }
+    let xyz = pose.transform_point(point.point.map(|v| v.as_meters()));
+    point.point = xyz.map(Length::from_meters);
+}
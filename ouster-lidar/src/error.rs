@@ -0,0 +1,32 @@
+//! The error type of this crate.
+
+use std::io;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("expect {expect} bytes for a packet, but get {actual}")]
+    SizeMismatch { expect: usize, actual: usize },
+    #[error(
+        "measurement ID {measurement_id} exceeds the upper bound {columns_per_revolution}. \
+         Is the lidar_mode configured correctly?"
+    )]
+    MeasurementIdOutOfRange {
+        measurement_id: u16,
+        columns_per_revolution: u16,
+    },
+    /// A column comes with a (frame ID, measurement ID) pair not after
+    /// the previous column.
+    #[error("column {curr:?} is received after {prev:?} in (frame ID, measurement ID)")]
+    OutOfOrderColumn { prev: (u16, u16), curr: (u16, u16) },
+    #[error("the sensor closed the connection")]
+    ConnectionClosed,
+    #[error("unexpected response {response:?} to the command {command:?}")]
+    UnexpectedResponse { command: String, response: String },
+    #[error("pose error: {0}")]
+    Pose(#[from] lidar_pose::Error),
+}
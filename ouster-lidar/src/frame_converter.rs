@@ -3,10 +3,10 @@
 use super::{
     config::Config,
     consts::COLUMNS_PER_PACKET,
+    error::Error,
     packet::{Column, Packet},
     pcd_converter::{Point, PointCloudConverter},
 };
-use anyhow::{anyhow, bail, Result};
 use std::{cmp::Ordering, ops::Range};
 
 /// A frame is a collection of points gathered in one
@@ -58,7 +58,7 @@ impl FrameConverter {
     }
 
     /// Pushes new [Column] to converter.
-    pub fn push_column(&mut self, column: &Column) -> Result<Vec<Frame>> {
+    pub fn push_column(&mut self, column: &Column) -> Result<Vec<Frame>, Error> {
         let curr_fid = column.frame_id;
         let curr_mid = column.measurement_id;
         let curr_ts = column.timestamp;
@@ -72,9 +72,10 @@ impl FrameConverter {
                         Ordering::Less => state.frame.take(),
                         Ordering::Equal => None,
                         Ordering::Greater => {
-                            bail!(
-                                "Measurement ID of received column is less than that of previous column"
-                            );
+                            return Err(Error::OutOfOrderColumn {
+                                prev: (state.last_fid, state.last_mid),
+                                curr: (curr_fid, curr_mid),
+                            });
                         }
                     };
 
@@ -138,10 +139,10 @@ impl FrameConverter {
                     }
                     Ordering::Equal => {
                         if state.last_mid >= curr_mid {
-                            let error = anyhow!(
-                                "Measurement ID of received column is less than that of previous column"
-                            );
-                            return Err(error);
+                            return Err(Error::OutOfOrderColumn {
+                                prev: (state.last_fid, state.last_mid),
+                                curr: (curr_fid, curr_mid),
+                            });
                         }
 
                         // Conditionally produce frame if measurement ID is the latest one
@@ -171,10 +172,10 @@ impl FrameConverter {
                         (new_state, output_frames)
                     }
                     Ordering::Greater => {
-                        let error = anyhow!(
-                            "Frame ID of received column is less than that of previous column"
-                        );
-                        return Err(error);
+                        return Err(Error::OutOfOrderColumn {
+                            prev: (state.last_fid, state.last_mid),
+                            curr: (curr_fid, curr_mid),
+                        });
                     }
                 }
             }
@@ -211,7 +212,7 @@ impl FrameConverter {
     }
 
     /// Pushes new [Packet] to converter.
-    pub fn push_packet<P>(&mut self, packet: P) -> Result<Vec<Frame>>
+    pub fn push_packet<P>(&mut self, packet: P) -> Result<Vec<Frame>, Error>
     where
         P: AsRef<Packet>,
     {
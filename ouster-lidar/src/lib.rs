@@ -3,7 +3,9 @@
 pub mod client;
 pub mod config;
 pub mod consts;
+pub mod deskew;
 pub mod enums;
+mod error;
 pub mod frame_converter;
 pub mod packet;
 pub mod pcd_converter;
@@ -12,6 +14,7 @@ mod utils;
 pub use client::*;
 pub use config::*;
 pub use enums::*;
+pub use error::Error;
 pub use frame_converter::*;
 pub use packet::*;
 pub use pcd_converter::*;
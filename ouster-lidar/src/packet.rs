@@ -1,7 +1,9 @@
 //! Provides a set of _C-packed_ structs for Ouster packets.
-use super::consts::{COLUMNS_PER_PACKET, ENCODER_TICKS_PER_REV, PIXELS_PER_COLUMN};
-use anyhow::{ensure, Result};
-use chrono::NaiveDateTime;
+use super::{
+    consts::{COLUMNS_PER_PACKET, ENCODER_TICKS_PER_REV, PIXELS_PER_COLUMN},
+    error::Error,
+};
+use chrono::{DateTime, NaiveDateTime};
 use measurements::{Angle, Length};
 use std::{mem, time::Duration};
 
@@ -51,7 +53,9 @@ impl Column {
     pub fn datetime(&self) -> NaiveDateTime {
         let secs = self.timestamp / 1_000_000_000;
         let nsecs = self.timestamp % 1_000_000_000;
-        NaiveDateTime::from_timestamp_opt(secs as i64, nsecs as u32).unwrap()
+        DateTime::from_timestamp(secs as i64, nsecs as u32)
+            .unwrap()
+            .naive_utc()
     }
 
     pub fn time(&self) -> Duration {
@@ -93,13 +97,13 @@ impl Packet {
     }
 
     /// Construct packet from slice of bytes. Error if the slice size is not correct.
-    pub fn from_slice(buffer: &[u8]) -> Result<&Packet> {
-        ensure!(
-            buffer.len() == mem::size_of::<Packet>(),
-            "Requre the slice length to be {}, but get {}",
-            mem::size_of::<Packet>(),
-            buffer.len(),
-        );
+    pub fn from_slice(buffer: &[u8]) -> Result<&Packet, Error> {
+        if buffer.len() != mem::size_of::<Packet>() {
+            return Err(Error::SizeMismatch {
+                expect: mem::size_of::<Packet>(),
+                actual: buffer.len(),
+            });
+        }
         let packet = unsafe { &*(buffer.as_ptr() as *const Packet) };
         Ok(packet)
     }
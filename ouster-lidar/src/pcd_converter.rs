@@ -3,10 +3,10 @@
 use super::{
     config::Config,
     consts::PIXELS_PER_COLUMN,
+    error::Error,
     packet::{Column, Packet},
 };
 use crate::utils::AngleExt as _;
-use anyhow::{ensure, Result};
 use itertools::izip;
 use measurements::{Angle, Length};
 use num_traits::Float;
@@ -92,15 +92,15 @@ impl PointCloudConverter {
     ///
     /// The method takes [Column.measurement_id](Column.measurement_id) as column index.
     /// It returns error if the index is out of bound.
-    pub(crate) fn column_to_points(&self, column: &Column) -> Result<Vec<Point>> {
+    pub(crate) fn column_to_points(&self, column: &Column) -> Result<Vec<Point>, Error> {
         // sanity check
         let col_index = column.measurement_id;
-        ensure!(
-            col_index < self.columns_per_revolution,
-            "measurement_id {} is exceeds the upper bound {}. Is the lidar_mode configured correctly?",
-            col_index,
-            self.columns_per_revolution,
-        );
+        if col_index >= self.columns_per_revolution {
+            return Err(Error::MeasurementIdOutOfRange {
+                measurement_id: col_index,
+                columns_per_revolution: self.columns_per_revolution,
+            });
+        }
 
         // return empty list if the column is not valid
         if !column.valid() {
@@ -143,7 +143,7 @@ impl PointCloudConverter {
     }
 
     /// Compute point positions from a packet.
-    pub fn convert<P>(&self, packet: P) -> Result<Vec<Point>>
+    pub fn convert<P>(&self, packet: P) -> Result<Vec<Point>, Error>
     where
         P: AsRef<Packet>,
     {
@@ -152,7 +152,7 @@ impl PointCloudConverter {
             .columns
             .iter()
             .map(|col| self.column_to_points(col))
-            .collect::<Result<Vec<_>>>()?
+            .collect::<Result<Vec<_>, _>>()?
             .into_iter()
             .flatten()
             .collect();
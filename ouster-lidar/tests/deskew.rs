@@ -0,0 +1,119 @@
+use measurements::{Angle, Length};
+use ouster_lidar::{
+    deskew::{deskew_frame, ConstantTwist, InterpolatedPoses, Pose, ReferenceTime},
+    frame_converter::Frame,
+    pcd_converter::Point,
+    Error,
+};
+use std::time::Duration;
+
+fn point(millis: u64, distance: f64, xyz: [f64; 3]) -> Point {
+    Point {
+        timestamp: Duration::from_millis(millis),
+        azimuth_angle: Angle::from_radians(0.0),
+        distance: Length::from_meters(distance),
+        reflectivity: 0,
+        signal_photons: 0,
+        noise_photons: 0,
+        laser_id: 0,
+        point: xyz.map(Length::from_meters),
+    }
+}
+
+/// Points sweeping from 10 to 100ms, where the one at 50ms has no
+/// return.
+fn frame() -> Frame {
+    let points = (1..=10)
+        .map(|index| {
+            let distance = if index == 5 { 0.0 } else { 5.0 };
+            point(index * 10, distance, [5.0, 0.0, 0.0])
+        })
+        .collect();
+
+    Frame {
+        frame_id: 0,
+        skipped_frame_ids: 0..0,
+        timestamps: vec![],
+        points,
+    }
+}
+
+fn xyz(point: &Point) -> [f64; 3] {
+    point.point.map(|v| v.as_meters())
+}
+
+fn assert_close(a: [f64; 3], b: [f64; 3]) {
+    let dist = a.iter().zip(&b).map(|(a, b)| (a - b).powi(2)).sum::<f64>();
+    assert!(dist.sqrt() < 1e-9, "{a:?} != {b:?}");
+}
+
+#[test]
+fn deskew_constant_velocity() {
+    let provider = ConstantTwist {
+        linear: [10.0, 0.0, 0.0],
+        angular: [0.0; 3],
+    };
+
+    for (reference, reference_millis) in [
+        (ReferenceTime::Start, 10),
+        (ReferenceTime::End, 100),
+        (ReferenceTime::At(Duration::from_millis(40)), 40),
+    ] {
+        let before = frame();
+        let mut frame = before.clone();
+        deskew_frame(&mut frame, &provider, reference).unwrap();
+
+        for (before, after) in before.points.iter().zip(&frame.points) {
+            let millis = before.timestamp.as_millis() as f64;
+            let dx = match before.distance.as_meters() {
+                0.0 => 0.0,
+                _ => 10.0 * (millis - reference_millis as f64) / 1000.0,
+            };
+            assert_close(xyz(after), [5.0 + dx, 0.0, 0.0]);
+        }
+    }
+}
+
+#[test]
+fn deskew_rotation() {
+    // a quarter turn about z over the 90ms sweep
+    let provider = ConstantTwist {
+        linear: [0.0; 3],
+        angular: [0.0, 0.0, std::f64::consts::FRAC_PI_2 / 0.09],
+    };
+    let mut frame = frame();
+    deskew_frame(&mut frame, &provider, ReferenceTime::Start).unwrap();
+
+    assert_close(xyz(&frame.points[0]), [5.0, 0.0, 0.0]);
+    assert_close(xyz(&frame.points[9]), [0.0, 5.0, 0.0]);
+}
+
+#[test]
+fn keep_frame_without_poses() {
+    let poses = InterpolatedPoses::new(vec![
+        (Duration::ZERO, Pose::identity()),
+        (
+            Duration::from_millis(50),
+            Pose::from_translation([1.0, 0.0, 0.0]),
+        ),
+    ])
+    .unwrap();
+
+    let before = frame();
+    let mut frame = before.clone();
+    let result = deskew_frame(&mut frame, &poses, ReferenceTime::Start);
+    assert!(matches!(
+        result,
+        Err(Error::Pose(lidar_pose::Error::PoseUnavailable(time)))
+            if time == Duration::from_millis(60)
+    ));
+    for (before, after) in before.points.iter().zip(&frame.points) {
+        assert_eq!(xyz(after), xyz(before));
+    }
+
+    let mut empty = Frame {
+        points: vec![],
+        ..before
+    };
+    deskew_frame(&mut empty, &poses, ReferenceTime::End).unwrap();
+}
@@ -1,27 +1,35 @@
 use anyhow::Result;
 use ouster_lidar::{
-    config::Config, frame_converter::FrameConverter, packet::Packet as OusterPacket,
+    config::Config,
+    deskew::{deskew_frame, ConstantTwist, ReferenceTime},
+    frame_converter::FrameConverter,
+    packet::Packet as OusterPacket,
     pcd_converter::PointCloudConverter,
 };
-use pcap::Capture;
-
-const UDP_HEADER_SIZE: usize = 42;
+use std::path::Path;
+use velodyne_lidar::capture::{CaptureReader, Decoded, FrameDecoder};
+
+/// Reads the UDP payloads in a capture file.
+fn udp_payloads(path: impl AsRef<Path>) -> Result<Vec<Vec<u8>>> {
+    let mut decoder = FrameDecoder::new();
+    let mut payloads = vec![];
+
+    for record in CaptureReader::open(path)? {
+        let record = record?;
+        if let Decoded::Datagram(datagram) = decoder.decode(record.link_type, &record.data) {
+            payloads.push(datagram.payload);
+        }
+    }
+    Ok(payloads)
+}
 
 #[test]
 fn ouster_create_packet() -> Result<()> {
-    let mut cap = Capture::from_file("test_files/ouster_example.pcap")?;
-    cap.filter("udp", true)?;
-
-    let packets: Vec<_> = itertools::unfold(cap, |cap| {
-        Some(loop {
-            let packet = cap.next_packet().ok()?;
-            let slice = &packet.data[UDP_HEADER_SIZE..];
-            if let Ok(packet) = OusterPacket::from_slice(slice) {
-                break *packet;
-            }
-        })
-    })
-    .collect();
+    let payloads = udp_payloads("test_files/ouster_example.pcap")?;
+    let packets: Vec<_> = payloads
+        .iter()
+        .filter_map(|payload| OusterPacket::from_slice(payload).ok())
+        .collect();
 
     let mut prev_timestamp = None;
 
@@ -43,12 +51,8 @@ fn ouster_pcd_converter() -> Result<()> {
     let pcd_converter = PointCloudConverter::from_config(config);
 
     // Load pcap file
-    let mut cap = Capture::from_file("test_files/ouster_example.pcap")?;
-    cap.filter("udp", true)?;
-
-    while let Ok(packet) = cap.next_packet() {
-        let slice = &packet.data[UDP_HEADER_SIZE..];
-        let lidar_packet = OusterPacket::from_slice(slice)?;
+    for payload in udp_payloads("test_files/ouster_example.pcap")? {
+        let lidar_packet = OusterPacket::from_slice(&payload)?;
         let points = pcd_converter.convert(lidar_packet)?;
         assert!(points.len() as u16 == pcd_converter.columns_per_revolution());
     }
@@ -63,14 +67,10 @@ fn ouster_frame_converter() -> Result<()> {
     let mut frame_converter = FrameConverter::from_config(config);
 
     // Load pcap file
-    let mut cap = Capture::from_file("test_files/ouster_example.pcap")?;
-    cap.filter("udp", true)?;
-
     let mut frames = vec![];
 
-    while let Ok(packet) = cap.next_packet() {
-        let slice = &packet.data[UDP_HEADER_SIZE..];
-        let lidar_packet = OusterPacket::from_slice(slice)?;
+    for payload in udp_payloads("test_files/ouster_example.pcap")? {
+        let lidar_packet = OusterPacket::from_slice(&payload)?;
         let new_frames = frame_converter.push_packet(lidar_packet)?;
         frames.extend(new_frames);
     }
@@ -106,3 +106,39 @@ fn ouster_frame_converter() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn ouster_deskew() -> Result<()> {
+    let config = Config::from_path("test_files/ouster_example.json")?;
+    let mut frame_converter = FrameConverter::from_config(config);
+
+    let mut frames = vec![];
+    for payload in udp_payloads("test_files/ouster_example.pcap")? {
+        let lidar_packet = OusterPacket::from_slice(&payload)?;
+        frames.extend(frame_converter.push_packet(lidar_packet)?);
+    }
+    let mut frame = frames.into_iter().next().expect("no complete frame");
+
+    let before = frame.points.clone();
+    let start = before.iter().map(|point| point.timestamp).min().unwrap();
+    let provider = ConstantTwist {
+        linear: [0.0, 0.0, 4.0],
+        angular: [0.0; 3],
+    };
+    deskew_frame(&mut frame, &provider, ReferenceTime::Start)?;
+
+    for (before, after) in before.iter().zip(&frame.points) {
+        let dz = 4.0 * (before.timestamp - start).as_secs_f64();
+        let [x0, y0, z0] = before.point.map(|v| v.as_meters());
+        let [x1, y1, z1] = after.point.map(|v| v.as_meters());
+
+        if before.distance.as_meters() == 0.0 {
+            assert_eq!([x0, y0, z0], [x1, y1, z1]);
+        } else {
+            assert!((x1 - x0).abs() < 1e-6 && (y1 - y0).abs() < 1e-6);
+            assert!((z1 - z0 - dz).abs() < 1e-6);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,125 @@
+//! Benchmarks of the conversion from channels to points.
+//!
+//! Run with `cargo bench --bench convert`. The results on the bundled
+//! VLP-32C capture are listed in the [trig_table] module
+//! documentation.
+//!
+//! [trig_table]: velodyne_lidar::trig_table
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use std::hint::black_box;
+use velodyne_lidar::{
+    capture::PacketReader,
+    packet::Channel,
+    trig_table::{Precision, XyzConverter},
+    Config, DataPacket, FrameBuilder, Packet,
+};
+
+fn load_packets() -> Vec<DataPacket> {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/velodyne_vlp32.pcap");
+    PacketReader::open(path)
+        .unwrap()
+        .packets()
+        .filter_map(|packet| match packet.unwrap().packet {
+            Packet::Data(packet) => Some(*packet),
+            Packet::Position(_) => None,
+        })
+        .collect()
+}
+
+/// Collects the channels of each firing and the azimuth of each
+/// channel in radians.
+fn load_firings(packets: &[DataPacket], config: &Config) -> Vec<(Vec<f32>, Vec<Channel>)> {
+    packets
+        .iter()
+        .flat_map(|packet| packet.firing_block_iter_s32())
+        .map(|firing| {
+            let start = firing.azimuth_range.start;
+            let azimuths = config
+                .lasers
+                .iter()
+                .map(|laser| (start + laser.azimuth_offset).as_radians().rem_euclid(TAU) as f32)
+                .collect();
+            (azimuths, firing.channels.to_vec())
+        })
+        .collect()
+}
+
+const TAU: f64 = std::f64::consts::PI * 2.0;
+
+fn build_frames(packets: &[DataPacket], trig_table: bool) -> usize {
+    let builder = FrameBuilder::new(Config::new_vlp_32c_strongest()).unwrap();
+    let mut builder = if trig_table {
+        builder.with_trig_table()
+    } else {
+        builder
+    };
+
+    let mut count = 0;
+    for packet in packets {
+        count += builder.push_packet(packet).len();
+    }
+    count + builder.finish().is_some() as usize
+}
+
+fn frame_builder(c: &mut Criterion) {
+    let packets = load_packets();
+    let num_points = packets.len() * 12 * 32;
+
+    let mut group = c.benchmark_group("frame_builder");
+    group.throughput(Throughput::Elements(num_points as u64));
+
+    for (name, trig_table) in [("direct", false), ("trig_table", true)] {
+        group.bench_function(name, |b| {
+            b.iter(|| build_frames(black_box(&packets), trig_table))
+        });
+    }
+    group.finish();
+}
+
+fn xyz_f32(c: &mut Criterion) {
+    let config = Config::new_vlp_32c_strongest();
+    let firings = load_firings(&load_packets(), &config);
+    let num_points = firings.len() * 32;
+
+    let mut group = c.benchmark_group("xyz_f32");
+    group.throughput(Throughput::Elements(num_points as u64));
+
+    for (name, precision) in [
+        ("exact", Precision::Exact),
+        ("approximate", Precision::Approximate),
+    ] {
+        let converter = XyzConverter::new(&config).with_precision(precision);
+
+        group.bench_function(format!("{name}_per_point"), |b| {
+            b.iter(|| {
+                let mut sum = 0.0;
+                for (azimuths, channels) in black_box(&firings) {
+                    for (laser_id, channel) in channels.iter().enumerate() {
+                        let azimuth = azimuths[laser_id] as f64;
+                        let ([x, y, z], _) = converter.xyz_f32(laser_id, channel, azimuth);
+                        sum += x + y + z;
+                    }
+                }
+                sum
+            })
+        });
+
+        group.bench_function(format!("{name}_per_firing"), |b| {
+            let mut xyz = [[0f32; 32]; 3];
+            b.iter(|| {
+                let mut sum = 0.0;
+                for (azimuths, channels) in black_box(&firings) {
+                    let [xs, ys, zs] = &mut xyz;
+                    converter.firing_xyz_f32(azimuths, channels, [xs, ys, zs]);
+                    sum += xs[0] + ys[0] + zs[0];
+                }
+                sum
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, frame_builder, xyz_f32);
+criterion_main!(benches);
@@ -0,0 +1,33 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use velodyne_lidar::{prelude::*, DataPacket, Packet};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(packet) = Packet::from_slice(data) {
+        assert_eq!(packet.as_bytes(), data);
+    }
+
+    let Ok(buffer) = data.try_into() else {
+        assert!(DataPacket::from_slice(data).is_err());
+        return;
+    };
+
+    // Unchecked packets must not panic either.
+    let packet = DataPacket::from_bytes(buffer);
+    assert_eq!(
+        packet.validate().is_ok(),
+        DataPacket::from_slice(data).is_ok()
+    );
+    assert_eq!(packet.as_bytes(), data);
+
+    if let Ok(firings) = packet.firing_block_iter() {
+        for firing in firings {
+            let _ = firing.azimuth_range();
+        }
+    }
+
+    let _ = packet.toh();
+    let _ = packet.status();
+    let _ = packet.try_format();
+});
@@ -0,0 +1,17 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use velodyne_lidar::{utc::parse_gprmc, PositionPacket};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(packet) = PositionPacket::from_slice(data) else {
+        return;
+    };
+    assert_eq!(packet.as_bytes(), data);
+
+    let _ = packet.toh();
+    let _ = packet.pps_status();
+    let _ = packet.thermal_status();
+    let _ = packet.last_adc_calibration_reason();
+    let _ = parse_gprmc(packet.nmea_sentence());
+});
@@ -1,42 +1,170 @@
 //! Group consecutive elements.
 
 use crate::{
-    traits::AzimuthRange,
+    error::ensure_argument,
+    traits::{AzimuthRange, TimeOfHour},
     types::{
-        firing_block::{FiringBlockD16, FiringBlockD32, FiringBlockS16, FiringBlockS32},
-        firing_xyz::{FiringXyzD16, FiringXyzD32, FiringXyzS16, FiringXyzS32},
-        format::FormatKind,
+        firing_block::{
+            FiringBlockD128, FiringBlockD16, FiringBlockD32, FiringBlockD64, FiringBlockS128,
+            FiringBlockS16, FiringBlockS32, FiringBlockS64,
+        },
+        firing_xyz::{
+            FiringXyzD128, FiringXyzD16, FiringXyzD32, FiringXyzD64, FiringXyzS128, FiringXyzS16,
+            FiringXyzS32, FiringXyzS64,
+        },
+        format::{Format, FormatKind},
     },
+    utils::AngleExt,
+    Error,
 };
-use std::mem;
+use measurements::Angle;
+use std::{mem, time::Duration};
+
+/// The rule deciding where a frame ends.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BatchStrategy {
+    /// Starts a new frame when the start azimuth crosses the angle.
+    ///
+    /// The default cuts at zero degrees, that is, behind the
+    /// sensor. Cutting at 180 degrees keeps the front view in one
+    /// piece.
+    Azimuth(Angle),
+    /// Starts a new frame at every multiple of the period since the
+    /// top of the hour.
+    Time(Duration),
+    /// Starts a new frame every fixed number of firings.
+    ///
+    /// Use [packet_count()](Self::packet_count) to count in packets.
+    FiringCount(usize),
+    /// Starts a new frame at every rotation of a phase-locked
+    /// sensor spinning at `rpm`.
+    ///
+    /// A phase-locked sensor points at its phase offset at the top
+    /// of each second and then once every `60 / rpm` seconds. The
+    /// frames are cut at these instants, so frames of multiple
+    /// sensors locked to the same PPS cover the same time spans.
+    ///
+    /// The cuts are computed from the time of hour alone and the
+    /// azimuth is ignored. Frames of a locked sensor start at its
+    /// phase offset, but a sensor that is not locked, for example one
+    /// without a PPS signal, is cut at the same instants and its
+    /// frames start at arbitrary azimuths.
+    PhaseLocked { rpm: u32 },
+}
+
+impl BatchStrategy {
+    /// Creates a strategy that starts a new frame every `count`
+    /// packets of the format.
+    pub fn packet_count(format: Format, count: usize) -> Self {
+        Self::FiringCount(format.firings_per_packet() * count)
+    }
+
+    pub(crate) fn check(&self) -> Result<(), Error> {
+        match *self {
+            Self::Azimuth(_) => {}
+            Self::Time(period) => {
+                ensure_argument!(!period.is_zero(), "the period must be non-zero")
+            }
+            Self::FiringCount(count) => {
+                ensure_argument!(count > 0, "the firing count must be non-zero")
+            }
+            Self::PhaseLocked { rpm } => ensure_argument!(rpm > 0, "the rpm must be non-zero"),
+        }
+        Ok(())
+    }
+
+    /// Checks if `curr` starts a new frame given the length and the
+    /// last element of the current frame.
+    fn is_cut<E>(&self, len: usize, prev: &E, curr: &E) -> bool
+    where
+        E: AzimuthRange + TimeOfHour,
+    {
+        self.is_cut_at(
+            len,
+            (prev.start_azimuth(), prev.toh()),
+            (curr.start_azimuth(), curr.toh()),
+        )
+    }
+
+    /// Checks if a firing starts a new frame given the length and the
+    /// (start azimuth, time of hour) pairs of the last and the current
+    /// firings.
+    pub(crate) fn is_cut_at(
+        &self,
+        len: usize,
+        (prev_azimuth, prev_toh): (Angle, Duration),
+        (curr_azimuth, curr_toh): (Angle, Duration),
+    ) -> bool {
+        match *self {
+            Self::Azimuth(cut) => {
+                let rel = |azimuth: Angle| (azimuth - cut).wrap_to_2pi();
+                rel(prev_azimuth) > rel(curr_azimuth)
+            }
+            Self::Time(period) => {
+                let slot = |toh: Duration| toh.as_nanos() / period.as_nanos();
+                slot(prev_toh) != slot(curr_toh)
+            }
+            Self::FiringCount(count) => len >= count,
+            Self::PhaseLocked { rpm } => {
+                let slot = |toh: Duration| {
+                    let rotation = toh.subsec_nanos() as u64 * rpm as u64 / 60_000_000_000;
+                    (toh.as_secs(), rotation)
+                };
+                slot(prev_toh) != slot(curr_toh)
+            }
+        }
+    }
+}
+
+impl Default for BatchStrategy {
+    fn default() -> Self {
+        Self::Azimuth(Angle::from_radians(0.0))
+    }
+}
 
 /// A helper that groups consecutive elements into frames according to
-/// their azimuth ranges.
+/// a [BatchStrategy].
 #[derive(Debug, Clone)]
 pub struct Batcher<E>
 where
-    E: AzimuthRange,
+    E: AzimuthRange + TimeOfHour,
 {
     buffer: Vec<E>,
+    strategy: BatchStrategy,
 }
 
 impl<E> Batcher<E>
 where
-    E: AzimuthRange,
+    E: AzimuthRange + TimeOfHour,
 {
-    /// Create a new instance.
+    /// Create a new instance that cuts frames at zero azimuth.
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// Create a new instance with a custom strategy.
+    pub fn with_strategy(strategy: BatchStrategy) -> Result<Self, Error> {
+        strategy.check()?;
+        Ok(Self {
+            buffer: vec![],
+            strategy,
+        })
+    }
+
+    pub fn strategy(&self) -> BatchStrategy {
+        self.strategy
+    }
+
     /// Pushes one element and returns a batch if the pushed element
-    /// is back aronud.
+    /// starts a new frame.
     pub fn push_one(&mut self, firing: E) -> Option<Vec<E>> {
         let buffer = &mut self.buffer;
-        let wrap =
-            matches!(buffer.last(), Some(prev) if prev.start_azimuth() > firing.start_azimuth());
+        let cut = matches!(
+            buffer.last(),
+            Some(prev) if self.strategy.is_cut(buffer.len(), prev, &firing)
+        );
 
-        if wrap {
+        if cut {
             let output = mem::replace(buffer, vec![firing]);
             Some(output)
         } else {
@@ -85,29 +213,238 @@ where
 
 impl<E> Default for Batcher<E>
 where
-    E: AzimuthRange,
+    E: AzimuthRange + TimeOfHour,
 {
     fn default() -> Self {
-        Self { buffer: vec![] }
+        Self {
+            buffer: vec![],
+            strategy: BatchStrategy::default(),
+        }
     }
 }
 
 pub type FiringBlockBatcherS16<'a> = Batcher<FiringBlockS16<'a>>;
 pub type FiringBlockBatcherS32<'a> = Batcher<FiringBlockS32<'a>>;
+pub type FiringBlockBatcherS64<'a> = Batcher<FiringBlockS64<'a>>;
+pub type FiringBlockBatcherS128<'a> = Batcher<FiringBlockS128<'a>>;
 pub type FiringBlockBatcherD16<'a> = Batcher<FiringBlockD16<'a>>;
 pub type FiringBlockBatcherD32<'a> = Batcher<FiringBlockD32<'a>>;
+pub type FiringBlockBatcherD64<'a> = Batcher<FiringBlockD64<'a>>;
+pub type FiringBlockBatcherD128<'a> = Batcher<FiringBlockD128<'a>>;
 
 pub type FiringBlockBatcher<'a> = FormatKind<
     FiringBlockBatcherS16<'a>,
     FiringBlockBatcherS32<'a>,
+    FiringBlockBatcherS64<'a>,
+    FiringBlockBatcherS128<'a>,
     FiringBlockBatcherD16<'a>,
     FiringBlockBatcherD32<'a>,
+    FiringBlockBatcherD64<'a>,
+    FiringBlockBatcherD128<'a>,
 >;
 
-pub type FiringXyzBatcher =
-    FormatKind<FiringXyzBatcherS16, FiringXyzBatcherS32, FiringXyzBatcherD16, FiringXyzBatcherD32>;
+pub type FiringXyzBatcher = FormatKind<
+    FiringXyzBatcherS16,
+    FiringXyzBatcherS32,
+    FiringXyzBatcherS64,
+    FiringXyzBatcherS128,
+    FiringXyzBatcherD16,
+    FiringXyzBatcherD32,
+    FiringXyzBatcherD64,
+    FiringXyzBatcherD128,
+>;
+
+impl<S16, S32, S64, S128, D16, D32, D64, D128>
+    FormatKind<
+        Batcher<S16>,
+        Batcher<S32>,
+        Batcher<S64>,
+        Batcher<S128>,
+        Batcher<D16>,
+        Batcher<D32>,
+        Batcher<D64>,
+        Batcher<D128>,
+    >
+where
+    S16: AzimuthRange + TimeOfHour,
+    S32: AzimuthRange + TimeOfHour,
+    S64: AzimuthRange + TimeOfHour,
+    S128: AzimuthRange + TimeOfHour,
+    D16: AzimuthRange + TimeOfHour,
+    D32: AzimuthRange + TimeOfHour,
+    D64: AzimuthRange + TimeOfHour,
+    D128: AzimuthRange + TimeOfHour,
+{
+    /// Creates a batcher for the format with a custom strategy.
+    pub fn from_format_with_strategy(
+        format: Format,
+        strategy: BatchStrategy,
+    ) -> Result<Self, Error> {
+        use Batcher as B;
+
+        Ok(match format {
+            Format::Single16 => Self::Single16(B::with_strategy(strategy)?),
+            Format::Single32 => Self::Single32(B::with_strategy(strategy)?),
+            Format::Single64 => Self::Single64(B::with_strategy(strategy)?),
+            Format::Single128 => Self::Single128(B::with_strategy(strategy)?),
+            Format::Dual16 => Self::Dual16(B::with_strategy(strategy)?),
+            Format::Dual32 => Self::Dual32(B::with_strategy(strategy)?),
+            Format::Dual64 => Self::Dual64(B::with_strategy(strategy)?),
+            Format::Dual128 => Self::Dual128(B::with_strategy(strategy)?),
+        })
+    }
+}
 
 pub type FiringXyzBatcherS16 = Batcher<FiringXyzS16>;
 pub type FiringXyzBatcherS32 = Batcher<FiringXyzS32>;
+pub type FiringXyzBatcherS64 = Batcher<FiringXyzS64>;
+pub type FiringXyzBatcherS128 = Batcher<FiringXyzS128>;
 pub type FiringXyzBatcherD16 = Batcher<FiringXyzD16>;
 pub type FiringXyzBatcherD32 = Batcher<FiringXyzD32>;
+pub type FiringXyzBatcherD64 = Batcher<FiringXyzD64>;
+pub type FiringXyzBatcherD128 = Batcher<FiringXyzD128>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packet::{Channel, DataPacket, DataPacketSynthesizer, ProductID, ReturnMode};
+
+    fn packets(rpm: f64, count: usize) -> Vec<DataPacket> {
+        DataPacketSynthesizer::new(ProductID::VLP16, ReturnMode::Strongest, rpm, |_, _| {
+            Channel {
+                distance: 1000,
+                intensity: 100,
+            }
+        })
+        .unwrap()
+        .start_azimuth_count(9000)
+        .take(count)
+        .collect()
+    }
+
+    fn batches(packets: &[DataPacket], strategy: BatchStrategy) -> Vec<Vec<FiringBlockS16<'_>>> {
+        let batcher = Batcher::with_strategy(strategy).unwrap();
+        let firings = packets
+            .iter()
+            .flat_map(|packet| packet.firing_block_iter_s16());
+        batcher.with_iter(firings).collect()
+    }
+
+    fn degrees(angle: Angle) -> f64 {
+        angle.wrap_to_2pi().as_degrees()
+    }
+
+    #[test]
+    fn cut_at_azimuth() {
+        let packets = packets(600.0, 300);
+
+        for cut in [0.0, 180.0, 270.0] {
+            let strategy = BatchStrategy::Azimuth(Angle::from_degrees(cut));
+            let frames = batches(&packets, strategy);
+            assert!(frames.len() > 3);
+
+            // complete frames start right after the cut angle
+            for frame in &frames[1..frames.len() - 1] {
+                let start = degrees(frame[0].start_azimuth() - Angle::from_degrees(cut));
+                assert!(start < 1.0, "cut {cut} starts at {start}");
+                assert!((1700..=1820).contains(&frame.len()));
+            }
+        }
+    }
+
+    #[test]
+    fn cut_by_time() {
+        let packets = packets(600.0, 300);
+        let period = Duration::from_millis(50);
+        let frames = batches(&packets, BatchStrategy::Time(period));
+
+        for frame in &frames {
+            let slot = frame[0].toh.as_nanos() / period.as_nanos();
+            assert!(frame
+                .iter()
+                .all(|f| f.toh.as_nanos() / period.as_nanos() == slot));
+        }
+        for frame in &frames[1..frames.len() - 1] {
+            assert!((800..=1000).contains(&frame.len()));
+        }
+    }
+
+    #[test]
+    fn cut_by_packet_count() {
+        let packets = packets(600.0, 100);
+        let strategy = BatchStrategy::packet_count(Format::Single16, 10);
+        let frames = batches(&packets, strategy);
+
+        assert_eq!(frames.len(), 10);
+        assert!(frames.iter().all(|frame| frame.len() == 240));
+    }
+
+    #[test]
+    fn cut_phase_locked() {
+        let packets = packets(900.0, 300);
+        let frames = batches(&packets, BatchStrategy::PhaseLocked { rpm: 900 });
+        let period = Duration::from_secs(60) / 900;
+
+        // boundaries fall on multiples of the rotation period
+        for pair in frames.windows(2) {
+            let boundary = pair[1][0].toh;
+            let prev = pair[0].last().unwrap().toh;
+            let offset = boundary.subsec_nanos() as u64 * 900 % 60_000_000_000;
+            assert!(offset < 60_000 * 900, "{boundary:?} is far from a boundary");
+            assert!(boundary - prev < Duration::from_micros(60));
+        }
+        for frame in &frames[1..frames.len() - 1] {
+            let span = frame.last().unwrap().toh - frame[0].toh;
+            assert!(span < period && period - span < Duration::from_micros(120));
+        }
+    }
+
+    #[test]
+    fn phase_locked_ignores_azimuth() {
+        let period = Duration::from_secs(60) / 900;
+        let start_azimuths = |start_toh: Duration| {
+            let packets: Vec<_> = DataPacketSynthesizer::new(
+                ProductID::VLP16,
+                ReturnMode::Strongest,
+                900.0,
+                |_, _| Channel {
+                    distance: 1000,
+                    intensity: 100,
+                },
+            )
+            .unwrap()
+            .start_toh(start_toh)
+            .take(300)
+            .collect();
+            let frames = batches(&packets, BatchStrategy::PhaseLocked { rpm: 900 });
+            let azimuths: Vec<_> = frames[1..frames.len() - 1]
+                .iter()
+                .map(|frame| degrees(frame[0].start_azimuth()))
+                .collect();
+            azimuths
+        };
+
+        // shifting the time by a quarter rotation shifts the cut
+        // azimuth by a quarter turn, since only the time is locked
+        let aligned = start_azimuths(Duration::ZERO);
+        let shifted = start_azimuths(period / 4);
+        assert!(aligned.len() > 3 && shifted.len() > 3);
+        for (aligned, shifted) in aligned.iter().zip(&shifted) {
+            let diff = (aligned - shifted).rem_euclid(360.0);
+            assert!((diff - 90.0).abs() < 1.0, "cut azimuths differ by {diff}");
+        }
+    }
+
+    #[test]
+    fn reject_invalid_strategy() {
+        assert!(FiringXyzBatcher::from_format_with_strategy(
+            Format::Single16,
+            BatchStrategy::Time(Duration::ZERO)
+        )
+        .is_err());
+        assert!(Batcher::<FiringXyzS32>::with_strategy(BatchStrategy::FiringCount(0)).is_err());
+        assert!(
+            Batcher::<FiringXyzS32>::with_strategy(BatchStrategy::PhaseLocked { rpm: 0 }).is_err()
+        );
+    }
+}
@@ -0,0 +1,14 @@
+//! Reading and writing packet captures without libpcap.
+
+mod decode;
+pub use decode::{link_type, Datagram, Decoded, FrameDecoder, SkipReason};
+
+mod reader;
+#[cfg(any(feature = "pcap", feature = "tokio"))]
+pub(crate) use reader::EventDecoder;
+pub use reader::*;
+#[cfg(feature = "tokio")]
+pub(crate) use reader::{file_header_len, FILE_PREFIX_LEN};
+
+mod writer;
+pub use writer::*;
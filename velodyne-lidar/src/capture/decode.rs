@@ -0,0 +1,620 @@
+//! Link layer, IP and UDP decoding of captured frames.
+
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    fmt,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+};
+
+/// Link-layer header types of captures.
+pub mod link_type {
+    pub const NULL: u32 = 0;
+    pub const ETHERNET: u32 = 1;
+    pub const RAW: u32 = 101;
+    pub const LOOP: u32 = 108;
+    pub const LINUX_SLL: u32 = 113;
+    pub const IPV4: u32 = 228;
+    pub const IPV6: u32 = 229;
+    pub const LINUX_SLL2: u32 = 276;
+}
+
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_IPV6: u16 = 0x86dd;
+const ETHERTYPE_VLAN: u16 = 0x8100;
+const ETHERTYPE_QINQ: u16 = 0x88a8;
+
+const IP_PROTOCOL_UDP: u8 = 17;
+const IPV6_HOP_BY_HOP: u8 = 0;
+const IPV6_ROUTING: u8 = 43;
+const IPV6_FRAGMENT: u8 = 44;
+const IPV6_DESTINATION: u8 = 60;
+
+/// Maximum number of datagrams waiting for missing fragments.
+const MAX_PENDING_DATAGRAMS: usize = 64;
+
+/// The reason a captured frame does not yield a packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SkipReason {
+    /// The link-layer header type is not supported.
+    UnsupportedLinkType(u32),
+    /// The frame carries neither IPv4 nor IPv6.
+    UnsupportedEtherType(u16),
+    /// The IP payload is not UDP. It holds the IP protocol number.
+    NotUdp(u8),
+    /// The frame is shorter than its headers claim.
+    Truncated,
+    /// Some fragments of the datagram never arrived.
+    IncompleteFragments,
+    /// A fragment lies past the end of its datagram. The datagram is
+    /// dropped.
+    InvalidFragment,
+    /// The UDP payload size matches neither data nor position packets.
+    InvalidPayloadSize(usize),
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedLinkType(link_type) => {
+                write!(f, "unsupported link type {link_type}")
+            }
+            Self::UnsupportedEtherType(ether_type) => {
+                write!(f, "unsupported EtherType 0x{ether_type:04x}")
+            }
+            Self::NotUdp(protocol) => write!(f, "IP protocol {protocol} is not UDP"),
+            Self::Truncated => write!(f, "truncated frame"),
+            Self::IncompleteFragments => write!(f, "incomplete IP fragments"),
+            Self::InvalidFragment => write!(f, "IP fragment past the end of the datagram"),
+            Self::InvalidPayloadSize(size) => {
+                write!(f, "UDP payload of {size} bytes is not a Velodyne packet")
+            }
+        }
+    }
+}
+
+/// A UDP datagram extracted from a frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Datagram {
+    pub source: SocketAddr,
+    pub destination: SocketAddr,
+    pub payload: Vec<u8>,
+}
+
+/// The outcome of decoding one frame.
+#[derive(Debug)]
+pub enum Decoded {
+    Datagram(Datagram),
+    /// The frame is a fragment of a datagram that is not complete yet.
+    Pending,
+    Skipped(SkipReason),
+}
+
+/// Decodes frames into UDP datagrams and reassembles IP fragments.
+///
+/// It works on payloads of any protocol. Use it with
+/// [CaptureReader](crate::capture::CaptureReader) to read UDP
+/// datagrams of other sensors from a capture.
+#[derive(Debug, Default)]
+pub struct FrameDecoder {
+    pending: HashMap<FragmentKey, Fragments>,
+    /// Keys of pending datagrams from the oldest to the newest.
+    order: VecDeque<FragmentKey>,
+    /// Datagrams dropped for missing fragments that are not reported yet.
+    dropped: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct FragmentKey {
+    source: IpAddr,
+    destination: IpAddr,
+    identification: u32,
+}
+
+#[derive(Debug, Default)]
+struct Fragments {
+    /// Fragment payloads by offset.
+    pieces: BTreeMap<usize, Vec<u8>>,
+    /// The total payload size, known once the last fragment arrives.
+    total_len: Option<usize>,
+}
+
+impl FrameDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decodes a frame with the given link-layer header type.
+    pub fn decode(&mut self, link_type: u32, frame: &[u8]) -> Decoded {
+        match self.decode_inner(link_type, frame) {
+            Ok(Some(datagram)) => Decoded::Datagram(datagram),
+            Ok(None) => Decoded::Pending,
+            Err(reason) => Decoded::Skipped(reason),
+        }
+    }
+
+    /// Takes the number of datagrams dropped for missing fragments
+    /// since the last call.
+    pub fn take_dropped(&mut self) -> usize {
+        std::mem::take(&mut self.dropped)
+    }
+
+    /// Drops all incomplete datagrams and returns their number.
+    pub fn finish(&mut self) -> usize {
+        let count = self.pending.len() + self.take_dropped();
+        self.pending.clear();
+        self.order.clear();
+        count
+    }
+
+    fn decode_inner(
+        &mut self,
+        link_type: u32,
+        frame: &[u8],
+    ) -> Result<Option<Datagram>, SkipReason> {
+        let (ether_type, network) = match link_type {
+            link_type::ETHERNET => ethernet(frame)?,
+            link_type::LINUX_SLL => {
+                let ether_type = read_u16(frame, 14)?;
+                (ether_type, slice_from(frame, 16)?)
+            }
+            link_type::LINUX_SLL2 => {
+                let ether_type = read_u16(frame, 0)?;
+                (ether_type, slice_from(frame, 20)?)
+            }
+            link_type::NULL | link_type::LOOP => {
+                let ether_type = match ip_version(slice_from(frame, 4)?)? {
+                    4 => ETHERTYPE_IPV4,
+                    _ => ETHERTYPE_IPV6,
+                };
+                (ether_type, slice_from(frame, 4)?)
+            }
+            link_type::RAW => {
+                let ether_type = match ip_version(frame)? {
+                    4 => ETHERTYPE_IPV4,
+                    _ => ETHERTYPE_IPV6,
+                };
+                (ether_type, frame)
+            }
+            link_type::IPV4 => (ETHERTYPE_IPV4, frame),
+            link_type::IPV6 => (ETHERTYPE_IPV6, frame),
+            _ => return Err(SkipReason::UnsupportedLinkType(link_type)),
+        };
+
+        let (source, destination, udp) = match ether_type {
+            ETHERTYPE_IPV4 => match self.ipv4(network)? {
+                Some(output) => output,
+                None => return Ok(None),
+            },
+            ETHERTYPE_IPV6 => match self.ipv6(network)? {
+                Some(output) => output,
+                None => return Ok(None),
+            },
+            _ => return Err(SkipReason::UnsupportedEtherType(ether_type)),
+        };
+
+        let source_port = read_u16(&udp, 0)?;
+        let destination_port = read_u16(&udp, 2)?;
+        let len = read_u16(&udp, 4)? as usize;
+        let payload = udp.get(8..len).ok_or(SkipReason::Truncated)?;
+
+        Ok(Some(Datagram {
+            source: SocketAddr::new(source, source_port),
+            destination: SocketAddr::new(destination, destination_port),
+            payload: payload.to_vec(),
+        }))
+    }
+
+    /// Decodes an IPv4 packet into the addresses and the UDP segment.
+    fn ipv4(&mut self, packet: &[u8]) -> Result<Option<(IpAddr, IpAddr, Vec<u8>)>, SkipReason> {
+        let header_len = (*packet.first().ok_or(SkipReason::Truncated)? & 0x0f) as usize * 4;
+        let total_len = read_u16(packet, 2)? as usize;
+        let identification = read_u16(packet, 4)? as u32;
+        let flags_offset = read_u16(packet, 6)?;
+        let protocol = *packet.get(9).ok_or(SkipReason::Truncated)?;
+        let source: Ipv4Addr = read_array::<4>(packet, 12)?.into();
+        let destination: Ipv4Addr = read_array::<4>(packet, 16)?.into();
+
+        if protocol != IP_PROTOCOL_UDP {
+            return Err(SkipReason::NotUdp(protocol));
+        }
+
+        // the frame may carry link-layer padding after the IP packet
+        let payload = packet
+            .get(header_len..total_len)
+            .ok_or(SkipReason::Truncated)?;
+        let more_fragments = flags_offset & 0x2000 != 0;
+        let offset = (flags_offset & 0x1fff) as usize * 8;
+
+        let key = FragmentKey {
+            source: source.into(),
+            destination: destination.into(),
+            identification,
+        };
+        let udp = self.reassemble(key, offset, more_fragments, payload)?;
+        Ok(udp.map(|udp| (source.into(), destination.into(), udp)))
+    }
+
+    /// Decodes an IPv6 packet into the addresses and the UDP segment.
+    fn ipv6(&mut self, packet: &[u8]) -> Result<Option<(IpAddr, IpAddr, Vec<u8>)>, SkipReason> {
+        let payload_len = read_u16(packet, 4)? as usize;
+        let mut next_header = *packet.get(6).ok_or(SkipReason::Truncated)?;
+        let source: Ipv6Addr = read_array::<16>(packet, 8)?.into();
+        let destination: Ipv6Addr = read_array::<16>(packet, 24)?.into();
+        let mut payload = packet
+            .get(40..40 + payload_len)
+            .ok_or(SkipReason::Truncated)?;
+        let mut fragment = None;
+
+        loop {
+            match next_header {
+                IP_PROTOCOL_UDP => break,
+                IPV6_HOP_BY_HOP | IPV6_ROUTING | IPV6_DESTINATION => {
+                    let len = (*payload.get(1).ok_or(SkipReason::Truncated)? as usize + 1) * 8;
+                    next_header = payload[0];
+                    payload = slice_from(payload, len)?;
+                }
+                IPV6_FRAGMENT => {
+                    let offset_flags = read_u16(payload, 2)?;
+                    let identification = u32::from_be_bytes(read_array::<4>(payload, 4)?);
+                    fragment = Some((
+                        (offset_flags & 0xfff8) as usize,
+                        offset_flags & 0x0001 != 0,
+                        identification,
+                    ));
+                    next_header = payload[0];
+                    payload = slice_from(payload, 8)?;
+                }
+                protocol => return Err(SkipReason::NotUdp(protocol)),
+            }
+        }
+
+        let udp = match fragment {
+            Some((offset, more_fragments, identification)) => {
+                let key = FragmentKey {
+                    source: source.into(),
+                    destination: destination.into(),
+                    identification,
+                };
+                self.reassemble(key, offset, more_fragments, payload)?
+            }
+            None => Some(payload.to_vec()),
+        };
+        Ok(udp.map(|udp| (source.into(), destination.into(), udp)))
+    }
+
+    /// Stores a fragment and returns the whole payload once all
+    /// fragments arrive.
+    fn reassemble(
+        &mut self,
+        key: FragmentKey,
+        offset: usize,
+        more_fragments: bool,
+        payload: &[u8],
+    ) -> Result<Option<Vec<u8>>, SkipReason> {
+        if offset == 0 && !more_fragments {
+            return Ok(Some(payload.to_vec()));
+        }
+
+        if !self.pending.contains_key(&key) {
+            if self.pending.len() >= MAX_PENDING_DATAGRAMS {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.pending.remove(&oldest);
+                    self.dropped += 1;
+                }
+            }
+            self.order.push_back(key);
+        }
+
+        let fragments = self.pending.entry(key).or_default();
+        let end = offset + payload.len();
+        // the last fragment may not change the size, and no fragment
+        // may lie past the end
+        let total_len = match more_fragments {
+            true => fragments.total_len,
+            false => Some(end),
+        };
+        let resized = !more_fragments && fragments.total_len.is_some_and(|len| len != end);
+        let past_end = total_len.is_some_and(|total_len| {
+            end > total_len
+                || fragments
+                    .pieces
+                    .iter()
+                    .any(|(&offset, piece)| offset + piece.len() > total_len)
+        });
+        if resized || past_end {
+            self.pending.remove(&key);
+            self.order.retain(|pending| *pending != key);
+            return Err(SkipReason::InvalidFragment);
+        }
+
+        fragments.pieces.insert(offset, payload.to_vec());
+        fragments.total_len = total_len;
+        let Some(total_len) = total_len else {
+            return Ok(None);
+        };
+
+        let mut covered = 0;
+        for (&offset, piece) in &fragments.pieces {
+            if offset > covered {
+                return Ok(None);
+            }
+            covered = covered.max(offset + piece.len());
+        }
+        if covered < total_len {
+            return Ok(None);
+        }
+
+        let Some(fragments) = self.pending.remove(&key) else {
+            return Ok(None);
+        };
+        self.order.retain(|pending| *pending != key);
+
+        let mut whole = vec![0; total_len];
+        for (offset, piece) in fragments.pieces {
+            whole[offset..offset + piece.len()].copy_from_slice(&piece);
+        }
+        Ok(Some(whole))
+    }
+}
+
+/// Strips the Ethernet header and VLAN tags.
+fn ethernet(frame: &[u8]) -> Result<(u16, &[u8]), SkipReason> {
+    let mut ether_type = read_u16(frame, 12)?;
+    let mut offset = 14;
+
+    while matches!(ether_type, ETHERTYPE_VLAN | ETHERTYPE_QINQ) {
+        ether_type = read_u16(frame, offset + 2)?;
+        offset += 4;
+    }
+
+    Ok((ether_type, slice_from(frame, offset)?))
+}
+
+fn ip_version(packet: &[u8]) -> Result<u8, SkipReason> {
+    match packet.first().ok_or(SkipReason::Truncated)? >> 4 {
+        4 => Ok(4),
+        6 => Ok(6),
+        _ => Err(SkipReason::UnsupportedEtherType(0)),
+    }
+}
+
+fn slice_from(bytes: &[u8], offset: usize) -> Result<&[u8], SkipReason> {
+    bytes.get(offset..).ok_or(SkipReason::Truncated)
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Result<u16, SkipReason> {
+    Ok(u16::from_be_bytes(read_array::<2>(bytes, offset)?))
+}
+
+fn read_array<const N: usize>(bytes: &[u8], offset: usize) -> Result<[u8; N], SkipReason> {
+    let slice = bytes.get(offset..offset + N).ok_or(SkipReason::Truncated)?;
+    Ok(slice.try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn udp(source_port: u16, destination_port: u16, payload: &[u8]) -> Vec<u8> {
+        let mut segment = vec![];
+        segment.extend(source_port.to_be_bytes());
+        segment.extend(destination_port.to_be_bytes());
+        segment.extend((8 + payload.len() as u16).to_be_bytes());
+        segment.extend([0, 0]);
+        segment.extend(payload);
+        segment
+    }
+
+    fn ipv4(identification: u16, flags_offset: u16, payload: &[u8]) -> Vec<u8> {
+        let mut packet = vec![0x45, 0];
+        packet.extend((20 + payload.len() as u16).to_be_bytes());
+        packet.extend(identification.to_be_bytes());
+        packet.extend(flags_offset.to_be_bytes());
+        packet.extend([64, IP_PROTOCOL_UDP, 0, 0]);
+        packet.extend([192, 168, 1, 201]);
+        packet.extend([255, 255, 255, 255]);
+        packet.extend(payload);
+        packet
+    }
+
+    fn ipv6(extensions: &[u8], first_header: u8, payload: &[u8]) -> Vec<u8> {
+        let mut packet = vec![0x60, 0, 0, 0];
+        packet.extend(((extensions.len() + payload.len()) as u16).to_be_bytes());
+        packet.extend([first_header, 64]);
+        packet.extend(Ipv6Addr::LOCALHOST.octets());
+        packet.extend(Ipv6Addr::LOCALHOST.octets());
+        packet.extend(extensions);
+        packet.extend(payload);
+        packet
+    }
+
+    fn ethernet(ether_types: &[u16], payload: &[u8]) -> Vec<u8> {
+        let mut frame = vec![0xff; 6];
+        frame.extend([0x60, 0x76, 0x88, 0, 0, 0]);
+        for (index, ether_type) in ether_types.iter().enumerate() {
+            frame.extend(ether_type.to_be_bytes());
+            if index + 1 < ether_types.len() {
+                frame.extend([0x00, 0x01]); // VLAN ID
+            }
+        }
+        frame.extend(payload);
+        frame
+    }
+
+    fn expect_datagram(decoded: Decoded) -> Datagram {
+        match decoded {
+            Decoded::Datagram(datagram) => datagram,
+            other => panic!("expect a datagram, but get {other:?}"),
+        }
+    }
+
+    #[test]
+    fn decode_ethernet_with_vlan_tags() {
+        let mut decoder = FrameDecoder::new();
+        let packet = ipv4(0, 0x4000, &udp(2368, 2368, b"payload"));
+
+        for ether_types in [
+            &[ETHERTYPE_IPV4][..],
+            &[ETHERTYPE_VLAN, ETHERTYPE_IPV4],
+            &[ETHERTYPE_QINQ, ETHERTYPE_VLAN, ETHERTYPE_IPV4],
+        ] {
+            let mut frame = ethernet(ether_types, &packet);
+            // link-layer padding is ignored
+            frame.extend([0; 4]);
+
+            let datagram = expect_datagram(decoder.decode(link_type::ETHERNET, &frame));
+            assert_eq!(datagram.source, "192.168.1.201:2368".parse().unwrap());
+            assert_eq!(datagram.payload, b"payload");
+        }
+    }
+
+    #[test]
+    fn decode_linux_cooked_and_raw() {
+        let mut decoder = FrameDecoder::new();
+        let packet = ipv4(0, 0, &udp(2368, 2368, b"payload"));
+
+        let mut sll = vec![0; 14];
+        sll.extend(ETHERTYPE_IPV4.to_be_bytes());
+        sll.extend(&packet);
+        let datagram = expect_datagram(decoder.decode(link_type::LINUX_SLL, &sll));
+        assert_eq!(datagram.payload, b"payload");
+
+        let mut sll2 = ETHERTYPE_IPV4.to_be_bytes().to_vec();
+        sll2.extend([0; 18]);
+        sll2.extend(&packet);
+        let datagram = expect_datagram(decoder.decode(link_type::LINUX_SLL2, &sll2));
+        assert_eq!(datagram.payload, b"payload");
+
+        let datagram = expect_datagram(decoder.decode(link_type::RAW, &packet));
+        assert_eq!(datagram.payload, b"payload");
+    }
+
+    #[test]
+    fn decode_ipv6_with_extensions() {
+        let mut decoder = FrameDecoder::new();
+        let segment = udp(2368, 2368, b"payload");
+
+        // a hop-by-hop options header precedes UDP
+        let mut extensions = vec![IP_PROTOCOL_UDP, 0];
+        extensions.extend([0; 6]);
+        let packet = ipv6(&extensions, IPV6_HOP_BY_HOP, &segment);
+
+        let frame = ethernet(&[ETHERTYPE_IPV6], &packet);
+        let datagram = expect_datagram(decoder.decode(link_type::ETHERNET, &frame));
+        assert_eq!(datagram.source, "[::1]:2368".parse().unwrap());
+        assert_eq!(datagram.payload, b"payload");
+    }
+
+    #[test]
+    fn reassemble_fragments() {
+        let mut decoder = FrameDecoder::new();
+        let payload: Vec<u8> = (0..100).collect();
+        let segment = udp(2368, 2368, &payload);
+        let (first, second) = segment.split_at(56);
+
+        // IPv4 fragments arriving out of order
+        let frame = ethernet(&[ETHERTYPE_IPV4], &ipv4(7, 56 / 8, second));
+        assert!(matches!(
+            decoder.decode(link_type::ETHERNET, &frame),
+            Decoded::Pending
+        ));
+        let frame = ethernet(&[ETHERTYPE_IPV4], &ipv4(7, 0x2000, first));
+        let datagram = expect_datagram(decoder.decode(link_type::ETHERNET, &frame));
+        assert_eq!(datagram.payload, payload);
+
+        // IPv6 fragments
+        let fragment_header = |offset: u16, more: bool| {
+            let mut header = vec![IP_PROTOCOL_UDP, 0];
+            header.extend((offset | more as u16).to_be_bytes());
+            header.extend(9u32.to_be_bytes());
+            header
+        };
+        let frame = ethernet(
+            &[ETHERTYPE_IPV6],
+            &ipv6(&fragment_header(0, true), IPV6_FRAGMENT, first),
+        );
+        assert!(matches!(
+            decoder.decode(link_type::ETHERNET, &frame),
+            Decoded::Pending
+        ));
+        let frame = ethernet(
+            &[ETHERTYPE_IPV6],
+            &ipv6(&fragment_header(56, false), IPV6_FRAGMENT, second),
+        );
+        let datagram = expect_datagram(decoder.decode(link_type::ETHERNET, &frame));
+        assert_eq!(datagram.payload, payload);
+
+        // an incomplete datagram is reported when finished
+        let frame = ethernet(&[ETHERTYPE_IPV4], &ipv4(8, 0x2000, first));
+        decoder.decode(link_type::ETHERNET, &frame);
+        assert_eq!(decoder.finish(), 1);
+    }
+
+    #[test]
+    fn reject_fragments_past_end() {
+        let mut decoder = FrameDecoder::new();
+        let payload: Vec<u8> = (0..100).collect();
+        let segment = udp(2368, 2368, &payload);
+        let (_, second) = segment.split_at(56);
+        let long = [&segment[..], &[0; 12]].concat();
+
+        // the last fragment ends before the stored ones
+        for (flags_offset, fragment) in [(0x2000, &long[..]), (0x2000 | (112 / 8), &[0; 8])] {
+            let frame = ethernet(&[ETHERTYPE_IPV4], &ipv4(7, flags_offset, fragment));
+            assert!(matches!(
+                decoder.decode(link_type::ETHERNET, &frame),
+                Decoded::Pending
+            ));
+        }
+        let frame = ethernet(&[ETHERTYPE_IPV4], &ipv4(7, 56 / 8, second));
+        assert!(matches!(
+            decoder.decode(link_type::ETHERNET, &frame),
+            Decoded::Skipped(SkipReason::InvalidFragment)
+        ));
+
+        // a fragment arrives past the end of the last fragment
+        let frame = ethernet(&[ETHERTYPE_IPV4], &ipv4(8, 56 / 8, second));
+        assert!(matches!(
+            decoder.decode(link_type::ETHERNET, &frame),
+            Decoded::Pending
+        ));
+        let frame = ethernet(&[ETHERTYPE_IPV4], &ipv4(8, 0x2000 | (112 / 8), &[0; 8]));
+        assert!(matches!(
+            decoder.decode(link_type::ETHERNET, &frame),
+            Decoded::Skipped(SkipReason::InvalidFragment)
+        ));
+        assert_eq!(decoder.finish(), 0);
+    }
+
+    #[test]
+    fn report_skip_reasons() {
+        let mut decoder = FrameDecoder::new();
+        let skipped = |decoded| match decoded {
+            Decoded::Skipped(reason) => reason,
+            other => panic!("expect a skipped frame, but get {other:?}"),
+        };
+
+        assert_eq!(
+            skipped(decoder.decode(147, &[0; 64])),
+            SkipReason::UnsupportedLinkType(147)
+        );
+        assert_eq!(
+            skipped(decoder.decode(link_type::ETHERNET, &ethernet(&[0x0806], &[0; 28]))),
+            SkipReason::UnsupportedEtherType(0x0806)
+        );
+
+        let mut packet = ipv4(0, 0, &[0; 20]);
+        packet[9] = 6;
+        let frame = ethernet(&[ETHERTYPE_IPV4], &packet);
+        assert_eq!(
+            skipped(decoder.decode(link_type::ETHERNET, &frame)),
+            SkipReason::NotUdp(6)
+        );
+
+        let frame = ethernet(&[ETHERTYPE_IPV4], &ipv4(0, 0, &udp(1, 2, &[0; 16])));
+        assert_eq!(
+            skipped(decoder.decode(link_type::ETHERNET, &frame[..40])),
+            SkipReason::Truncated
+        );
+    }
+}
@@ -0,0 +1,869 @@
+//! Readers of pcap and pcapng files.
+
+use super::decode::{Decoded, FrameDecoder, SkipReason};
+use crate::{
+    iter::{
+        try_packet_to_frame_xyz, try_packet_to_frame_xyz_auto, FormatChangePolicy, ReceivedPacket,
+        ResultFrameXyzIter,
+    },
+    Config, Error, Packet,
+};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufReader, Read},
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const PCAP_MAGIC_MICROS: u32 = 0xa1b2c3d4;
+const PCAP_MAGIC_NANOS: u32 = 0xa1b23c4d;
+const PCAPNG_SECTION_HEADER_BLOCK: u32 = 0x0a0d0d0a;
+const PCAPNG_INTERFACE_DESCRIPTION_BLOCK: u32 = 0x00000001;
+const PCAPNG_PACKET_BLOCK: u32 = 0x00000002;
+const PCAPNG_SIMPLE_PACKET_BLOCK: u32 = 0x00000003;
+const PCAPNG_ENHANCED_PACKET_BLOCK: u32 = 0x00000006;
+const PCAPNG_BYTE_ORDER_MAGIC: u32 = 0x1a2b3c4d;
+const PCAPNG_OPTION_END: u16 = 0;
+const PCAPNG_OPTION_TIMESTAMP_RESOLUTION: u16 = 9;
+
+/// Maximum size of a record or a block, which guards against
+/// allocating huge buffers for corrupted files.
+const MAX_RECORD_SIZE: usize = 16 * 1024 * 1024;
+
+/// A frame stored in a capture.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptureRecord {
+    /// The capture time.
+    pub timestamp: SystemTime,
+    /// The link-layer header type.
+    pub link_type: u32,
+    pub data: Vec<u8>,
+}
+
+/// Reads frames from a pcap or pcapng file.
+///
+/// The format and the byte order are detected from the file header.
+#[derive(Debug)]
+pub struct CaptureReader<R>
+where
+    R: Read,
+{
+    reader: R,
+    format: FileFormat,
+}
+
+#[derive(Debug)]
+enum FileFormat {
+    Pcap {
+        little_endian: bool,
+        nanos: bool,
+        link_type: u32,
+    },
+    PcapNg {
+        little_endian: bool,
+        interfaces: Vec<Interface>,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Interface {
+    link_type: u32,
+    /// Number of timestamp units per second.
+    units_per_sec: u64,
+}
+
+impl CaptureReader<BufReader<File>> {
+    /// Opens a capture file.
+    pub fn open<P>(path: P) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let file = File::open(path)?;
+        Self::new(BufReader::new(file))
+    }
+}
+
+impl<R> CaptureReader<R>
+where
+    R: Read,
+{
+    /// Reads the file header from `reader`.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+
+        let format = if u32::from_le_bytes(magic) == PCAPNG_SECTION_HEADER_BLOCK {
+            let mut format = FileFormat::PcapNg {
+                little_endian: true,
+                interfaces: vec![],
+            };
+            read_section_header(&mut reader, &mut format)?;
+            format
+        } else {
+            let (little_endian, nanos) =
+                match (u32::from_le_bytes(magic), u32::from_be_bytes(magic)) {
+                    (PCAP_MAGIC_MICROS, _) => (true, false),
+                    (PCAP_MAGIC_NANOS, _) => (true, true),
+                    (_, PCAP_MAGIC_MICROS) => (false, false),
+                    (_, PCAP_MAGIC_NANOS) => (false, true),
+                    _ => return Err(invalid_data("not a pcap or pcapng file".to_string())),
+                };
+
+            let mut header = [0u8; 20];
+            reader.read_exact(&mut header)?;
+            let link_type = read_u32(&header, 16, little_endian);
+
+            FileFormat::Pcap {
+                little_endian,
+                nanos,
+                link_type,
+            }
+        };
+
+        Ok(Self { reader, format })
+    }
+
+    /// Reads the next frame, or returns `None` at the end of file.
+    pub fn next_record(&mut self) -> io::Result<Option<CaptureRecord>> {
+        match &mut self.format {
+            FileFormat::Pcap {
+                little_endian,
+                nanos,
+                link_type,
+            } => {
+                let little_endian = *little_endian;
+                let mut header = [0u8; 16];
+                if !read_or_eof(&mut self.reader, &mut header)? {
+                    return Ok(None);
+                }
+
+                let secs = read_u32(&header, 0, little_endian) as u64;
+                let frac = read_u32(&header, 4, little_endian) as u64;
+                let len = read_u32(&header, 8, little_endian) as usize;
+                let frac = if *nanos {
+                    Duration::from_nanos(frac)
+                } else {
+                    Duration::from_micros(frac)
+                };
+
+                let data = read_vec(&mut self.reader, len)?;
+                Ok(Some(CaptureRecord {
+                    timestamp: UNIX_EPOCH + Duration::from_secs(secs) + frac,
+                    link_type: *link_type,
+                    data,
+                }))
+            }
+            FileFormat::PcapNg { .. } => loop {
+                let mut header = [0u8; 8];
+                if !read_or_eof(&mut self.reader, &mut header)? {
+                    return Ok(None);
+                }
+
+                let FileFormat::PcapNg {
+                    little_endian,
+                    interfaces,
+                } = &mut self.format
+                else {
+                    unreachable!();
+                };
+                let little_endian = *little_endian;
+
+                let block_type = read_u32(&header, 0, little_endian);
+                if block_type == PCAPNG_SECTION_HEADER_BLOCK {
+                    // a new section restarts the interface list
+                    read_section_header_body(&mut self.reader, &mut self.format, header)?;
+                    continue;
+                }
+
+                let total_len = read_u32(&header, 4, little_endian) as usize;
+                if total_len < 12 || !total_len.is_multiple_of(4) {
+                    return Err(invalid_data(format!("invalid block length {total_len}")));
+                }
+                let body = read_vec(&mut self.reader, total_len - 8)?;
+                let body = &body[..total_len - 12];
+
+                match block_type {
+                    PCAPNG_INTERFACE_DESCRIPTION_BLOCK => {
+                        interfaces.push(read_interface(body, little_endian)?);
+                    }
+                    PCAPNG_ENHANCED_PACKET_BLOCK | PCAPNG_PACKET_BLOCK => {
+                        // the obsolete packet block has a 16-bit
+                        // interface ID followed by a drop count
+                        let interface_id = if block_type == PCAPNG_ENHANCED_PACKET_BLOCK {
+                            read_u32(body, 0, little_endian) as usize
+                        } else {
+                            read_u16(body, 0, little_endian) as usize
+                        };
+                        let interface = interfaces.get(interface_id).ok_or_else(|| {
+                            invalid_data(format!("unknown interface {interface_id}"))
+                        })?;
+
+                        let high = read_u32(body, 4, little_endian) as u64;
+                        let low = read_u32(body, 8, little_endian) as u64;
+                        let len = read_u32(body, 12, little_endian) as usize;
+                        let data = body
+                            .get(20..20 + len)
+                            .ok_or_else(|| invalid_data("truncated packet block".to_string()))?;
+
+                        let units = (high << 32) | low;
+                        let timestamp = units_to_time(units, interface.units_per_sec);
+
+                        return Ok(Some(CaptureRecord {
+                            timestamp,
+                            link_type: interface.link_type,
+                            data: data.to_vec(),
+                        }));
+                    }
+                    PCAPNG_SIMPLE_PACKET_BLOCK => {
+                        let interface = interfaces
+                            .first()
+                            .ok_or_else(|| invalid_data("unknown interface 0".to_string()))?;
+                        let len = read_u32(body, 0, little_endian) as usize;
+                        let data = body.get(4..4 + len).unwrap_or(&body[4.min(body.len())..]);
+
+                        // simple packet blocks carry no timestamp
+                        return Ok(Some(CaptureRecord {
+                            timestamp: UNIX_EPOCH,
+                            link_type: interface.link_type,
+                            data: data.to_vec(),
+                        }));
+                    }
+                    _ => continue,
+                }
+            },
+        }
+    }
+}
+
+/// Framing of records for readers that buffer whole records before
+/// parsing them, such as the async file streams.
+#[cfg(feature = "tokio")]
+impl<R> CaptureReader<R>
+where
+    R: Read,
+{
+    /// Gets the number of leading bytes of a record or a block that
+    /// determine its size.
+    pub(crate) fn record_prefix_len(&self) -> usize {
+        match self.format {
+            FileFormat::Pcap { .. } => 16,
+            // every block has a trailing length, so it spans at least
+            // 12 bytes
+            FileFormat::PcapNg { .. } => 12,
+        }
+    }
+
+    /// Gets the size of a record or a block including the header from
+    /// its leading bytes.
+    pub(crate) fn record_len(&self, prefix: &[u8]) -> io::Result<usize> {
+        let len = match self.format {
+            FileFormat::Pcap { little_endian, .. } => {
+                16 + read_u32(prefix, 8, little_endian) as usize
+            }
+            FileFormat::PcapNg { little_endian, .. } => {
+                let block_type = read_u32(prefix, 0, little_endian);
+                let little_endian = if block_type == PCAPNG_SECTION_HEADER_BLOCK {
+                    section_byte_order(prefix)?
+                } else {
+                    little_endian
+                };
+                let total_len = read_u32(prefix, 4, little_endian) as usize;
+                if total_len < 12 {
+                    return Err(invalid_data(format!("invalid block length {total_len}")));
+                }
+                total_len
+            }
+        };
+
+        if len > MAX_RECORD_SIZE {
+            return Err(invalid_data(format!("record size {len} is too large")));
+        }
+        Ok(len)
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+}
+
+/// The number of leading bytes of a file that determine the size of
+/// the file header.
+#[cfg(feature = "tokio")]
+pub(crate) const FILE_PREFIX_LEN: usize = 12;
+
+/// Gets the size of the file header from the leading bytes of a file.
+#[cfg(feature = "tokio")]
+pub(crate) fn file_header_len(prefix: &[u8; FILE_PREFIX_LEN]) -> io::Result<usize> {
+    let magic = read_u32(prefix, 0, true);
+    if magic == PCAPNG_SECTION_HEADER_BLOCK {
+        let little_endian = section_byte_order(prefix)?;
+        let len = read_u32(prefix, 4, little_endian) as usize;
+        if !(28..=MAX_RECORD_SIZE).contains(&len) {
+            return Err(invalid_data(format!("invalid block length {len}")));
+        }
+        return Ok(len);
+    }
+
+    match (magic, magic.swap_bytes()) {
+        (PCAP_MAGIC_MICROS | PCAP_MAGIC_NANOS, _) | (_, PCAP_MAGIC_MICROS | PCAP_MAGIC_NANOS) => {
+            Ok(24)
+        }
+        _ => Err(invalid_data("not a pcap or pcapng file".to_string())),
+    }
+}
+
+impl<R> Iterator for CaptureReader<R>
+where
+    R: Read,
+{
+    type Item = io::Result<CaptureRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_record().transpose()
+    }
+}
+
+/// A frame in a capture that does not yield a packet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedFrame {
+    /// The capture time, or `None` for datagrams dropped for missing
+    /// fragments.
+    pub timestamp: Option<SystemTime>,
+    pub reason: SkipReason,
+}
+
+/// An item read by [PacketReader].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaptureEvent {
+    Packet(ReceivedPacket),
+    Skipped(SkippedFrame),
+}
+
+/// Reads Velodyne packets from a pcap or pcapng file.
+///
+/// Frames are decoded down to UDP payloads, including 802.1Q VLAN
+/// tags, Linux cooked headers, IPv6 and fragmented IP datagrams.
+/// Every frame that does not yield a packet is reported with a
+/// [SkipReason].
+#[derive(Debug)]
+pub struct PacketReader<R>
+where
+    R: Read,
+{
+    records: CaptureReader<R>,
+    events: EventDecoder,
+}
+
+impl PacketReader<BufReader<File>> {
+    /// Opens a capture file.
+    pub fn open<P>(path: P) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        Ok(Self::from_capture_reader(CaptureReader::open(path)?))
+    }
+}
+
+impl<R> PacketReader<R>
+where
+    R: Read,
+{
+    /// Reads the file header from `reader`.
+    pub fn new(reader: R) -> io::Result<Self> {
+        Ok(Self::from_capture_reader(CaptureReader::new(reader)?))
+    }
+
+    pub fn from_capture_reader(records: CaptureReader<R>) -> Self {
+        Self {
+            records,
+            events: EventDecoder::new(),
+        }
+    }
+
+    /// Gets the number of skipped frames for each reason so far.
+    pub fn skip_counts(&self) -> &HashMap<SkipReason, usize> {
+        self.events.skip_counts()
+    }
+
+    /// Iterates over packets and drops skipped frames.
+    ///
+    /// The skipped frames are still counted in
+    /// [skip_counts()](Self::skip_counts).
+    pub fn packets(self) -> impl Iterator<Item = io::Result<ReceivedPacket>> {
+        self.filter_map(|event| match event {
+            Ok(CaptureEvent::Packet(packet)) => Some(Ok(packet)),
+            Ok(CaptureEvent::Skipped(_)) => None,
+            Err(err) => Some(Err(err)),
+        })
+    }
+
+    fn next_event(&mut self) -> io::Result<Option<CaptureEvent>> {
+        loop {
+            if let Some(event) = self.events.take_dropped() {
+                return Ok(Some(event));
+            }
+
+            let Some(record) = self.records.next_record()? else {
+                return Ok(self.events.finish());
+            };
+
+            let event = self
+                .events
+                .decode(record.link_type, &record.data, record.timestamp);
+            if let Some(event) = event {
+                return Ok(Some(event));
+            }
+        }
+    }
+}
+
+impl<R> Iterator for PacketReader<R>
+where
+    R: Read,
+{
+    type Item = io::Result<CaptureEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_event().transpose()
+    }
+}
+
+/// Turns captured frames into [CaptureEvent]s and counts the skipped
+/// frames.
+///
+/// It is shared by the readers of capture files and of libpcap
+/// captures.
+#[derive(Debug)]
+pub(crate) struct EventDecoder {
+    decoder: FrameDecoder,
+    skip_counts: HashMap<SkipReason, usize>,
+    finished: bool,
+}
+
+impl EventDecoder {
+    pub fn new() -> Self {
+        Self {
+            decoder: FrameDecoder::new(),
+            skip_counts: HashMap::new(),
+            finished: false,
+        }
+    }
+
+    pub fn skip_counts(&self) -> &HashMap<SkipReason, usize> {
+        &self.skip_counts
+    }
+
+    /// Reports the datagrams dropped for missing fragments since the
+    /// last call.
+    pub fn take_dropped(&mut self) -> Option<CaptureEvent> {
+        let dropped = self.decoder.take_dropped();
+        (dropped > 0).then(|| self.skip(None, SkipReason::IncompleteFragments, dropped))
+    }
+
+    /// Decodes a frame, or returns `None` if it is a fragment of an
+    /// incomplete datagram.
+    pub fn decode(
+        &mut self,
+        link_type: u32,
+        data: &[u8],
+        timestamp: SystemTime,
+    ) -> Option<CaptureEvent> {
+        let datagram = match self.decoder.decode(link_type, data) {
+            Decoded::Datagram(datagram) => datagram,
+            Decoded::Pending => return None,
+            Decoded::Skipped(reason) => return Some(self.skip(Some(timestamp), reason, 1)),
+        };
+
+        let Ok(packet) = Packet::from_slice(&datagram.payload) else {
+            let reason = SkipReason::InvalidPayloadSize(datagram.payload.len());
+            return Some(self.skip(Some(timestamp), reason, 1));
+        };
+
+        Some(CaptureEvent::Packet(ReceivedPacket {
+            packet,
+            source: datagram.source,
+            received_at: timestamp,
+        }))
+    }
+
+    /// Reports the datagrams still waiting for fragments at the end of
+    /// the capture. It returns `None` after the first call.
+    pub fn finish(&mut self) -> Option<CaptureEvent> {
+        if self.finished {
+            return None;
+        }
+        self.finished = true;
+
+        let incomplete = self.decoder.finish();
+        (incomplete > 0).then(|| self.skip(None, SkipReason::IncompleteFragments, incomplete))
+    }
+
+    fn skip(
+        &mut self,
+        timestamp: Option<SystemTime>,
+        reason: SkipReason,
+        count: usize,
+    ) -> CaptureEvent {
+        *self.skip_counts.entry(reason).or_default() += count;
+        CaptureEvent::Skipped(SkippedFrame { timestamp, reason })
+    }
+}
+
+/// Creates a packet iterator by loading from a pcap or pcapng file,
+/// keeping the source address and the capture time of each packet.
+///
+/// Skipped frames are dropped. Use [PacketReader] to inspect them.
+pub fn received_packet_iter_from_file<P>(
+    path: P,
+) -> io::Result<impl Iterator<Item = io::Result<ReceivedPacket>> + Send>
+where
+    P: AsRef<Path>,
+{
+    Ok(PacketReader::open(path)?.packets())
+}
+
+/// Creates a packet iterator by loading from a pcap or pcapng file.
+///
+/// Skipped frames are dropped. Use [PacketReader] to inspect them.
+pub fn packet_iter_from_file<P>(
+    path: P,
+) -> io::Result<impl Iterator<Item = io::Result<Packet>> + Send>
+where
+    P: AsRef<Path>,
+{
+    let reader = PacketReader::open(path)?;
+    Ok(reader
+        .packets()
+        .map(|packet| packet.map(|packet| packet.packet)))
+}
+
+/// Creates a frame iterator by loading from a pcap or pcapng file.
+pub fn frame_xyz_iter_from_file<P>(
+    config: Config,
+    path: P,
+) -> Result<ResultFrameXyzIter<'static, io::Error>, Error>
+where
+    P: AsRef<Path>,
+{
+    let packets = PacketReader::open(path)?
+        .packets()
+        .map(|packet| packet.map(|packet| packet.packet));
+    let iter = try_packet_to_frame_xyz(config, packets)?;
+    Ok(iter)
+}
+
+/// Creates a frame iterator by loading from a pcap or pcapng file,
+/// choosing the config from the packets.
+///
+/// See [try_packet_to_frame_xyz_auto] for the `fallback` config.
+pub fn frame_xyz_iter_from_file_auto<P>(
+    path: P,
+    policy: FormatChangePolicy,
+    fallback: Option<Config>,
+) -> Result<ResultFrameXyzIter<'static, Error>, Error>
+where
+    P: AsRef<Path>,
+{
+    let packets = PacketReader::open(path)?
+        .packets()
+        .map(|packet| packet.map(|packet| packet.packet));
+    Ok(try_packet_to_frame_xyz_auto(packets, policy, fallback))
+}
+
+fn read_section_header<R>(reader: &mut R, format: &mut FileFormat) -> io::Result<()>
+where
+    R: Read,
+{
+    let mut header = [0u8; 8];
+    header[..4].copy_from_slice(&PCAPNG_SECTION_HEADER_BLOCK.to_le_bytes());
+    reader.read_exact(&mut header[4..])?;
+    read_section_header_body(reader, format, header)
+}
+
+/// Reads the rest of a section header block after the first 8 bytes.
+fn read_section_header_body<R>(
+    reader: &mut R,
+    format: &mut FileFormat,
+    header: [u8; 8],
+) -> io::Result<()>
+where
+    R: Read,
+{
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    let little_endian = byte_order(magic)?;
+
+    let total_len = read_u32(&header, 4, little_endian) as usize;
+    if total_len < 28 || !total_len.is_multiple_of(4) {
+        return Err(invalid_data(format!("invalid block length {total_len}")));
+    }
+    read_vec(reader, total_len - 12)?;
+
+    *format = FileFormat::PcapNg {
+        little_endian,
+        interfaces: vec![],
+    };
+    Ok(())
+}
+
+/// Gets the byte order of a section from the leading bytes of its
+/// section header block.
+#[cfg(feature = "tokio")]
+fn section_byte_order(prefix: &[u8]) -> io::Result<bool> {
+    let magic = prefix
+        .get(8..12)
+        .ok_or_else(|| invalid_data("truncated section header block".to_string()))?;
+    byte_order(magic.try_into().unwrap())
+}
+
+/// Returns whether a section is little endian from its byte order
+/// magic.
+fn byte_order(magic: [u8; 4]) -> io::Result<bool> {
+    match u32::from_le_bytes(magic) {
+        PCAPNG_BYTE_ORDER_MAGIC => Ok(true),
+        _ if u32::from_be_bytes(magic) == PCAPNG_BYTE_ORDER_MAGIC => Ok(false),
+        _ => Err(invalid_data("invalid pcapng byte order magic".to_string())),
+    }
+}
+
+fn read_interface(body: &[u8], little_endian: bool) -> io::Result<Interface> {
+    let link_type = read_u16(body, 0, little_endian) as u32;
+    let mut units_per_sec = 1_000_000;
+
+    // walk the options for the timestamp resolution
+    let mut options = body.get(8..).unwrap_or_default();
+    while options.len() >= 4 {
+        let code = read_u16(options, 0, little_endian);
+        let len = read_u16(options, 2, little_endian) as usize;
+        if code == PCAPNG_OPTION_END {
+            break;
+        }
+
+        let value = options.get(4..4 + len).unwrap_or_default();
+        if code == PCAPNG_OPTION_TIMESTAMP_RESOLUTION {
+            if let Some(&resolution) = value.first() {
+                let exponent = (resolution & 0x7f) as u32;
+                let base: u64 = if resolution & 0x80 == 0 { 10 } else { 2 };
+                units_per_sec = base
+                    .checked_pow(exponent)
+                    .ok_or_else(|| invalid_data(format!("invalid resolution {resolution}")))?;
+            }
+        }
+
+        options = options
+            .get(4 + len.next_multiple_of(4)..)
+            .unwrap_or_default();
+    }
+
+    Ok(Interface {
+        link_type,
+        units_per_sec,
+    })
+}
+
+/// Converts a pcapng timestamp in units since the epoch.
+///
+/// The sub-second part is computed in 128 bits, since it overflows
+/// 64 bits for resolutions finer than 10 ns.
+fn units_to_time(units: u64, units_per_sec: u64) -> SystemTime {
+    let subsec_units = (units % units_per_sec) as u128;
+    let nanos = subsec_units * 1_000_000_000 / units_per_sec as u128;
+    UNIX_EPOCH + Duration::from_secs(units / units_per_sec) + Duration::from_nanos(nanos as u64)
+}
+
+/// Fills `buffer`, or returns false if the reader is at the end.
+fn read_or_eof<R>(reader: &mut R, buffer: &mut [u8]) -> io::Result<bool>
+where
+    R: Read,
+{
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(len) => filled += len,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(true)
+}
+
+fn read_vec<R>(reader: &mut R, len: usize) -> io::Result<Vec<u8>>
+where
+    R: Read,
+{
+    if len > MAX_RECORD_SIZE {
+        return Err(invalid_data(format!("record size {len} is too large")));
+    }
+    let mut buffer = vec![0; len];
+    reader.read_exact(&mut buffer)?;
+    Ok(buffer)
+}
+
+fn read_u16(bytes: &[u8], offset: usize, little_endian: bool) -> u16 {
+    let bytes: [u8; 2] = bytes
+        .get(offset..offset + 2)
+        .map(|bytes| bytes.try_into().unwrap())
+        .unwrap_or_default();
+    if little_endian {
+        u16::from_le_bytes(bytes)
+    } else {
+        u16::from_be_bytes(bytes)
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize, little_endian: bool) -> u32 {
+    let bytes: [u8; 4] = bytes
+        .get(offset..offset + 4)
+        .map(|bytes| bytes.try_into().unwrap())
+        .unwrap_or_default();
+    if little_endian {
+        u32::from_le_bytes(bytes)
+    } else {
+        u32::from_be_bytes(bytes)
+    }
+}
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture::{CaptureFormat, PcapWriter, PcapWriterOptions};
+    use std::net::SocketAddr;
+
+    #[test]
+    fn read_bundled_capture() -> io::Result<()> {
+        let mut reader = PacketReader::open("tests/velodyne_vlp32.pcap")?;
+        let events: Vec<_> = reader.by_ref().collect::<io::Result<_>>()?;
+        let packets: Vec<_> = events
+            .iter()
+            .filter_map(|event| match event {
+                CaptureEvent::Packet(packet) => Some(packet),
+                CaptureEvent::Skipped(_) => None,
+            })
+            .collect();
+
+        // the capture contains only sensor packets
+        assert!(!packets.is_empty());
+        assert_eq!(packets.len(), events.len());
+        assert!(reader.skip_counts().is_empty());
+
+        let first = packets[0];
+        assert_eq!(first.source, "192.168.1.201:2368".parse().unwrap());
+        assert_eq!(
+            first.received_at,
+            UNIX_EPOCH + Duration::from_secs(1355262377) + Duration::from_micros(969576)
+        );
+        assert!(packets
+            .windows(2)
+            .all(|w| w[0].received_at <= w[1].received_at));
+        Ok(())
+    }
+
+    #[test]
+    fn round_trip_through_writer() -> io::Result<()> {
+        let packets: Vec<_> = PacketReader::open("tests/velodyne_vlp32.pcap")?
+            .packets()
+            .take(10)
+            .collect::<io::Result<_>>()?;
+
+        for format in [CaptureFormat::Pcap, CaptureFormat::PcapNg] {
+            let mut writer = PcapWriter::new(
+                vec![],
+                PcapWriterOptions {
+                    format,
+                    ..Default::default()
+                },
+            )?;
+            for packet in &packets {
+                writer.write_received(packet)?;
+            }
+            let bytes = writer.into_inner()?;
+
+            let read: Vec<_> = PacketReader::new(&bytes[..])?
+                .packets()
+                .collect::<io::Result<_>>()?;
+            assert_eq!(read, packets);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn report_skipped_frames() -> io::Result<()> {
+        let packet = Packet::from(crate::PositionPacket::default());
+        let mut writer = PcapWriter::new(vec![], PcapWriterOptions::default())?;
+        writer.write_packet(&packet, UNIX_EPOCH)?;
+        let mut bytes = writer.into_inner()?;
+
+        // append an ARP frame and a UDP datagram of unknown size
+        let mut append = |frame: &[u8]| {
+            bytes.extend([0; 8]);
+            bytes.extend((frame.len() as u32).to_le_bytes());
+            bytes.extend((frame.len() as u32).to_le_bytes());
+            bytes.extend(frame);
+        };
+        let mut arp = vec![0xff; 12];
+        arp.extend([0x08, 0x06]);
+        arp.extend([0; 28]);
+        append(&arp);
+
+        let mut udp = vec![0xff; 12];
+        udp.extend([0x08, 0x00, 0x45, 0, 0, 36, 0, 0, 0, 0, 64, 17, 0, 0]);
+        udp.extend([10, 0, 0, 1, 10, 0, 0, 2]);
+        udp.extend([0, 1, 0, 2, 0, 16, 0, 0]);
+        udp.extend([0; 8]);
+        append(&udp);
+
+        let mut reader = PacketReader::new(&bytes[..])?;
+        let events: Vec<_> = reader.by_ref().collect::<io::Result<_>>()?;
+        assert_eq!(events.len(), 3);
+        assert!(matches!(events[0], CaptureEvent::Packet(_)));
+        assert_eq!(
+            events[1],
+            CaptureEvent::Skipped(SkippedFrame {
+                timestamp: Some(UNIX_EPOCH),
+                reason: SkipReason::UnsupportedEtherType(0x0806),
+            })
+        );
+        assert_eq!(
+            events[2],
+            CaptureEvent::Skipped(SkippedFrame {
+                timestamp: Some(UNIX_EPOCH),
+                reason: SkipReason::InvalidPayloadSize(8),
+            })
+        );
+        assert_eq!(reader.skip_counts().values().sum::<usize>(), 2);
+
+        let source: SocketAddr = "192.168.1.201:8308".parse().unwrap();
+        let CaptureEvent::Packet(received) = &events[0] else {
+            unreachable!();
+        };
+        assert_eq!(received.source, source);
+        Ok(())
+    }
+
+    #[test]
+    fn fine_timestamp_resolutions() {
+        // picoseconds
+        let units_per_sec = 10u64.pow(12);
+        let units = 1_000_000 * units_per_sec + 123_456_789_999;
+        let expect = UNIX_EPOCH + Duration::new(1_000_000, 123_456_789);
+        assert_eq!(units_to_time(units, units_per_sec), expect);
+
+        // femtoseconds
+        let units_per_sec = 10u64.pow(15);
+        let units = 1_000 * units_per_sec + 123_456_789_000_000;
+        let expect = UNIX_EPOCH + Duration::new(1_000, 123_456_789);
+        assert_eq!(units_to_time(units, units_per_sec), expect);
+    }
+
+    #[test]
+    fn reject_unknown_files() {
+        assert!(CaptureReader::new(&[0u8; 64][..]).is_err());
+    }
+}
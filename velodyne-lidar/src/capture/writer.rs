@@ -0,0 +1,368 @@
+//! Writers of pcap and pcapng files.
+
+use crate::{
+    consts::{DATA_PORT, POSITION_PORT},
+    iter::ReceivedPacket,
+    Packet,
+};
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    net::{IpAddr, Ipv4Addr},
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const PCAP_MAGIC_MICROS: u32 = 0xa1b2c3d4;
+const PCAPNG_SECTION_HEADER_BLOCK: u32 = 0x0a0d0d0a;
+const PCAPNG_INTERFACE_DESCRIPTION_BLOCK: u32 = 0x00000001;
+const PCAPNG_ENHANCED_PACKET_BLOCK: u32 = 0x00000006;
+const PCAPNG_BYTE_ORDER_MAGIC: u32 = 0x1a2b3c4d;
+const LINKTYPE_ETHERNET: u16 = 1;
+const SNAPSHOT_LENGTH: u32 = 65535;
+
+const ETHERNET_HEADER_SIZE: usize = 14;
+const IPV4_HEADER_SIZE: usize = 20;
+const UDP_HEADER_SIZE: usize = 8;
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const IP_PROTOCOL_UDP: u8 = 17;
+
+/// The file format of a capture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CaptureFormat {
+    #[default]
+    Pcap,
+    PcapNg,
+}
+
+/// Options for [PcapWriter].
+///
+/// The defaults follow the factory settings of Velodyne sensors.
+#[derive(Debug, Clone)]
+pub struct PcapWriterOptions {
+    pub format: CaptureFormat,
+    /// The source MAC address of Ethernet frames.
+    pub source_mac: [u8; 6],
+    /// The source address of packets without a known sender.
+    pub source_ip: Ipv4Addr,
+    /// The destination address of all packets.
+    pub destination_ip: Ipv4Addr,
+    /// The UDP port of data packets.
+    pub data_port: u16,
+    /// The UDP port of position packets.
+    pub position_port: u16,
+}
+
+impl Default for PcapWriterOptions {
+    fn default() -> Self {
+        Self {
+            format: CaptureFormat::Pcap,
+            source_mac: [0x60, 0x76, 0x88, 0x00, 0x00, 0x00],
+            source_ip: Ipv4Addr::new(192, 168, 1, 201),
+            destination_ip: Ipv4Addr::BROADCAST,
+            data_port: DATA_PORT,
+            position_port: POSITION_PORT,
+        }
+    }
+}
+
+/// Writes packets to a pcap or pcapng file.
+///
+/// Each packet is wrapped in synthetic Ethernet, IPv4 and UDP headers
+/// as if it were broadcast by the sensor. Data packets and position
+/// packets are sent to their respective ports.
+#[derive(Debug)]
+pub struct PcapWriter<W>
+where
+    W: Write,
+{
+    writer: W,
+    options: PcapWriterOptions,
+}
+
+impl PcapWriter<BufWriter<File>> {
+    /// Creates a file and writes the file header.
+    pub fn create<P>(path: P, options: PcapWriterOptions) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let file = File::create(path)?;
+        Self::new(BufWriter::new(file), options)
+    }
+}
+
+impl<W> PcapWriter<W>
+where
+    W: Write,
+{
+    /// Writes the file header to `writer`.
+    pub fn new(mut writer: W, options: PcapWriterOptions) -> io::Result<Self> {
+        match options.format {
+            CaptureFormat::Pcap => {
+                let mut header = Vec::with_capacity(24);
+                header.extend(PCAP_MAGIC_MICROS.to_le_bytes());
+                header.extend(2u16.to_le_bytes());
+                header.extend(4u16.to_le_bytes());
+                header.extend(0i32.to_le_bytes()); // time zone offset
+                header.extend(0u32.to_le_bytes()); // timestamp accuracy
+                header.extend(SNAPSHOT_LENGTH.to_le_bytes());
+                header.extend((LINKTYPE_ETHERNET as u32).to_le_bytes());
+                writer.write_all(&header)?;
+            }
+            CaptureFormat::PcapNg => {
+                let mut body = Vec::with_capacity(16);
+                body.extend(PCAPNG_BYTE_ORDER_MAGIC.to_le_bytes());
+                body.extend(1u16.to_le_bytes());
+                body.extend(0u16.to_le_bytes());
+                body.extend((-1i64).to_le_bytes()); // unspecified section length
+                write_pcapng_block(&mut writer, PCAPNG_SECTION_HEADER_BLOCK, &body)?;
+
+                // the timestamp resolution is microseconds by default
+                let mut body = Vec::with_capacity(8);
+                body.extend(LINKTYPE_ETHERNET.to_le_bytes());
+                body.extend(0u16.to_le_bytes());
+                body.extend(SNAPSHOT_LENGTH.to_le_bytes());
+                write_pcapng_block(&mut writer, PCAPNG_INTERFACE_DESCRIPTION_BLOCK, &body)?;
+            }
+        }
+
+        Ok(Self { writer, options })
+    }
+
+    /// Writes a packet sent from the default source address at `timestamp`.
+    pub fn write_packet(&mut self, packet: &Packet, timestamp: SystemTime) -> io::Result<()> {
+        let port = self.port_of(packet);
+        self.write_frame(packet, self.options.source_ip, port, timestamp)
+    }
+
+    /// Writes a received packet with its sender and reception time.
+    ///
+    /// The default source address is used if the sender is an IPv6
+    /// address.
+    pub fn write_received(&mut self, packet: &ReceivedPacket) -> io::Result<()> {
+        let source_ip = match packet.source.ip() {
+            IpAddr::V4(ip) => ip,
+            IpAddr::V6(_) => self.options.source_ip,
+        };
+        self.write_frame(
+            &packet.packet,
+            source_ip,
+            packet.source.port(),
+            packet.received_at,
+        )
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Flushes and unwraps the underlying writer.
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn port_of(&self, packet: &Packet) -> u16 {
+        match packet {
+            Packet::Data(_) => self.options.data_port,
+            Packet::Position(_) => self.options.position_port,
+        }
+    }
+
+    fn write_frame(
+        &mut self,
+        packet: &Packet,
+        source_ip: Ipv4Addr,
+        source_port: u16,
+        timestamp: SystemTime,
+    ) -> io::Result<()> {
+        let since_epoch = timestamp.duration_since(UNIX_EPOCH).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the timestamp is earlier than the Unix epoch",
+            )
+        })?;
+        let frame = self.ethernet_frame(packet, source_ip, source_port);
+        let frame_len = frame.len() as u32;
+
+        match self.options.format {
+            CaptureFormat::Pcap => {
+                let mut header = Vec::with_capacity(16);
+                header.extend((since_epoch.as_secs() as u32).to_le_bytes());
+                header.extend(since_epoch.subsec_micros().to_le_bytes());
+                header.extend(frame_len.to_le_bytes());
+                header.extend(frame_len.to_le_bytes());
+                self.writer.write_all(&header)?;
+                self.writer.write_all(&frame)?;
+            }
+            CaptureFormat::PcapNg => {
+                let micros = since_epoch.as_micros() as u64;
+                let mut body = Vec::with_capacity(20 + frame.len() + 3);
+                body.extend(0u32.to_le_bytes()); // interface ID
+                body.extend(((micros >> 32) as u32).to_le_bytes());
+                body.extend((micros as u32).to_le_bytes());
+                body.extend(frame_len.to_le_bytes());
+                body.extend(frame_len.to_le_bytes());
+                body.extend(&frame);
+                body.resize(body.len().next_multiple_of(4), 0);
+                write_pcapng_block(&mut self.writer, PCAPNG_ENHANCED_PACKET_BLOCK, &body)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn ethernet_frame(&self, packet: &Packet, source_ip: Ipv4Addr, source_port: u16) -> Vec<u8> {
+        let payload = packet.as_bytes();
+        let udp_len = UDP_HEADER_SIZE + payload.len();
+        let ip_len = IPV4_HEADER_SIZE + udp_len;
+        let mut frame = Vec::with_capacity(ETHERNET_HEADER_SIZE + ip_len);
+
+        // Ethernet header
+        frame.extend([0xff; 6]);
+        frame.extend(self.options.source_mac);
+        frame.extend(ETHERTYPE_IPV4.to_be_bytes());
+
+        // IPv4 header with the don't-fragment flag
+        let mut ip_header = Vec::with_capacity(IPV4_HEADER_SIZE);
+        ip_header.extend([0x45, 0x00]);
+        ip_header.extend((ip_len as u16).to_be_bytes());
+        ip_header.extend([0x00, 0x00, 0x40, 0x00]);
+        ip_header.extend([0xff, IP_PROTOCOL_UDP]);
+        ip_header.extend([0x00, 0x00]);
+        ip_header.extend(source_ip.octets());
+        ip_header.extend(self.options.destination_ip.octets());
+        let checksum = ipv4_checksum(&ip_header);
+        ip_header[10..12].copy_from_slice(&checksum.to_be_bytes());
+        frame.extend(ip_header);
+
+        // UDP header without checksum, as the sensor does
+        frame.extend(source_port.to_be_bytes());
+        frame.extend(self.port_of(packet).to_be_bytes());
+        frame.extend((udp_len as u16).to_be_bytes());
+        frame.extend([0x00, 0x00]);
+
+        frame.extend(payload);
+        frame
+    }
+}
+
+fn write_pcapng_block<W>(writer: &mut W, block_type: u32, body: &[u8]) -> io::Result<()>
+where
+    W: Write,
+{
+    let total_len = (12 + body.len()) as u32;
+    writer.write_all(&block_type.to_le_bytes())?;
+    writer.write_all(&total_len.to_le_bytes())?;
+    writer.write_all(body)?;
+    writer.write_all(&total_len.to_le_bytes())?;
+    Ok(())
+}
+
+fn ipv4_checksum(header: &[u8]) -> u16 {
+    let sum: u32 = header
+        .chunks(2)
+        .map(|word| u16::from_be_bytes([word[0], word[1]]) as u32)
+        .sum();
+    let sum = (sum & 0xffff) + (sum >> 16);
+    let sum = (sum & 0xffff) + (sum >> 16);
+    !(sum as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, net::SocketAddr, time::Duration};
+
+    /// Reads the first record of the bundled capture.
+    fn first_record() -> (SystemTime, Vec<u8>) {
+        let bytes = fs::read("tests/velodyne_vlp32.pcap").unwrap();
+        let read_u32 =
+            |offset: usize| u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+
+        let timestamp = UNIX_EPOCH
+            + Duration::from_secs(read_u32(24) as u64)
+            + Duration::from_micros(read_u32(28) as u64);
+        let len = read_u32(32) as usize;
+        let frame = bytes[40..40 + len].to_vec();
+        (timestamp, frame)
+    }
+
+    #[test]
+    fn reproduce_sensor_frames() -> io::Result<()> {
+        let (timestamp, frame) = first_record();
+        let packet = Packet::from_slice(&frame[42..]).unwrap();
+
+        let mut writer = PcapWriter::new(
+            vec![],
+            PcapWriterOptions {
+                source_mac: frame[6..12].try_into().unwrap(),
+                ..Default::default()
+            },
+        )?;
+        writer.write_packet(&packet, timestamp)?;
+        let bytes = writer.into_inner()?;
+
+        let original = fs::read("tests/velodyne_vlp32.pcap")?;
+        assert_eq!(bytes[..], original[..bytes.len()]);
+        Ok(())
+    }
+
+    #[test]
+    fn write_pcapng() -> io::Result<()> {
+        let (timestamp, frame) = first_record();
+        let data_packet = Packet::from_slice(&frame[42..]).unwrap();
+        let position_packet = Packet::from(crate::PositionPacket::default());
+        let source: SocketAddr = "10.0.0.2:2368".parse().unwrap();
+
+        let mut writer = PcapWriter::new(
+            vec![],
+            PcapWriterOptions {
+                format: CaptureFormat::PcapNg,
+                ..Default::default()
+            },
+        )?;
+        writer.write_packet(&position_packet, timestamp)?;
+        writer.write_received(&ReceivedPacket {
+            packet: data_packet.clone(),
+            source,
+            received_at: timestamp,
+        })?;
+        let bytes = writer.into_inner()?;
+
+        // walk the blocks
+        let mut blocks = vec![];
+        let mut offset = 0;
+        while offset < bytes.len() {
+            let read_u32 =
+                |offset: usize| u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+            let block_type = read_u32(offset);
+            let len = read_u32(offset + 4) as usize;
+            assert_eq!(len % 4, 0);
+            assert_eq!(read_u32(offset + len - 4) as usize, len);
+            blocks.push((block_type, &bytes[offset + 8..offset + len - 4]));
+            offset += len;
+        }
+
+        let types: Vec<_> = blocks.iter().map(|(block_type, _)| *block_type).collect();
+        assert_eq!(types, [0x0a0d0d0a, 1, 6, 6]);
+
+        let (_, body) = blocks[3];
+        let micros = ((u32::from_le_bytes(body[4..8].try_into().unwrap()) as u64) << 32)
+            | u32::from_le_bytes(body[8..12].try_into().unwrap()) as u64;
+        assert_eq!(UNIX_EPOCH + Duration::from_micros(micros), timestamp);
+
+        let captured_len = u32::from_le_bytes(body[12..16].try_into().unwrap()) as usize;
+        let frame = &body[20..20 + captured_len];
+        assert_eq!(frame[26..30], [10, 0, 0, 2]);
+        assert_eq!(frame[34..36], 2368u16.to_be_bytes());
+        assert_eq!(ipv4_checksum(&frame[14..34]), 0);
+        assert_eq!(&frame[42..], data_packet.as_bytes());
+
+        let (_, body) = blocks[2];
+        let frame = &body[20..];
+        assert_eq!(frame[36..38], POSITION_PORT.to_be_bytes());
+
+        Ok(())
+    }
+}
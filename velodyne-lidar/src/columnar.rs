@@ -0,0 +1,536 @@
+//! Struct-of-arrays frames with single precision values.
+//!
+//! [ColumnarFrame] stores each attribute of points in a contiguous
+//! buffer, which can be handed to SIMD code, GPUs or other libraries
+//! without copying. [ColumnarFrameBuilder] fills the buffers directly
+//! from [DataPacket]s, skipping the per-point structs of
+//! [FrameXyz](crate::types::frame_xyz::FrameXyz).
+//!
+//! ```rust
+//! # fn main() -> anyhow::Result<()> {
+//! use velodyne_lidar::{capture::PacketReader, columnar::ColumnarFrameBuilder, Config, Packet};
+//!
+//! let mut builder = ColumnarFrameBuilder::new(Config::new_vlp_32c_strongest())?;
+//!
+//! for packet in PacketReader::open("tests/velodyne_vlp32.pcap")?.packets() {
+//!     if let Packet::Data(packet) = packet?.packet {
+//!         for frame in builder.push_packet(&packet) {
+//!             println!("{} valid points", frame.num_valid());
+//!         }
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    batcher::BatchStrategy,
+    config::Config,
+    convert::channel_timing,
+    iter::audit_format,
+    packet::{Channel, DataPacket, ProductID},
+    range_image::ReturnSelection,
+    traits::FiringLike as _,
+    trig_table::{wrap_radians, Precision, XyzConverter},
+    types::format::Format,
+    Error,
+};
+use measurements::Angle;
+use std::{ops::Range, time::Duration};
+
+const HOUR_NANOS: i128 = 3_600_000_000_000;
+
+/// A frame with points stored in columns.
+///
+/// Points are ordered by firings and then by laser IDs. The point of
+/// laser `laser_id` in the firing `firing` is at index
+/// `firing * num_lasers + laser_id`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ColumnarFrame {
+    /// The time of hour of the first firing.
+    pub start_toh: Duration,
+    pub num_lasers: usize,
+    /// The x coordinates in meters.
+    pub x: Vec<f32>,
+    /// The y coordinates in meters.
+    pub y: Vec<f32>,
+    /// The z coordinates in meters.
+    pub z: Vec<f32>,
+    pub intensity: Vec<f32>,
+    /// The rank of the laser elevation, where 0 is the lowest laser.
+    pub ring: Vec<u16>,
+    /// The time since [start_toh](Self::start_toh) in seconds.
+    pub time_offset: Vec<f32>,
+    /// The azimuth angle in radians.
+    pub azimuth: Vec<f32>,
+    /// True if the laser gets a return.
+    pub valid: Vec<bool>,
+}
+
+impl ColumnarFrame {
+    /// Gets the number of points including invalid ones.
+    pub fn len(&self) -> usize {
+        self.x.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.x.is_empty()
+    }
+
+    pub fn num_firings(&self) -> usize {
+        self.len().checked_div(self.num_lasers).unwrap_or(0)
+    }
+
+    /// Counts the points with a return.
+    pub fn num_valid(&self) -> usize {
+        self.valid.iter().filter(|&&valid| valid).count()
+    }
+
+    /// Gets the index of a point in the columns.
+    pub fn index(&self, firing: usize, laser_id: usize) -> Option<usize> {
+        (laser_id < self.num_lasers && firing < self.num_firings())
+            .then_some(firing * self.num_lasers + laser_id)
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.x.reserve(additional);
+        self.y.reserve(additional);
+        self.z.reserve(additional);
+        self.intensity.reserve(additional);
+        self.ring.reserve(additional);
+        self.time_offset.reserve(additional);
+        self.azimuth.reserve(additional);
+        self.valid.reserve(additional);
+    }
+}
+
+/// Precomputed values of a laser.
+#[derive(Debug, Clone)]
+struct LaserColumn {
+    ring: u16,
+    azimuth_offset: f64,
+    /// The time relative to the start of the firing.
+    time_offset: Duration,
+    /// The time offset in the ratio to the firing period.
+    ratio: f64,
+}
+
+/// Builds [ColumnarFrame]s from [DataPacket]s one packet at a time.
+///
+/// It cuts frames by a [BatchStrategy] like
+/// [FrameBuilder](crate::FrameBuilder). Dual return packets fill the
+/// columns with the selected return.
+#[derive(Debug, Clone)]
+pub struct ColumnarFrameBuilder {
+    format: Format,
+    strategy: BatchStrategy,
+    return_selection: ReturnSelection,
+    converter: XyzConverter,
+    rings: Vec<u16>,
+    azimuth_offsets: Vec<f64>,
+    /// Timing of lasers for the last seen product ID.
+    lasers: Option<(Option<ProductID>, Vec<LaserColumn>)>,
+    frame: ColumnarFrame,
+    /// The start azimuth and the time of hour of the last firing.
+    last_firing: Option<(Angle, Duration)>,
+    num_firings: usize,
+    /// The channels of the firing being converted.
+    channels: Vec<Channel>,
+}
+
+impl ColumnarFrameBuilder {
+    /// Creates a builder that cuts frames at zero azimuth.
+    pub fn new(config: Config) -> Result<Self, Error> {
+        Self::with_strategy(config, BatchStrategy::default())
+    }
+
+    /// Creates a builder that cuts frames by the strategy.
+    pub fn with_strategy(config: Config, strategy: BatchStrategy) -> Result<Self, Error> {
+        strategy.check()?;
+
+        let converter = XyzConverter::new(&config);
+        let azimuth_offsets = config
+            .lasers
+            .iter()
+            .map(|laser| laser.azimuth_offset.as_radians())
+            .collect();
+
+        let mut order: Vec<_> = (0..config.lasers.len()).collect();
+        order.sort_by(|&lhs, &rhs| {
+            let lhs = config.lasers[lhs].elevation.as_radians();
+            let rhs = config.lasers[rhs].elevation.as_radians();
+            lhs.total_cmp(&rhs)
+        });
+        let mut rings = vec![0; order.len()];
+        for (ring, laser_id) in order.into_iter().enumerate() {
+            rings[laser_id] = ring as u16;
+        }
+
+        let format = config.try_into_kind()?.format();
+
+        Ok(Self {
+            format,
+            strategy,
+            return_selection: ReturnSelection::default(),
+            converter,
+            rings,
+            azimuth_offsets,
+            lasers: None,
+            frame: ColumnarFrame::default(),
+            last_firing: None,
+            num_firings: 0,
+            channels: vec![],
+        })
+    }
+
+    /// Converts points with the precision of the trigonometric tables.
+    ///
+    /// It is [Precision::Exact] by default.
+    pub fn with_precision(mut self, precision: Precision) -> Self {
+        self.converter = self.converter.with_precision(precision);
+        self
+    }
+
+    /// Selects the return of dual return packets.
+    pub fn with_return_selection(mut self, return_selection: ReturnSelection) -> Self {
+        self.return_selection = return_selection;
+        self
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Pushes a packet and returns completed frames.
+    ///
+    /// A packet of another format than the config is converted
+    /// anyway with a warning.
+    pub fn push_packet(&mut self, packet: &DataPacket) -> Vec<ColumnarFrame> {
+        use Format as F;
+
+        audit_format(packet.try_format(), self.format);
+        self.update_timing(packet.product_id());
+
+        let mut frames = vec![];
+        let select_last = self.return_selection == ReturnSelection::Last;
+
+        macro_rules! push_single {
+            ($iter_fn:ident) => {
+                for firing in packet.$iter_fn() {
+                    let frame = self.push_firing(firing.toh, &firing.azimuth_range, |index| {
+                        *firing.point_at(index).unwrap()
+                    });
+                    frames.extend(frame);
+                }
+            };
+        }
+
+        macro_rules! push_dual {
+            ($iter_fn:ident) => {
+                for firing in packet.$iter_fn() {
+                    let frame = self.push_firing(firing.toh, &firing.azimuth_range, |index| {
+                        let channels = firing.point_at(index).unwrap();
+                        if select_last {
+                            *channels.last
+                        } else {
+                            *channels.strongest
+                        }
+                    });
+                    frames.extend(frame);
+                }
+            };
+        }
+
+        match self.format {
+            F::Single16 => push_single!(firing_block_iter_s16),
+            F::Single32 => push_single!(firing_block_iter_s32),
+            F::Single64 => push_single!(firing_block_iter_s64),
+            F::Single128 => push_single!(firing_block_iter_s128),
+            F::Dual16 => push_dual!(firing_block_iter_d16),
+            F::Dual32 => push_dual!(firing_block_iter_d32),
+            F::Dual64 => push_dual!(firing_block_iter_d64),
+            F::Dual128 => push_dual!(firing_block_iter_d128),
+        }
+
+        frames
+    }
+
+    /// Takes the incomplete frame, if any, and starts over.
+    pub fn flush(&mut self) -> Option<ColumnarFrame> {
+        self.last_firing = None;
+        self.take_frame()
+    }
+
+    /// Consumes the builder and outputs the last maybe incomplete
+    /// frame.
+    pub fn finish(mut self) -> Option<ColumnarFrame> {
+        self.flush()
+    }
+
+    fn update_timing(&mut self, product_id: Option<ProductID>) {
+        if matches!(&self.lasers, Some((prev, _)) if *prev == product_id) {
+            return;
+        }
+
+        let (firing_period, offsets) = channel_timing(self.format, product_id);
+        let lasers = offsets
+            .into_iter()
+            .enumerate()
+            .map(|(laser_id, time_offset)| LaserColumn {
+                ring: self.rings[laser_id],
+                azimuth_offset: self.azimuth_offsets[laser_id],
+                time_offset,
+                ratio: time_offset.as_secs_f64() / firing_period.as_secs_f64(),
+            })
+            .collect();
+        self.lasers = Some((product_id, lasers));
+    }
+
+    fn take_frame(&mut self) -> Option<ColumnarFrame> {
+        if self.frame.is_empty() {
+            return None;
+        }
+        self.num_firings = 0;
+
+        let num_lasers = self.frame.num_lasers;
+        let capacity = self.frame.len();
+        let frame = std::mem::take(&mut self.frame);
+        self.frame = ColumnarFrame {
+            num_lasers,
+            ..ColumnarFrame::default()
+        };
+        self.frame.reserve(capacity);
+        Some(frame)
+    }
+
+    fn push_firing<F>(
+        &mut self,
+        toh: Duration,
+        azimuth_range: &Range<Angle>,
+        channel_at: F,
+    ) -> Option<ColumnarFrame>
+    where
+        F: Fn(usize) -> Channel,
+    {
+        let curr = (azimuth_range.start, toh);
+        let output = match self.last_firing {
+            Some(prev) if self.strategy.is_cut_at(self.num_firings, prev, curr) => {
+                self.take_frame()
+            }
+            _ => None,
+        };
+        self.last_firing = Some(curr);
+
+        let Some((_, lasers)) = &self.lasers else {
+            unreachable!();
+        };
+        let frame = &mut self.frame;
+        if frame.is_empty() {
+            frame.start_toh = toh;
+            frame.num_lasers = lasers.len();
+        }
+        let firing_offset = toh_diff(toh, frame.start_toh);
+        let start = azimuth_range.start.as_radians();
+        let span = (azimuth_range.end - azimuth_range.start).as_radians();
+        let base = frame.len();
+
+        self.channels.clear();
+        for (laser_id, laser) in lasers.iter().enumerate() {
+            let channel = channel_at(laser_id);
+            let azimuth = wrap_radians(start + span * laser.ratio + laser.azimuth_offset);
+
+            frame
+                .intensity
+                .push(self.converter.intensity(laser_id, &channel) as f32);
+            frame.ring.push(laser.ring);
+            frame
+                .time_offset
+                .push((firing_offset + laser.time_offset).as_secs_f32());
+            frame.azimuth.push(azimuth as f32);
+            frame.valid.push(channel.distance != 0);
+            self.channels.push(channel);
+        }
+
+        let len = base + lasers.len();
+        frame.x.resize(len, 0.0);
+        frame.y.resize(len, 0.0);
+        frame.z.resize(len, 0.0);
+        self.converter.firing_xyz_f32(
+            &frame.azimuth[base..],
+            &self.channels,
+            [
+                &mut frame.x[base..],
+                &mut frame.y[base..],
+                &mut frame.z[base..],
+            ],
+        );
+        self.num_firings += 1;
+
+        output
+    }
+}
+
+/// Computes `toh - start` with the wrap at the top of the hour.
+fn toh_diff(toh: Duration, start: Duration) -> Duration {
+    let diff = (toh.as_nanos() as i128 - start.as_nanos() as i128).rem_euclid(HOUR_NANOS);
+    Duration::from_nanos(diff as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{capture::PacketReader, FrameBuilder, Packet};
+
+    fn packets() -> Vec<DataPacket> {
+        PacketReader::open("tests/velodyne_vlp32.pcap")
+            .unwrap()
+            .packets()
+            .filter_map(|packet| match packet.unwrap().packet {
+                Packet::Data(packet) => Some(*packet),
+                Packet::Position(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn same_points_as_frame_xyz() {
+        let config = Config::new_vlp_32c_strongest();
+        let packets = packets();
+
+        let mut builder = FrameBuilder::new(config.clone()).unwrap();
+        let mut columnar = ColumnarFrameBuilder::new(config).unwrap();
+        let mut num_frames = 0;
+
+        for packet in &packets {
+            let frames = builder.push_packet(packet);
+            let columnar_frames = columnar.push_packet(packet);
+            assert_eq!(frames.len(), columnar_frames.len());
+
+            for (frame, columnar) in frames.into_iter().zip(columnar_frames) {
+                assert_eq!(frame.firing_iter().count(), columnar.num_firings());
+                let start_toh = frame.firing_iter().next().unwrap().time();
+                assert_eq!(columnar.start_toh, start_toh);
+
+                for (index, point) in frame.into_point_iter().enumerate() {
+                    let point = point.try_into_single().unwrap();
+                    let [x, y, z] = point.measurement.xyz.map(|value| value.as_meters());
+
+                    assert!((columnar.x[index] as f64 - x).abs() < 1e-4);
+                    assert!((columnar.y[index] as f64 - y).abs() < 1e-4);
+                    assert!((columnar.z[index] as f64 - z).abs() < 1e-4);
+                    assert_eq!(
+                        columnar.intensity[index],
+                        point.measurement.intensity as f32
+                    );
+                    assert!(
+                        (columnar.azimuth[index] as f64 - point.azimuth.as_radians()).abs() < 1e-5
+                    );
+                    let time_offset = (point.toh - start_toh).as_secs_f32();
+                    assert!((columnar.time_offset[index] - time_offset).abs() < 1e-6);
+                    assert_eq!(
+                        columnar.valid[index],
+                        point.measurement.distance.as_meters() > 0.0
+                    );
+                }
+                num_frames += 1;
+            }
+        }
+        assert!(num_frames > 0);
+    }
+
+    #[test]
+    fn approximate_precision() {
+        let packets = packets();
+        let build = |precision| -> Vec<_> {
+            let mut builder = ColumnarFrameBuilder::new(Config::new_vlp_32c_strongest())
+                .unwrap()
+                .with_precision(precision);
+            packets
+                .iter()
+                .flat_map(|packet| builder.push_packet(packet))
+                .collect()
+        };
+        let exact = build(Precision::Exact);
+        let approximate = build(Precision::Approximate);
+
+        assert!(!exact.is_empty());
+        assert_eq!(exact.len(), approximate.len());
+        for (exact, approximate) in exact.iter().zip(&approximate) {
+            assert_eq!(exact.len(), approximate.len());
+            assert_eq!(exact.azimuth, approximate.azimuth);
+            assert_eq!(exact.intensity, approximate.intensity);
+
+            let exact = exact.x.iter().zip(&exact.y).zip(&exact.z);
+            let approximate = approximate.x.iter().zip(&approximate.y).zip(&approximate.z);
+            for (((x0, y0), z0), ((x1, y1), z1)) in exact.zip(approximate) {
+                let range = (x0 * x0 + y0 * y0 + z0 * z0).sqrt();
+                let error = ((x0 - x1).powi(2) + (y0 - y1).powi(2) + (z0 - z1).powi(2)).sqrt();
+                assert!(error <= range * 9e-5 + 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn rings_and_dual_return() {
+        let mut packets: Vec<_> = crate::packet::DataPacketSynthesizer::new(
+            ProductID::VLP32C,
+            crate::ReturnMode::Dual,
+            600.0,
+            |_, laser_id| Channel {
+                distance: 1000,
+                intensity: laser_id as u8,
+            },
+        )
+        .unwrap()
+        .take(1000)
+        .collect();
+
+        // Each firing stores the strongest return in a block followed by
+        // the last return. Move the last return farther and brighten it.
+        for packet in &mut packets {
+            for block in packet.blocks.iter_mut().skip(1).step_by(2) {
+                for channel in &mut block.channels {
+                    channel.distance = 2000;
+                    channel.intensity += 100;
+                }
+            }
+        }
+
+        for (return_selection, distance, intensity_offset) in [
+            (ReturnSelection::Strongest, 1000.0, 0.0),
+            (ReturnSelection::Last, 2000.0, 100.0),
+        ] {
+            let mut builder = ColumnarFrameBuilder::new(Config::new_vlp_32c_dual())
+                .unwrap()
+                .with_return_selection(return_selection);
+            let frames: Vec<_> = packets
+                .iter()
+                .flat_map(|packet| builder.push_packet(packet))
+                .collect();
+            let frame = &frames[1];
+
+            assert_eq!(frame.num_lasers, 32);
+            assert_eq!(frame.num_valid(), frame.len());
+
+            // The lowest laser of VLP-32C is laser 0 at -25 degrees.
+            let index = frame.index(0, 0).unwrap();
+            assert_eq!(frame.ring[index], 0);
+            let mut rings = frame.ring[..32].to_vec();
+            rings.sort_unstable();
+            assert_eq!(rings, (0..32).collect::<Vec<u16>>());
+
+            for laser_id in 0..32 {
+                let index = frame.index(0, laser_id).unwrap();
+                assert_eq!(
+                    frame.intensity[index],
+                    laser_id as f32 + intensity_offset,
+                    "{return_selection:?} picks the wrong return of laser {laser_id}"
+                );
+                let range =
+                    (frame.x[index].powi(2) + frame.y[index].powi(2) + frame.z[index].powi(2))
+                        .sqrt();
+                assert!((range - distance * 0.004).abs() < 0.1);
+            }
+        }
+    }
+}
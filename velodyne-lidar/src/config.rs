@@ -1,9 +1,13 @@
 //! Defines a set of Velodyne LiDAR configurations.
 
-use crate::{consts, packet::ReturnMode};
-use anyhow::Result;
+use crate::{
+    consts,
+    error::Error,
+    packet::{ProductID, ReturnMode},
+};
 use itertools::izip;
 use measurements::{Angle, Length};
+use serde::{Deserialize, Serialize};
 
 pub use config_::*;
 mod config_ {
@@ -14,9 +18,14 @@ mod config_ {
     // type
 
     /// Config type for Velodyne LiDARs.
-    #[derive(Debug, Clone)]
+    ///
+    /// It can be serialized with serde. Lengths are written in meters
+    /// and angles in radians. Human readable formats also accept
+    /// strings with units, such as `"-15 deg"` or `"4.2 cm"`.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     pub struct Config {
         pub return_mode: ReturnMode,
+        #[serde(with = "crate::serde_units::length")]
         pub distance_resolution: Length,
         pub lasers: Vec<Beam>,
     }
@@ -35,12 +44,42 @@ mod config_ {
         pub lasers: [Beam; 32],
     }
 
+    /// Config type for HDL-64E.
+    ///
+    /// HDL-64E units are calibrated individually and there is no
+    /// built-in beam table. Load the calibration file shipped with
+    /// the sensor using `Config::from_params_path()` instead.
+    #[derive(Debug, Clone)]
+    pub struct Config64 {
+        pub return_mode: ReturnMode,
+        pub distance_resolution: Length,
+        pub lasers: [Beam; 64],
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct Config128 {
+        pub return_mode: ReturnMode,
+        pub distance_resolution: Length,
+        pub lasers: [Beam; 128],
+    }
+
+    pub type ConfigKind = FormatKind<
+        Config16,
+        Config32,
+        Config64,
+        Config128,
+        Config16,
+        Config32,
+        Config64,
+        Config128,
+    >;
+
     // impls
 
     impl Config {
-        pub fn try_into_kind(
-            self,
-        ) -> Result<FormatKind<Config16, Config32, Config16, Config32>, Self> {
+        /// Converts to the config of the format given by the number of
+        /// lasers and the return mode.
+        pub fn try_into_kind(self) -> Result<ConfigKind, Error> {
             use FormatKind as K;
             use ReturnMode::*;
 
@@ -71,11 +110,30 @@ mod config_ {
                     distance_resolution,
                     lasers: lasers.try_into().unwrap(),
                 }),
+                (Strongest | Last, 64) => K::from_s64(Config64 {
+                    return_mode,
+                    distance_resolution,
+                    lasers: lasers.try_into().unwrap(),
+                }),
+                (Dual, 64) => K::from_d64(Config64 {
+                    return_mode,
+                    distance_resolution,
+                    lasers: lasers.try_into().unwrap(),
+                }),
+                (Strongest | Last, 128) => K::from_s128(Config128 {
+                    return_mode,
+                    distance_resolution,
+                    lasers: lasers.try_into().unwrap(),
+                }),
+                (Dual, 128) => K::from_d128(Config128 {
+                    return_mode,
+                    distance_resolution,
+                    lasers: lasers.try_into().unwrap(),
+                }),
                 _ => {
-                    return Err(Self {
+                    return Err(Error::UnsupportedConfig {
                         return_mode,
-                        distance_resolution,
-                        lasers,
+                        num_lasers: lasers.len(),
                     })
                 }
             })
@@ -85,6 +143,30 @@ mod config_ {
             Format::try_new(self.lasers.len(), self.return_mode)
         }
 
+        /// Builds the built-in config for a sensor model.
+        ///
+        /// Returns `None` if the model has no built-in beam table.
+        pub fn try_from_model(product_id: ProductID, return_mode: ReturnMode) -> Option<Self> {
+            let BeamConfig {
+                lasers,
+                distance_resolution,
+            } = match product_id {
+                ProductID::HDL32E => BeamConfig::new_hdl_32e(),
+                ProductID::VLP16 => BeamConfig::new_vlp_16(),
+                ProductID::PuckLite => BeamConfig::new_puck_lite(),
+                ProductID::PuckHiRes => BeamConfig::new_puck_hires(),
+                ProductID::VLP32C => BeamConfig::new_vlp_32c(),
+                ProductID::VLS128 => BeamConfig::new_vls_128(),
+                ProductID::Velarray => return None,
+            };
+
+            Some(Self {
+                return_mode,
+                lasers,
+                distance_resolution,
+            })
+        }
+
         pub fn format(&self) -> Format {
             self.try_format().unwrap()
         }
@@ -244,6 +326,84 @@ mod config_ {
                 distance_resolution,
             }
         }
+
+        pub fn new_hdl_32e_last() -> Self {
+            let BeamConfig {
+                lasers,
+                distance_resolution,
+            } = BeamConfig::new_hdl_32e();
+
+            Self {
+                return_mode: ReturnMode::Last,
+                lasers,
+                distance_resolution,
+            }
+        }
+
+        pub fn new_hdl_32e_strongest() -> Self {
+            let BeamConfig {
+                lasers,
+                distance_resolution,
+            } = BeamConfig::new_hdl_32e();
+
+            Self {
+                return_mode: ReturnMode::Strongest,
+                lasers,
+                distance_resolution,
+            }
+        }
+
+        pub fn new_hdl_32e_dual() -> Self {
+            let BeamConfig {
+                lasers,
+                distance_resolution,
+            } = BeamConfig::new_hdl_32e();
+
+            Self {
+                return_mode: ReturnMode::Dual,
+                lasers,
+                distance_resolution,
+            }
+        }
+
+        pub fn new_vls_128_last() -> Self {
+            let BeamConfig {
+                lasers,
+                distance_resolution,
+            } = BeamConfig::new_vls_128();
+
+            Self {
+                return_mode: ReturnMode::Last,
+                lasers,
+                distance_resolution,
+            }
+        }
+
+        pub fn new_vls_128_strongest() -> Self {
+            let BeamConfig {
+                lasers,
+                distance_resolution,
+            } = BeamConfig::new_vls_128();
+
+            Self {
+                return_mode: ReturnMode::Strongest,
+                lasers,
+                distance_resolution,
+            }
+        }
+
+        pub fn new_vls_128_dual() -> Self {
+            let BeamConfig {
+                lasers,
+                distance_resolution,
+            } = BeamConfig::new_vls_128();
+
+            Self {
+                return_mode: ReturnMode::Dual,
+                lasers,
+                distance_resolution,
+            }
+        }
     }
 
     impl Config16 {
@@ -424,6 +584,108 @@ mod config_ {
                 distance_resolution,
             }
         }
+
+        pub fn new_hdl_32e_last() -> Self {
+            let BeamConfig {
+                lasers,
+                distance_resolution,
+            } = BeamConfig::new_hdl_32e();
+
+            Self {
+                return_mode: ReturnMode::Last,
+                lasers: lasers.try_into().unwrap(),
+                distance_resolution,
+            }
+        }
+
+        pub fn new_hdl_32e_strongest() -> Self {
+            let BeamConfig {
+                lasers,
+                distance_resolution,
+            } = BeamConfig::new_hdl_32e();
+
+            Self {
+                return_mode: ReturnMode::Strongest,
+                lasers: lasers.try_into().unwrap(),
+                distance_resolution,
+            }
+        }
+
+        pub fn new_hdl_32e_dual() -> Self {
+            let BeamConfig {
+                lasers,
+                distance_resolution,
+            } = BeamConfig::new_hdl_32e();
+
+            Self {
+                return_mode: ReturnMode::Dual,
+                lasers: lasers.try_into().unwrap(),
+                distance_resolution,
+            }
+        }
+    }
+
+    impl Config64 {
+        pub fn format(&self) -> Format {
+            use Format::*;
+            use ReturnMode::*;
+
+            match self.return_mode {
+                Strongest | Last => Single64,
+                Dual => Dual64,
+            }
+        }
+    }
+
+    impl Config128 {
+        pub fn format(&self) -> Format {
+            use Format::*;
+            use ReturnMode::*;
+
+            match self.return_mode {
+                Strongest | Last => Single128,
+                Dual => Dual128,
+            }
+        }
+
+        pub fn new_vls_128_last() -> Self {
+            let BeamConfig {
+                lasers,
+                distance_resolution,
+            } = BeamConfig::new_vls_128();
+
+            Self {
+                return_mode: ReturnMode::Last,
+                lasers: lasers.try_into().unwrap(),
+                distance_resolution,
+            }
+        }
+
+        pub fn new_vls_128_strongest() -> Self {
+            let BeamConfig {
+                lasers,
+                distance_resolution,
+            } = BeamConfig::new_vls_128();
+
+            Self {
+                return_mode: ReturnMode::Strongest,
+                lasers: lasers.try_into().unwrap(),
+                distance_resolution,
+            }
+        }
+
+        pub fn new_vls_128_dual() -> Self {
+            let BeamConfig {
+                lasers,
+                distance_resolution,
+            } = BeamConfig::new_vls_128();
+
+            Self {
+                return_mode: ReturnMode::Dual,
+                lasers: lasers.try_into().unwrap(),
+                distance_resolution,
+            }
+        }
     }
 
     impl From<Config16> for Config {
@@ -456,6 +718,36 @@ mod config_ {
         }
     }
 
+    impl From<Config64> for Config {
+        fn from(from: Config64) -> Self {
+            let Config64 {
+                return_mode,
+                lasers,
+                distance_resolution,
+            } = from;
+            Self {
+                return_mode,
+                lasers: lasers.into(),
+                distance_resolution,
+            }
+        }
+    }
+
+    impl From<Config128> for Config {
+        fn from(from: Config128) -> Self {
+            let Config128 {
+                return_mode,
+                lasers,
+                distance_resolution,
+            } = from;
+            Self {
+                return_mode,
+                lasers: lasers.into(),
+                distance_resolution,
+            }
+        }
+    }
+
     impl TryFrom<Config> for Config16 {
         type Error = Config;
 
@@ -503,6 +795,54 @@ mod config_ {
             })
         }
     }
+
+    impl TryFrom<Config> for Config64 {
+        type Error = Config;
+
+        fn try_from(from: Config) -> Result<Self, Self::Error> {
+            let Config {
+                return_mode,
+                lasers,
+                distance_resolution,
+            } = from;
+
+            let lasers = lasers.try_into().map_err(|lasers| Config {
+                return_mode,
+                lasers,
+                distance_resolution,
+            })?;
+
+            Ok(Self {
+                return_mode,
+                lasers,
+                distance_resolution,
+            })
+        }
+    }
+
+    impl TryFrom<Config> for Config128 {
+        type Error = Config;
+
+        fn try_from(from: Config) -> Result<Self, Self::Error> {
+            let Config {
+                return_mode,
+                lasers,
+                distance_resolution,
+            } = from;
+
+            let lasers = lasers.try_into().map_err(|lasers| Config {
+                return_mode,
+                lasers,
+                distance_resolution,
+            })?;
+
+            Ok(Self {
+                return_mode,
+                lasers,
+                distance_resolution,
+            })
+        }
+    }
 }
 
 pub use params::*;
@@ -528,11 +868,48 @@ mod params {
     }
 
     #[derive(Debug, Clone)]
+    pub(super) struct BeamConfig128 {
+        pub lasers: [Beam; 128],
+        pub distance_resolution: Length,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     pub struct Beam {
+        #[serde(with = "crate::serde_units::angle")]
         pub elevation: Angle,
+        #[serde(with = "crate::serde_units::angle")]
         pub azimuth_offset: Angle,
+        #[serde(with = "crate::serde_units::length")]
         pub vertical_offset: Length,
+        #[serde(with = "crate::serde_units::length")]
         pub horizontal_offset: Length,
+        /// Per-beam distance and intensity corrections from calibration.
+        ///
+        /// Built-in configs leave it `None` and measure raw distances.
+        #[serde(default)]
+        pub correction: Option<BeamCorrection>,
+    }
+
+    /// Distance and intensity corrections of a calibrated beam.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct BeamCorrection {
+        /// The offset added to every measured distance.
+        #[serde(with = "crate::serde_units::length")]
+        pub distance: Length,
+        /// The distance correction at 25.04m along the x-axis.
+        #[serde(with = "crate::serde_units::length")]
+        pub distance_x: Length,
+        /// The distance correction at 25.04m along the y-axis.
+        #[serde(with = "crate::serde_units::length")]
+        pub distance_y: Length,
+        /// Whether to interpolate `distance_x` and `distance_y`.
+        pub two_point: bool,
+        /// The focal distance, in the unit of the calibration file.
+        pub focal_distance: f64,
+        /// The slope of intensity correction over distance.
+        pub focal_slope: f64,
+        pub min_intensity: u8,
+        pub max_intensity: u8,
     }
 
     impl BeamConfig {
@@ -549,6 +926,7 @@ mod params {
                     vertical_offset: Length::from_millimeters(vertical_offset),
                     horizontal_offset: Length::from_millimeters(horizontal_offset),
                     azimuth_offset: Angle::from_degrees(azimuth_offset),
+                    correction: None,
                 },
             )
             .collect();
@@ -572,6 +950,7 @@ mod params {
                     vertical_offset: Length::from_millimeters(vertical_offset),
                     horizontal_offset: Length::from_millimeters(horizontal_offset),
                     azimuth_offset: Angle::from_degrees(azimuth_offset),
+                    correction: None,
                 },
             )
             .collect();
@@ -595,6 +974,7 @@ mod params {
                     vertical_offset: Length::from_millimeters(vertical_offset),
                     horizontal_offset: Length::from_millimeters(horizontal_offset),
                     azimuth_offset: Angle::from_degrees(azimuth_offset),
+                    correction: None,
                 },
             )
             .collect();
@@ -618,6 +998,7 @@ mod params {
                     vertical_offset: Length::from_millimeters(vertical_offset),
                     horizontal_offset: Length::from_millimeters(horizontal_offset),
                     azimuth_offset: Angle::from_degrees(azimuth_offset),
+                    correction: None,
                 },
             )
             .collect();
@@ -627,6 +1008,54 @@ mod params {
                 distance_resolution: *consts::vlp_32c::DISTANCE_RESOLUTION,
             }
         }
+
+        pub fn new_hdl_32e() -> Self {
+            let lasers: Vec<_> = izip!(
+                consts::hdl_32e::ELEVAION_DEGREES,
+                consts::hdl_32e::VERTICAL_OFFSETS,
+                consts::hdl_32e::HORIZONTAL_OFFSETS,
+                consts::hdl_32e::AZIMUTH_OFFSETS,
+            )
+            .map(
+                |(elevation, vertical_offset, horizontal_offset, azimuth_offset)| Beam {
+                    elevation: Angle::from_degrees(elevation),
+                    vertical_offset: Length::from_millimeters(vertical_offset),
+                    horizontal_offset: Length::from_millimeters(horizontal_offset),
+                    azimuth_offset: Angle::from_degrees(azimuth_offset),
+                    correction: None,
+                },
+            )
+            .collect();
+
+            Self {
+                lasers,
+                distance_resolution: *consts::hdl_32e::DISTANCE_RESOLUTION,
+            }
+        }
+
+        pub fn new_vls_128() -> Self {
+            let lasers: Vec<_> = izip!(
+                consts::vls_128::ELEVAION_DEGREES,
+                consts::vls_128::VERTICAL_OFFSETS,
+                consts::vls_128::HORIZONTAL_OFFSETS,
+                consts::vls_128::AZIMUTH_OFFSETS,
+            )
+            .map(
+                |(elevation, vertical_offset, horizontal_offset, azimuth_offset)| Beam {
+                    elevation: Angle::from_degrees(elevation),
+                    vertical_offset: Length::from_millimeters(vertical_offset),
+                    horizontal_offset: Length::from_millimeters(horizontal_offset),
+                    azimuth_offset: Angle::from_degrees(azimuth_offset),
+                    correction: None,
+                },
+            )
+            .collect();
+
+            Self {
+                lasers,
+                distance_resolution: *consts::vls_128::DISTANCE_RESOLUTION,
+            }
+        }
     }
 
     impl From<BeamConfig16> for BeamConfig {
@@ -655,6 +1084,19 @@ mod params {
         }
     }
 
+    impl From<BeamConfig128> for BeamConfig {
+        fn from(from: BeamConfig128) -> Self {
+            let BeamConfig128 {
+                lasers,
+                distance_resolution,
+            } = from;
+            Self {
+                lasers: lasers.into(),
+                distance_resolution,
+            }
+        }
+    }
+
     impl TryFrom<BeamConfig> for BeamConfig16 {
         type Error = BeamConfig;
 
@@ -696,4 +1138,188 @@ mod params {
             })
         }
     }
+
+    impl TryFrom<BeamConfig> for BeamConfig128 {
+        type Error = BeamConfig;
+
+        fn try_from(from: BeamConfig) -> Result<Self, Self::Error> {
+            let BeamConfig {
+                lasers,
+                distance_resolution,
+            } = from;
+
+            let lasers = lasers.try_into().map_err(|lasers| BeamConfig {
+                lasers,
+                distance_resolution,
+            })?;
+
+            Ok(Self {
+                lasers,
+                distance_resolution,
+            })
+        }
+    }
+}
+
+#[cfg(feature = "velodyne-params")]
+mod from_params {
+    use super::*;
+    use crate::error::ensure_argument;
+    use std::path::Path;
+    use velodyne_params::{Laser, VelodyneParams};
+
+    impl Config {
+        /// Builds a config from calibration parameters.
+        ///
+        /// Lasers are ordered by `laser_id`, which must cover every
+        /// index from zero to the number of lasers exactly once.
+        pub fn from_params(
+            params: &VelodyneParams,
+            return_mode: ReturnMode,
+        ) -> Result<Self, Error> {
+            let VelodyneParams {
+                distance_resolution,
+                ref lasers,
+            } = *params;
+
+            let mut lasers: Vec<&Laser> = lasers.iter().collect();
+            lasers.sort_by_key(|laser| laser.laser_id);
+
+            let is_complete = lasers
+                .iter()
+                .enumerate()
+                .all(|(index, laser)| laser.laser_id as usize == index);
+            ensure_argument!(
+                !lasers.is_empty() && is_complete,
+                "laser IDs must range from 0 to {} without duplicates",
+                lasers.len().saturating_sub(1)
+            );
+
+            Ok(Self {
+                return_mode,
+                distance_resolution,
+                lasers: lasers.into_iter().map(Beam::from).collect(),
+            })
+        }
+
+        /// Loads calibration parameters from a YAML file and builds a config.
+        pub fn from_params_path<P: AsRef<Path>>(
+            path: P,
+            return_mode: ReturnMode,
+        ) -> Result<Self, Error> {
+            let params = VelodyneParams::from_path(path)?;
+            Self::from_params(&params, return_mode)
+        }
+    }
+
+    impl From<&Laser> for Beam {
+        fn from(laser: &Laser) -> Self {
+            // The calibration rotates the azimuth clockwise by
+            // `-rot_correction`, while the azimuth offset is added.
+            Self {
+                elevation: Angle::from_radians(laser.vert_correction),
+                azimuth_offset: Angle::from_radians(-laser.rot_correction),
+                vertical_offset: Length::from_meters(laser.vert_offset_correction),
+                horizontal_offset: Length::from_meters(laser.horiz_offset_correction),
+                correction: Some(BeamCorrection::from(laser)),
+            }
+        }
+    }
+
+    impl From<&Laser> for BeamCorrection {
+        fn from(laser: &Laser) -> Self {
+            let intensity = |value: f64| value.round().clamp(0.0, 255.0) as u8;
+
+            Self {
+                distance: Length::from_meters(laser.dist_correction),
+                distance_x: Length::from_meters(laser.dist_correction_x),
+                distance_y: Length::from_meters(laser.dist_correction_y),
+                two_point: laser.two_pt_correction_available,
+                focal_distance: laser.focal_distance,
+                focal_slope: laser.focal_slope,
+                min_intensity: intensity(laser.min_intensity),
+                max_intensity: intensity(laser.max_intensity),
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::types::format::Format;
+
+        const PARAMS_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../velodyne-params/params");
+
+        fn assert_same_angles(lhs: &Config, rhs: &Config) {
+            assert_eq!(lhs.lasers.len(), rhs.lasers.len());
+
+            for (lhs, rhs) in izip!(&lhs.lasers, &rhs.lasers) {
+                assert!((lhs.elevation.as_degrees() - rhs.elevation.as_degrees()).abs() < 1e-3);
+                assert!(
+                    (lhs.azimuth_offset.as_degrees() - rhs.azimuth_offset.as_degrees()).abs()
+                        < 1e-3
+                );
+            }
+        }
+
+        #[test]
+        fn from_params_matches_builtin_configs() -> Result<(), Error> {
+            let vlp_16 = Config::from_params_path(
+                format!("{PARAMS_DIR}/VLP16db.yaml"),
+                ReturnMode::Strongest,
+            )?;
+            assert_same_angles(&vlp_16, &Config::new_vlp_16_strongest());
+            assert!(vlp_16.try_into_kind().is_ok());
+
+            let vlp_32c = Config::from_params_path(
+                format!("{PARAMS_DIR}/VeloView-VLP-32C.yaml"),
+                ReturnMode::Dual,
+            )?;
+            assert_same_angles(&vlp_32c, &Config::new_vlp_32c_dual());
+            assert_eq!(
+                vlp_32c.distance_resolution,
+                *consts::vlp_32c::DISTANCE_RESOLUTION
+            );
+            assert!(vlp_32c.try_into_kind().is_ok());
+
+            let hdl_32e =
+                Config::from_params_path(format!("{PARAMS_DIR}/32db.yaml"), ReturnMode::Last)?;
+            assert_same_angles(&hdl_32e, &Config::new_hdl_32e_last());
+            assert_eq!(
+                hdl_32e.distance_resolution,
+                *consts::hdl_32e::DISTANCE_RESOLUTION
+            );
+            assert!(hdl_32e.try_into_kind().is_ok());
+
+            let vls_128 =
+                Config::from_params_path(format!("{PARAMS_DIR}/VLS128.yaml"), ReturnMode::Dual)?;
+            assert_same_angles(&vls_128, &Config::new_vls_128_dual());
+            assert!(vls_128.try_into_kind().is_ok());
+
+            Ok(())
+        }
+
+        #[test]
+        fn from_params_hdl_64e() -> Result<(), Error> {
+            for file in [
+                "64e_s2.1-sztaki.yaml",
+                "64e_s3-xiesc.yaml",
+                "64e_utexas.yaml",
+            ] {
+                let config =
+                    Config::from_params_path(format!("{PARAMS_DIR}/{file}"), ReturnMode::Dual)?;
+                assert_eq!(config.format(), Format::Dual64);
+                assert_eq!(
+                    config.distance_resolution,
+                    *consts::hdl_64e::DISTANCE_RESOLUTION
+                );
+                assert!(config.lasers.iter().all(|laser| laser.correction.is_some()));
+
+                let config: Config64 = config.try_into().unwrap();
+                assert_eq!(config.format(), Format::Dual64);
+            }
+
+            Ok(())
+        }
+    }
 }
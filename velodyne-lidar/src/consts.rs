@@ -7,6 +7,9 @@ use std::time::Duration;
 /// Default UDP data port used by Velodyne LiDARs.
 pub const DATA_PORT: u16 = 2368;
 
+/// Default UDP position port used by Velodyne LiDARs.
+pub const POSITION_PORT: u16 = 8308;
+
 /// Number of channels in one block, where each channel represents a laser return.
 pub const CHANNELS_PER_BLOCK: usize = 32;
 
@@ -157,3 +160,203 @@ pub mod vlp_32c {
         0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
     ];
 }
+
+// HDL-32E parameters
+
+pub mod hdl_32e {
+    use super::*;
+
+    pub const DISTANCE_RESOLUTION_MILLIS: f64 = 2.0;
+
+    pub static DISTANCE_RESOLUTION: Lazy<Length> =
+        Lazy::new(|| Length::from_millimeters(DISTANCE_RESOLUTION_MILLIS));
+
+    /// Period of one laser return of HDL-32E in microseconds.
+    const CHANNEL_PERIOD_MICROS: f64 = 1.152;
+
+    /// Period of one laser return of HDL-32E.
+    ///
+    /// HDL-32E fires its 32 lasers one after another.
+    pub const CHANNEL_PERIOD: Duration = Duration::from_nanos((CHANNEL_PERIOD_MICROS * 1e3) as u64);
+
+    /// Period of one vertical scan of HDL-32E in microseconds.
+    const FIRING_PERIOD_MICROS: f64 = 46.08;
+
+    /// Period of one vertical scan of HDL-32E.
+    pub const FIRING_PERIOD: Duration = Duration::from_nanos((FIRING_PERIOD_MICROS * 1e3) as u64);
+
+    /// Elevaion angles of HDL-32E.
+    pub const ELEVAION_DEGREES: [f64; 32] = [
+        -30.67, -9.33, -29.33, -8.00, -28.00, -6.67, -26.67, -5.33, -25.33, -4.00, -24.00, -2.67,
+        -22.67, -1.33, -21.33, 0.00, -20.00, 1.33, -18.67, 2.67, -17.33, 4.00, -16.00, 5.33,
+        -14.67, 6.67, -13.33, 8.00, -12.00, 9.33, -10.67, 10.67,
+    ];
+
+    /// HDL-32E correspond index
+    pub const ELEVAION_INDEX: [usize; 32] = [
+        31, 29, 27, 25, 23, 21, 19, 17, 15, 13, 11, 9, 7, 5, 3, 1, 30, 28, 26, 24, 22, 20, 18, 16,
+        14, 12, 10, 8, 6, 4, 2, 0,
+    ];
+
+    /// The correction distance added to point position along vertical axis for HDL-32E.
+    pub const VERTICAL_OFFSETS: [f64; 32] = [
+        0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+        0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+    ];
+
+    pub const AZIMUTH_OFFSETS: [f64; 32] = [
+        0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+        0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+    ];
+
+    pub const HORIZONTAL_OFFSETS: [f64; 32] = [
+        0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+        0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+    ];
+}
+
+// HDL-64E parameters
+
+pub mod hdl_64e {
+    use super::*;
+
+    pub const DISTANCE_RESOLUTION_MILLIS: f64 = 2.0;
+
+    pub static DISTANCE_RESOLUTION: Lazy<Length> =
+        Lazy::new(|| Length::from_millimeters(DISTANCE_RESOLUTION_MILLIS));
+
+    /// Number of blocks holding one firing of all 64 lasers.
+    ///
+    /// The upper block holds lasers 0 to 31 and the lower block holds
+    /// lasers 32 to 63.
+    pub const BLOCKS_PER_FIRING: usize = 2;
+
+    /// Period of one laser return of HDL-64E in microseconds.
+    const CHANNEL_PERIOD_MICROS: f64 = 1.5;
+
+    /// Period of one laser return of HDL-64E.
+    ///
+    /// The upper and lower banks fire in parallel, each firing its 32
+    /// lasers one after another.
+    pub const CHANNEL_PERIOD: Duration = Duration::from_nanos((CHANNEL_PERIOD_MICROS * 1e3) as u64);
+
+    /// Period of one vertical scan of HDL-64E in microseconds.
+    const FIRING_PERIOD_MICROS: f64 = 48.0;
+
+    /// Period of one vertical scan of HDL-64E.
+    pub const FIRING_PERIOD: Duration = Duration::from_nanos((FIRING_PERIOD_MICROS * 1e3) as u64);
+
+    /// Period of one laser return of HDL-64E in dual return mode in microseconds.
+    const DUAL_CHANNEL_PERIOD_MICROS: f64 = 1.8;
+
+    /// Period of one laser return of HDL-64E in dual return mode.
+    pub const DUAL_CHANNEL_PERIOD: Duration =
+        Duration::from_nanos((DUAL_CHANNEL_PERIOD_MICROS * 1e3) as u64);
+
+    /// Period of one vertical scan of HDL-64E in dual return mode in microseconds.
+    const DUAL_FIRING_PERIOD_MICROS: f64 = 57.6;
+
+    /// Period of one vertical scan of HDL-64E in dual return mode.
+    pub const DUAL_FIRING_PERIOD: Duration =
+        Duration::from_nanos((DUAL_FIRING_PERIOD_MICROS * 1e3) as u64);
+}
+
+// VLS-128 parameters
+
+pub mod vls_128 {
+    use super::*;
+
+    pub const DISTANCE_RESOLUTION_MILLIS: f64 = 4.0;
+
+    pub static DISTANCE_RESOLUTION: Lazy<Length> =
+        Lazy::new(|| Length::from_millimeters(DISTANCE_RESOLUTION_MILLIS));
+
+    /// Number of blocks holding one firing of all 128 lasers.
+    pub const BLOCKS_PER_FIRING: usize = 4;
+
+    /// Number of lasers fired at the same time.
+    pub const LASERS_PER_GROUP: usize = 8;
+
+    /// Period of one firing group of VLS-128 in microseconds.
+    const CHANNEL_PERIOD_MICROS: f64 = 2.665;
+
+    /// Period of one firing group of VLS-128.
+    ///
+    /// A group of 8 lasers fires at a time. An idle period of the same
+    /// length is inserted after the first 64 lasers.
+    pub const CHANNEL_PERIOD: Duration = Duration::from_nanos((CHANNEL_PERIOD_MICROS * 1e3) as u64);
+
+    /// Period of one vertical scan of VLS-128 in microseconds.
+    const FIRING_PERIOD_MICROS: f64 = 53.3;
+
+    /// Period of one vertical scan of VLS-128.
+    pub const FIRING_PERIOD: Duration = Duration::from_nanos((FIRING_PERIOD_MICROS * 1e3) as u64);
+
+    /// Time between the first firing and the packet timestamp in microseconds.
+    const TOH_ADJUSTMENT_MICROS: f64 = 8.7;
+
+    /// Time between the first firing and the packet timestamp.
+    pub const TOH_ADJUSTMENT: Duration = Duration::from_nanos((TOH_ADJUSTMENT_MICROS * 1e3) as u64);
+
+    /// Elevaion angles of VLS-128.
+    #[allow(clippy::approx_constant)]
+    pub const ELEVAION_DEGREES: [f64; 128] = [
+        -11.742, -1.99, 3.4, -5.29, -0.78, 4.61, -4.08, 1.31, -6.5, -1.11, 4.28, -4.41, 0.1, 6.48,
+        -3.2, 2.19, -3.86, 1.53, -9.244, -1.77, 2.74, -5.95, -0.56, 4.83, -2.98, 2.41, -6.28,
+        -0.89, 3.62, -5.07, 0.32, 7.58, -0.34, 5.18, -3.64, 1.75, -25.0, -2.43, 2.96, -5.73, 0.54,
+        9.7, -2.76, 2.63, -7.65, -1.55, 3.84, -4.85, 3.18, -5.51, -0.12, 5.73, -4.3, 1.09, -16.042,
+        -2.21, 4.06, -4.63, 0.76, 15.0, -3.42, 1.97, -6.85, -1.33, -5.62, -0.23, 5.43, -3.53, 0.98,
+        -19.582, -2.32, 3.07, -4.74, 0.65, 11.75, -2.65, 1.86, -7.15, -1.44, 3.95, -2.1, 3.29,
+        -5.4, -0.01, 4.5, -4.19, 1.2, -13.565, -1.22, 4.17, -4.52, 0.87, 6.08, -3.31, 2.08, -6.65,
+        1.42, -10.346, -1.88, 3.51, -6.06, -0.67, 4.72, -3.97, 2.3, -6.39, -1.0, 4.39, -5.18, 0.21,
+        6.98, -3.09, 4.98, -3.75, 1.64, -8.352, -2.54, 2.85, -5.84, -0.45, 8.43, -2.87, 2.52,
+        -6.17, -1.66, 3.73, -4.96, 0.43,
+    ];
+
+    /// VLS-128 correspond index
+    pub const ELEVAION_INDEX: [usize; 128] = [
+        59, 74, 41, 120, 31, 110, 13, 92, 51, 66, 33, 112, 23, 102, 5, 84, 107, 10, 89, 56, 79, 46,
+        125, 28, 99, 2, 81, 48, 71, 38, 117, 20, 43, 122, 25, 104, 15, 94, 61, 76, 35, 114, 17, 96,
+        7, 86, 53, 68, 91, 58, 73, 40, 127, 30, 109, 12, 83, 50, 65, 32, 119, 22, 101, 4, 27, 106,
+        9, 88, 63, 78, 45, 124, 19, 98, 1, 80, 55, 70, 37, 116, 75, 42, 121, 24, 111, 14, 93, 60,
+        67, 34, 113, 16, 103, 6, 85, 52, 11, 90, 57, 72, 47, 126, 29, 108, 3, 82, 49, 64, 39, 118,
+        21, 100, 123, 26, 105, 8, 95, 62, 77, 44, 115, 18, 97, 0, 87, 54, 69, 36,
+    ];
+
+    /// The correction distance added to point position along vertical axis for VLS-128.
+    pub const VERTICAL_OFFSETS: [f64; 128] = [
+        0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+        0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+        0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+        0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+        0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+        0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+        0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+        0.0, 0.0,
+    ];
+
+    pub const AZIMUTH_OFFSETS: [f64; 128] = [
+        6.354, 4.548, 2.732, 0.911, -0.911, -2.732, -4.548, -6.354, 6.354, 4.548, 2.732, 0.911,
+        -0.911, -2.732, -4.548, -6.354, 6.354, 4.548, 2.732, 0.911, -0.911, -2.732, -4.548, -6.354,
+        6.354, 4.548, 2.732, 0.911, -0.911, -2.732, -4.548, -6.354, 6.354, 4.548, 2.732, 0.911,
+        -0.911, -2.732, -4.548, -6.354, 6.354, 4.548, 2.732, 0.911, -0.911, -2.732, -4.548, -6.354,
+        6.354, 4.548, 2.732, 0.911, -0.911, -2.732, -4.548, -6.354, 6.354, 4.548, 2.732, 0.911,
+        -0.911, -2.732, -4.548, -6.354, 6.354, 4.548, 2.732, 0.911, -0.911, -2.732, -4.548, -6.354,
+        6.354, 4.548, 2.732, 0.911, -0.911, -2.732, -4.548, -6.354, 6.354, 4.548, 2.732, 0.911,
+        -0.911, -2.732, -4.548, -6.354, 6.354, 4.548, 2.732, 0.911, -0.911, -2.732, -4.548, -6.354,
+        6.354, 4.548, 2.732, 0.911, -0.911, -2.732, -4.548, -6.354, 6.354, 4.548, 2.732, 0.911,
+        -0.911, -2.732, -4.548, -6.354, 6.354, 4.548, 2.732, 0.911, -0.911, -2.732, -4.548, -6.354,
+        6.354, 4.548, 2.732, 0.911, -0.911, -2.732, -4.548, -6.354,
+    ];
+
+    pub const HORIZONTAL_OFFSETS: [f64; 128] = [
+        0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+        0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+        0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+        0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+        0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+        0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+        0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+        0.0, 0.0,
+    ];
+}
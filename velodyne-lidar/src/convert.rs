@@ -1,26 +1,49 @@
 use crate::{
-    config::Beam,
-    consts::{CHANNEL_PERIOD, FIRING_PERIOD},
+    config::{Beam, BeamCorrection},
+    consts::{self, CHANNEL_PERIOD, FIRING_PERIOD},
+    packet::{Channel, ProductID},
     types::{
         channel_array::ChannelArrayDRef,
-        firing_block::{FiringBlockD16, FiringBlockD32, FiringBlockS16, FiringBlockS32},
-        firing_xyz::{FiringXyzD16, FiringXyzD32, FiringXyzS16, FiringXyzS32},
+        firing_block::{
+            FiringBlockD128, FiringBlockD16, FiringBlockD32, FiringBlockD64, FiringBlockS128,
+            FiringBlockS16, FiringBlockS32, FiringBlockS64,
+        },
+        firing_xyz::{
+            FiringXyzD128, FiringXyzD16, FiringXyzD32, FiringXyzD64, FiringXyzS128, FiringXyzS16,
+            FiringXyzS32, FiringXyzS64,
+        },
+        format::Format,
         measurements::{Measurement, MeasurementDual},
         point::{PointD, PointS},
     },
     utils::{AngleExt as _, DurationExt as _},
-    Config16, Config32,
+    Config128, Config16, Config32, Config64,
 };
 use itertools::izip;
 use measurements::{Angle, Length};
-use std::iter;
+use std::{iter, time::Duration};
 
 pub fn firing_block_to_xyz_s16(firing: &FiringBlockS16, beams: &Config16) -> FiringXyzS16 {
-    let Config16 {
-        ref lasers,
-        distance_resolution,
-        ..
-    } = *beams;
+    firing_block_to_xyz_s16_with(firing, beams, |laser_id, channel, azimuth| {
+        channel_to_measurement(
+            channel,
+            &beams.lasers[laser_id],
+            azimuth,
+            beams.distance_resolution,
+        )
+    })
+}
+
+/// Converts a firing with a custom channel conversion.
+pub fn firing_block_to_xyz_s16_with<M>(
+    firing: &FiringBlockS16,
+    beams: &Config16,
+    measure: M,
+) -> FiringXyzS16
+where
+    M: Fn(usize, &Channel, Angle) -> Measurement,
+{
+    let Config16 { ref lasers, .. } = *beams;
     let FiringBlockS16 {
         toh: firing_toh,
         ref azimuth_range,
@@ -33,38 +56,20 @@ pub fn firing_block_to_xyz_s16(firing: &FiringBlockS16, beams: &Config16) -> Fir
     let points: Vec<_> = izip!(0.., channel_tohs, channels, lasers)
         .map(move |(laser_id, channel_toh, channel, laser)| -> PointS {
             let ratio = (channel_toh - firing_toh).div_duration(FIRING_PERIOD);
-            let Beam {
-                elevation,
-                azimuth_offset,
-                vertical_offset,
-                horizontal_offset,
-            } = *laser;
 
             // clockwise angle with origin points to front of sensor
             let azimuth = {
                 let azimuth = azimuth_range.start
                     + ((azimuth_range.end - azimuth_range.start) * ratio)
-                    + azimuth_offset;
+                    + laser.azimuth_offset;
                 azimuth.wrap_to_2pi()
             };
-            let distance = distance_resolution * channel.distance as f64;
-            let xyz = spherical_to_xyz(
-                distance,
-                elevation,
-                azimuth,
-                vertical_offset,
-                horizontal_offset,
-            );
 
             PointS {
                 laser_id,
                 toh: channel_toh,
                 azimuth,
-                measurement: Measurement {
-                    distance,
-                    intensity: channel.intensity,
-                    xyz,
-                },
+                measurement: measure(laser_id, channel, azimuth),
             }
         })
         .collect();
@@ -78,58 +83,54 @@ pub fn firing_block_to_xyz_s16(firing: &FiringBlockS16, beams: &Config16) -> Fir
 }
 
 pub fn firing_block_to_xyz_s32(firing: &FiringBlockS32, beams: &Config32) -> FiringXyzS32 {
-    let Config32 {
-        ref lasers,
-        distance_resolution,
-        ..
-    } = *beams;
+    firing_block_to_xyz_s32_with(firing, beams, |laser_id, channel, azimuth| {
+        channel_to_measurement(
+            channel,
+            &beams.lasers[laser_id],
+            azimuth,
+            beams.distance_resolution,
+        )
+    })
+}
+
+/// Converts a firing with a custom channel conversion.
+pub fn firing_block_to_xyz_s32_with<M>(
+    firing: &FiringBlockS32,
+    beams: &Config32,
+    measure: M,
+) -> FiringXyzS32
+where
+    M: Fn(usize, &Channel, Angle) -> Measurement,
+{
+    let Config32 { ref lasers, .. } = *beams;
     let FiringBlockS32 {
         toh: firing_toh,
         ref azimuth_range,
+        product_id,
         channels,
         ..
     } = *firing;
 
-    let channel_tohs = iter::successors(Some(firing_toh), |&prev| Some(prev + CHANNEL_PERIOD))
-        .flat_map(|toh| [toh, toh]);
+    let firing_period = product_id.firing_period();
+    let channel_tohs = channel_tohs_32(product_id, firing_toh);
 
     let points: Vec<_> = izip!(0.., channel_tohs, channels, lasers)
         .map(move |(laser_id, channel_toh, channel, laser)| {
-            // let timestamp = lower_timestamp + CHANNEL_PERIOD.mul_f64((channel_idx / 2) as f64);
-
-            let ratio = (channel_toh - firing_toh).div_duration(FIRING_PERIOD);
-            let Beam {
-                elevation,
-                azimuth_offset,
-                vertical_offset,
-                horizontal_offset,
-            } = *laser;
+            let ratio = (channel_toh - firing_toh).div_duration(firing_period);
 
             // clockwise angle with origin points to front of sensor
             let azimuth = {
                 let azimuth = azimuth_range.start
                     + ((azimuth_range.end - azimuth_range.start) * ratio)
-                    + azimuth_offset;
+                    + laser.azimuth_offset;
                 azimuth.wrap_to_2pi()
             };
-            let distance = distance_resolution * channel.distance as f64;
-            let xyz = spherical_to_xyz(
-                distance,
-                elevation,
-                azimuth,
-                vertical_offset,
-                horizontal_offset,
-            );
 
             PointS {
                 laser_id,
                 toh: channel_toh,
                 azimuth,
-                measurement: Measurement {
-                    distance,
-                    intensity: channel.intensity,
-                    xyz,
-                },
+                measurement: measure(laser_id, channel, azimuth),
             }
         })
         .collect();
@@ -144,11 +145,26 @@ pub fn firing_block_to_xyz_s32(firing: &FiringBlockS32, beams: &Config32) -> Fir
 }
 
 pub fn firing_block_to_xyz_d16(firing: &FiringBlockD16, beams: &Config16) -> FiringXyzD16 {
-    let Config16 {
-        ref lasers,
-        distance_resolution,
-        ..
-    } = *beams;
+    firing_block_to_xyz_d16_with(firing, beams, |laser_id, channel, azimuth| {
+        channel_to_measurement(
+            channel,
+            &beams.lasers[laser_id],
+            azimuth,
+            beams.distance_resolution,
+        )
+    })
+}
+
+/// Converts a firing with a custom channel conversion.
+pub fn firing_block_to_xyz_d16_with<M>(
+    firing: &FiringBlockD16,
+    beams: &Config16,
+    measure: M,
+) -> FiringXyzD16
+where
+    M: Fn(usize, &Channel, Angle) -> Measurement,
+{
+    let Config16 { ref lasers, .. } = *beams;
     let FiringBlockD16 {
         toh: firing_toh,
         ref azimuth_range,
@@ -166,53 +182,22 @@ pub fn firing_block_to_xyz_d16(firing: &FiringBlockD16, beams: &Config16) -> Fir
         .map(
             move |(laser_id, channel_toh, channel_strongest, channel_last, laser)| -> PointD {
                 let ratio = (channel_toh - firing_toh).div_duration(FIRING_PERIOD);
-                let Beam {
-                    elevation,
-                    azimuth_offset,
-                    vertical_offset,
-                    horizontal_offset,
-                } = *laser;
 
                 // clockwise angle with origin points to front of sensor
                 let azimuth = {
                     let azimuth = azimuth_range.start
                         + ((azimuth_range.end - azimuth_range.start) * ratio)
-                        + azimuth_offset;
+                        + laser.azimuth_offset;
                     azimuth.wrap_to_2pi()
                 };
-                let distance_strongest = distance_resolution * channel_strongest.distance as f64;
-                let distance_last = distance_resolution * channel_last.distance as f64;
-
-                let xyz_strongest = spherical_to_xyz(
-                    distance_strongest,
-                    elevation,
-                    azimuth,
-                    vertical_offset,
-                    horizontal_offset,
-                );
-                let xyz_last = spherical_to_xyz(
-                    distance_last,
-                    elevation,
-                    azimuth,
-                    vertical_offset,
-                    horizontal_offset,
-                );
 
                 PointD {
                     laser_id,
                     toh: channel_toh,
                     azimuth,
                     measurements: MeasurementDual {
-                        strongest: Measurement {
-                            distance: distance_strongest,
-                            intensity: channel_strongest.intensity,
-                            xyz: xyz_strongest,
-                        },
-                        last: Measurement {
-                            distance: distance_last,
-                            intensity: channel_last.intensity,
-                            xyz: xyz_last,
-                        },
+                        strongest: measure(laser_id, channel_strongest, azimuth),
+                        last: measure(laser_id, channel_last, azimuth),
                     },
                 }
             },
@@ -228,14 +213,30 @@ pub fn firing_block_to_xyz_d16(firing: &FiringBlockD16, beams: &Config16) -> Fir
 }
 
 pub fn firing_block_to_xyz_d32(firing: &FiringBlockD32, beams: &Config32) -> FiringXyzD32 {
-    let Config32 {
-        ref lasers,
-        distance_resolution,
-        ..
-    } = *beams;
+    firing_block_to_xyz_d32_with(firing, beams, |laser_id, channel, azimuth| {
+        channel_to_measurement(
+            channel,
+            &beams.lasers[laser_id],
+            azimuth,
+            beams.distance_resolution,
+        )
+    })
+}
+
+/// Converts a firing with a custom channel conversion.
+pub fn firing_block_to_xyz_d32_with<M>(
+    firing: &FiringBlockD32,
+    beams: &Config32,
+    measure: M,
+) -> FiringXyzD32
+where
+    M: Fn(usize, &Channel, Angle) -> Measurement,
+{
+    let Config32 { ref lasers, .. } = *beams;
     let FiringBlockD32 {
         toh: firing_toh,
         ref azimuth_range,
+        product_id,
         channels:
             ChannelArrayDRef {
                 strongest: channels_strongest,
@@ -244,62 +245,29 @@ pub fn firing_block_to_xyz_d32(firing: &FiringBlockD32, beams: &Config32) -> Fir
         ..
     } = *firing;
 
-    let channel_tohs = iter::successors(Some(firing_toh), |&prev| Some(prev + CHANNEL_PERIOD))
-        .flat_map(|toh| [toh, toh]);
+    let firing_period = product_id.firing_period();
+    let channel_tohs = channel_tohs_32(product_id, firing_toh);
 
     let points: Vec<_> = izip!(0.., channel_tohs, channels_strongest, channels_last, lasers)
         .map(
             move |(laser_id, channel_toh, channel_strongest, channel_last, laser)| {
-                // let timestamp = lower_timestamp + CHANNEL_PERIOD.mul_f64((channel_idx / 2) as f64);
-
-                let ratio = (channel_toh - firing_toh).div_duration(FIRING_PERIOD);
-                let Beam {
-                    elevation,
-                    azimuth_offset,
-                    vertical_offset,
-                    horizontal_offset,
-                } = *laser;
+                let ratio = (channel_toh - firing_toh).div_duration(firing_period);
 
                 // clockwise angle with origin points to front of sensor
                 let azimuth = {
                     let azimuth = azimuth_range.start
                         + ((azimuth_range.end - azimuth_range.start) * ratio)
-                        + azimuth_offset;
+                        + laser.azimuth_offset;
                     azimuth.wrap_to_2pi()
                 };
-                let distance_strongest = distance_resolution * channel_strongest.distance as f64;
-                let distance_last = distance_resolution * channel_last.distance as f64;
-
-                let xyz_strongest = spherical_to_xyz(
-                    distance_strongest,
-                    elevation,
-                    azimuth,
-                    vertical_offset,
-                    horizontal_offset,
-                );
-                let xyz_last = spherical_to_xyz(
-                    distance_last,
-                    elevation,
-                    azimuth,
-                    vertical_offset,
-                    horizontal_offset,
-                );
 
                 PointD {
                     laser_id,
                     toh: channel_toh,
                     azimuth,
                     measurements: MeasurementDual {
-                        strongest: Measurement {
-                            distance: distance_strongest,
-                            intensity: channel_strongest.intensity,
-                            xyz: xyz_strongest,
-                        },
-                        last: Measurement {
-                            distance: distance_last,
-                            intensity: channel_last.intensity,
-                            xyz: xyz_last,
-                        },
+                        strongest: measure(laser_id, channel_strongest, azimuth),
+                        last: measure(laser_id, channel_last, azimuth),
                     },
                 }
             },
@@ -315,6 +283,461 @@ pub fn firing_block_to_xyz_d32(firing: &FiringBlockD32, beams: &Config32) -> Fir
     }
 }
 
+pub fn firing_block_to_xyz_s64(firing: &FiringBlockS64, beams: &Config64) -> FiringXyzS64 {
+    firing_block_to_xyz_s64_with(firing, beams, |laser_id, channel, azimuth| {
+        channel_to_measurement(
+            channel,
+            &beams.lasers[laser_id],
+            azimuth,
+            beams.distance_resolution,
+        )
+    })
+}
+
+/// Converts a firing with a custom channel conversion.
+pub fn firing_block_to_xyz_s64_with<M>(
+    firing: &FiringBlockS64,
+    beams: &Config64,
+    measure: M,
+) -> FiringXyzS64
+where
+    M: Fn(usize, &Channel, Angle) -> Measurement,
+{
+    use consts::hdl_64e::{CHANNEL_PERIOD, FIRING_PERIOD};
+
+    let Config64 { ref lasers, .. } = *beams;
+    let FiringBlockS64 {
+        toh: firing_toh,
+        ref azimuth_range,
+        ..
+    } = *firing;
+
+    let points: Vec<_> = izip!(0.., firing.channel_iter(), lasers)
+        .map(|(laser_id, channel, laser)| -> PointS {
+            let channel_toh = firing_toh + channel_offset_64(laser_id, CHANNEL_PERIOD);
+            let ratio = (channel_toh - firing_toh).div_duration(FIRING_PERIOD);
+
+            // clockwise angle with origin points to front of sensor
+            let azimuth = {
+                let azimuth = azimuth_range.start
+                    + ((azimuth_range.end - azimuth_range.start) * ratio)
+                    + laser.azimuth_offset;
+                azimuth.wrap_to_2pi()
+            };
+
+            PointS {
+                laser_id,
+                toh: channel_toh,
+                azimuth,
+                measurement: measure(laser_id, channel, azimuth),
+            }
+        })
+        .collect();
+    let points: [_; 64] = points.try_into().unwrap_or_else(|_| unreachable!());
+
+    FiringXyzS64 {
+        toh: firing_toh,
+        azimuth_range: azimuth_range.clone(),
+        points,
+    }
+}
+
+pub fn firing_block_to_xyz_d64(firing: &FiringBlockD64, beams: &Config64) -> FiringXyzD64 {
+    firing_block_to_xyz_d64_with(firing, beams, |laser_id, channel, azimuth| {
+        channel_to_measurement(
+            channel,
+            &beams.lasers[laser_id],
+            azimuth,
+            beams.distance_resolution,
+        )
+    })
+}
+
+/// Converts a firing with a custom channel conversion.
+pub fn firing_block_to_xyz_d64_with<M>(
+    firing: &FiringBlockD64,
+    beams: &Config64,
+    measure: M,
+) -> FiringXyzD64
+where
+    M: Fn(usize, &Channel, Angle) -> Measurement,
+{
+    use consts::hdl_64e::{DUAL_CHANNEL_PERIOD, DUAL_FIRING_PERIOD};
+
+    let Config64 { ref lasers, .. } = *beams;
+    let FiringBlockD64 {
+        toh: firing_toh,
+        ref azimuth_range,
+        ..
+    } = *firing;
+    let strongest = firing.strongest_part();
+    let last = firing.last_part();
+
+    let points: Vec<_> = izip!(0.., strongest.channel_iter(), last.channel_iter(), lasers)
+        .map(
+            |(laser_id, channel_strongest, channel_last, laser)| -> PointD {
+                let channel_toh = firing_toh + channel_offset_64(laser_id, DUAL_CHANNEL_PERIOD);
+                let ratio = (channel_toh - firing_toh).div_duration(DUAL_FIRING_PERIOD);
+
+                // clockwise angle with origin points to front of sensor
+                let azimuth = {
+                    let azimuth = azimuth_range.start
+                        + ((azimuth_range.end - azimuth_range.start) * ratio)
+                        + laser.azimuth_offset;
+                    azimuth.wrap_to_2pi()
+                };
+
+                PointD {
+                    laser_id,
+                    toh: channel_toh,
+                    azimuth,
+                    measurements: MeasurementDual {
+                        strongest: measure(laser_id, channel_strongest, azimuth),
+                        last: measure(laser_id, channel_last, azimuth),
+                    },
+                }
+            },
+        )
+        .collect();
+    let points: [_; 64] = points.try_into().unwrap_or_else(|_| unreachable!());
+
+    FiringXyzD64 {
+        toh: firing_toh,
+        azimuth_range: azimuth_range.clone(),
+        points,
+    }
+}
+
+pub fn firing_block_to_xyz_s128(firing: &FiringBlockS128, beams: &Config128) -> FiringXyzS128 {
+    firing_block_to_xyz_s128_with(firing, beams, |laser_id, channel, azimuth| {
+        channel_to_measurement(
+            channel,
+            &beams.lasers[laser_id],
+            azimuth,
+            beams.distance_resolution,
+        )
+    })
+}
+
+/// Converts a firing with a custom channel conversion.
+pub fn firing_block_to_xyz_s128_with<M>(
+    firing: &FiringBlockS128,
+    beams: &Config128,
+    measure: M,
+) -> FiringXyzS128
+where
+    M: Fn(usize, &Channel, Angle) -> Measurement,
+{
+    let Config128 { ref lasers, .. } = *beams;
+    let FiringBlockS128 {
+        toh: firing_toh,
+        ref azimuth_range,
+        ..
+    } = *firing;
+
+    let points: Vec<_> = izip!(0.., firing.channel_iter(), lasers)
+        .map(|(laser_id, channel, laser)| -> PointS {
+            let channel_toh = firing_toh + channel_offset_128(laser_id);
+            let ratio = (channel_toh - firing_toh).div_duration(consts::vls_128::FIRING_PERIOD);
+
+            // clockwise angle with origin points to front of sensor
+            let azimuth = {
+                let azimuth = azimuth_range.start
+                    + ((azimuth_range.end - azimuth_range.start) * ratio)
+                    + laser.azimuth_offset;
+                azimuth.wrap_to_2pi()
+            };
+
+            PointS {
+                laser_id,
+                toh: channel_toh,
+                azimuth,
+                measurement: measure(laser_id, channel, azimuth),
+            }
+        })
+        .collect();
+    let points: [_; 128] = points.try_into().unwrap_or_else(|_| unreachable!());
+
+    FiringXyzS128 {
+        toh: firing_toh,
+        azimuth_range: azimuth_range.clone(),
+        points,
+    }
+}
+
+pub fn firing_block_to_xyz_d128(firing: &FiringBlockD128, beams: &Config128) -> FiringXyzD128 {
+    firing_block_to_xyz_d128_with(firing, beams, |laser_id, channel, azimuth| {
+        channel_to_measurement(
+            channel,
+            &beams.lasers[laser_id],
+            azimuth,
+            beams.distance_resolution,
+        )
+    })
+}
+
+/// Converts a firing with a custom channel conversion.
+pub fn firing_block_to_xyz_d128_with<M>(
+    firing: &FiringBlockD128,
+    beams: &Config128,
+    measure: M,
+) -> FiringXyzD128
+where
+    M: Fn(usize, &Channel, Angle) -> Measurement,
+{
+    let Config128 { ref lasers, .. } = *beams;
+    let FiringBlockD128 {
+        toh: firing_toh,
+        ref azimuth_range,
+        ..
+    } = *firing;
+    let strongest = firing.strongest_part();
+    let last = firing.last_part();
+
+    let points: Vec<_> = izip!(0.., strongest.channel_iter(), last.channel_iter(), lasers)
+        .map(
+            |(laser_id, channel_strongest, channel_last, laser)| -> PointD {
+                let channel_toh = firing_toh + channel_offset_128(laser_id);
+                let ratio = (channel_toh - firing_toh).div_duration(consts::vls_128::FIRING_PERIOD);
+
+                // clockwise angle with origin points to front of sensor
+                let azimuth = {
+                    let azimuth = azimuth_range.start
+                        + ((azimuth_range.end - azimuth_range.start) * ratio)
+                        + laser.azimuth_offset;
+                    azimuth.wrap_to_2pi()
+                };
+
+                PointD {
+                    laser_id,
+                    toh: channel_toh,
+                    azimuth,
+                    measurements: MeasurementDual {
+                        strongest: measure(laser_id, channel_strongest, azimuth),
+                        last: measure(laser_id, channel_last, azimuth),
+                    },
+                }
+            },
+        )
+        .collect();
+    let points: [_; 128] = points.try_into().unwrap_or_else(|_| unreachable!());
+
+    FiringXyzD128 {
+        toh: firing_toh,
+        azimuth_range: azimuth_range.clone(),
+        points,
+    }
+}
+
+/// Returns the firing period and the time of each laser relative to
+/// the start of the firing.
+pub(crate) fn channel_timing(
+    format: Format,
+    product_id: Option<ProductID>,
+) -> (Duration, Vec<Duration>) {
+    use Format::*;
+
+    let firing_period = format.firing_period(product_id);
+    let offsets = match format {
+        Single16 | Dual16 => (0..16).map(|index| CHANNEL_PERIOD * index).collect(),
+        Single32 | Dual32 => {
+            channel_tohs_32(product_id.unwrap_or(ProductID::VLP32C), Duration::ZERO).collect()
+        }
+        Single64 => (0..64)
+            .map(|laser_id| channel_offset_64(laser_id, consts::hdl_64e::CHANNEL_PERIOD))
+            .collect(),
+        Dual64 => (0..64)
+            .map(|laser_id| channel_offset_64(laser_id, consts::hdl_64e::DUAL_CHANNEL_PERIOD))
+            .collect(),
+        Single128 | Dual128 => (0..128).map(channel_offset_128).collect(),
+    };
+    (firing_period, offsets)
+}
+
+/// Returns the time of an HDL-64E laser relative to the start of the firing.
+fn channel_offset_64(laser_id: usize, channel_period: Duration) -> Duration {
+    // The upper and lower banks fire in parallel.
+    channel_period * (laser_id % 32) as u32
+}
+
+/// Returns the time of a VLS-128 laser relative to the start of the firing.
+pub(crate) fn channel_offset_128(laser_id: usize) -> Duration {
+    use consts::vls_128::LASERS_PER_GROUP;
+
+    // An idle slot follows the first 8 groups.
+    let group = laser_id / LASERS_PER_GROUP + laser_id / 64;
+    consts::vls_128::CHANNEL_PERIOD * group as u32
+}
+
+/// Returns the time of each channel in a 32-laser firing.
+fn channel_tohs_32(product_id: ProductID, firing_toh: Duration) -> impl Iterator<Item = Duration> {
+    let channel_period = product_id.channel_period();
+
+    // VLP-32C fires lasers in pairs, while HDL-32E fires them one at a time.
+    let lasers_per_shot = match product_id {
+        ProductID::HDL32E => 1,
+        _ => 2,
+    };
+
+    (0..32).map(move |index: u32| firing_toh + channel_period * (index / lasers_per_shot))
+}
+
+/// Distances along x and y at which the two-point correction is
+/// measured, in meters.
+const TWO_POINT_NEAR_X: f64 = 2.4;
+const TWO_POINT_NEAR_Y: f64 = 1.93;
+const TWO_POINT_FAR: f64 = 25.04;
+
+/// Converts a channel return to a measurement.
+///
+/// If the beam carries calibration corrections, the distance,
+/// coordinates and intensity are corrected the same way the reference
+/// Velodyne driver does. Zero distances mean no return and are left
+/// uncorrected.
+pub fn channel_to_measurement(
+    channel: &Channel,
+    laser: &Beam,
+    azimuth: Angle,
+    distance_resolution: Length,
+) -> Measurement {
+    let Beam {
+        elevation,
+        vertical_offset,
+        horizontal_offset,
+        ref correction,
+        ..
+    } = *laser;
+    let Channel {
+        distance: raw_distance,
+        intensity,
+    } = *channel;
+
+    match correction {
+        Some(correction) if raw_distance != 0 => {
+            let distance = distance_resolution * raw_distance as f64 + correction.distance;
+            let xyz = spherical_to_xyz_calibrated(
+                distance,
+                elevation,
+                azimuth,
+                vertical_offset,
+                horizontal_offset,
+                correction,
+            );
+            let intensity = correct_intensity(intensity, raw_distance, correction);
+
+            Measurement {
+                distance,
+                intensity,
+                xyz,
+            }
+        }
+        _ => {
+            let distance = distance_resolution * raw_distance as f64;
+            let xyz = spherical_to_xyz(
+                distance,
+                elevation,
+                azimuth,
+                vertical_offset,
+                horizontal_offset,
+            );
+
+            Measurement {
+                distance,
+                intensity,
+                xyz,
+            }
+        }
+    }
+}
+
+/// Converts a corrected distance to Cartesian coordinates, applying the
+/// two-point correction if the beam has one.
+///
+/// The `distance` is expected to include `correction.distance` already.
+pub fn spherical_to_xyz_calibrated(
+    distance: Length,
+    elevation: Angle,
+    azimuth: Angle,
+    vertical_offset: Length,
+    horizontal_offset: Length,
+    correction: &BeamCorrection,
+) -> [Length; 3] {
+    spherical_to_xyz_calibrated_sin_cos(
+        distance,
+        (elevation.sin(), elevation.cos()),
+        (azimuth.sin(), azimuth.cos()),
+        vertical_offset,
+        horizontal_offset,
+        correction,
+    )
+}
+
+/// The same as [spherical_to_xyz_calibrated] with precomputed sines
+/// and cosines of the elevation and the azimuth.
+pub(crate) fn spherical_to_xyz_calibrated_sin_cos(
+    distance: Length,
+    (elevation_sin, elevation_cos): (f64, f64),
+    (azimuth_sin, azimuth_cos): (f64, f64),
+    vertical_offset: Length,
+    horizontal_offset: Length,
+    correction: &BeamCorrection,
+) -> [Length; 3] {
+    let (distance_x, distance_y) = if correction.two_point {
+        // Interpolate the correction linearly over the absolute
+        // coordinates computed from the uncorrected projection.
+        let distance_plane = distance * elevation_cos - vertical_offset * elevation_sin;
+        let x = (distance_plane * azimuth_sin - horizontal_offset * azimuth_cos)
+            .as_meters()
+            .abs();
+        let y = (distance_plane * azimuth_cos + horizontal_offset * azimuth_sin)
+            .as_meters()
+            .abs();
+
+        let base = correction.distance.as_meters();
+        let corr_x = correction.distance_x.as_meters();
+        let corr_y = correction.distance_y.as_meters();
+        let delta_x = (base - corr_x) * (x - TWO_POINT_NEAR_X) / (TWO_POINT_FAR - TWO_POINT_NEAR_X)
+            + corr_x
+            - base;
+        let delta_y = (base - corr_y) * (y - TWO_POINT_NEAR_Y) / (TWO_POINT_FAR - TWO_POINT_NEAR_Y)
+            + corr_y
+            - base;
+
+        (
+            distance + Length::from_meters(delta_x),
+            distance + Length::from_meters(delta_y),
+        )
+    } else {
+        (distance, distance)
+    };
+
+    let x = (distance_x * elevation_cos - vertical_offset * elevation_sin) * azimuth_sin
+        - horizontal_offset * azimuth_cos;
+    let y = (distance_y * elevation_cos - vertical_offset * elevation_sin) * azimuth_cos
+        + horizontal_offset * azimuth_sin;
+    let z = distance_y * elevation_sin + vertical_offset * elevation_cos;
+    [x, y, z]
+}
+
+/// Applies the focal intensity correction on a raw intensity.
+pub fn correct_intensity(intensity: u8, raw_distance: u16, correction: &BeamCorrection) -> u8 {
+    let BeamCorrection {
+        focal_distance,
+        focal_slope,
+        min_intensity,
+        max_intensity,
+        ..
+    } = *correction;
+
+    let focal_offset = 256.0 * (1.0 - focal_distance / 13100.0).powi(2);
+    let distance_offset = 256.0 * (1.0 - raw_distance as f64 / 65535.0).powi(2);
+    let intensity = intensity as f64 + focal_slope * (focal_offset - distance_offset).abs();
+    intensity.round().clamp(
+        min_intensity as f64,
+        max_intensity.max(min_intensity) as f64,
+    ) as u8
+}
+
 pub fn spherical_to_xyz(
     distance: Length,
     elevation: Angle,
@@ -329,34 +752,6 @@ pub fn spherical_to_xyz(
         vertical_offset,
         horizontal_offset,
     )
-
-    // #[cfg(any(
-    //     not(feature = "fast_approx"),
-    //     not(any(target_arch = "x86", target_arch = "x86_64"))
-    // ))]
-    // {
-    //     spherical_to_xyz_generic(
-    //         distance,
-    //         elevation,
-    //         azimuth,
-    //         vertical_offset,
-    //         horizontal_offset,
-    //     )
-    // }
-
-    // #[cfg(all(
-    //     feature = "fast_approx",
-    //     any(target_arch = "x86", target_arch = "x86_64")
-    // ))]
-    // {
-    //     spherical_to_xyz_x86(
-    //         distance,
-    //         elevation,
-    //         azimuth,
-    //         vertical_offset,
-    //         horizontal_offset,
-    //     )
-    // }
 }
 
 pub fn spherical_to_xyz_generic(
@@ -381,28 +776,127 @@ pub fn spherical_to_xyz_generic(
     [x, y, z]
 }
 
-// #[allow(dead_code)]
-// #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-// pub fn spherical_to_xyz_x86(
-//     distance: Length,
-//     elevation: Angle,
-//     azimuth: Angle,
-//     vertical_offset: Length,
-//     horizontal_offset: Length,
-// ) -> [Length; 3] {
-//     use fastapprox::fast::{cosfull, sin, sinfull};
-//     use std::f64::consts::FRAC_PI_2;
-
-//     debug_assert!(((-FRAC_PI_2)..=FRAC_PI_2).contains(&elevation.as_radians()));
-
-//     let elevation_sin = sin(elevation.as_radians() as f32) as f64;
-//     let elevation_cos = 1.0 - elevation_sin.abs();
-//     let azimuth_sin = sinfull(azimuth.as_radians() as f32) as f64;
-//     let azimuth_cos = cosfull(azimuth.as_radians() as f32) as f64;
-
-//     let distance_plane = distance * elevation_cos - vertical_offset * elevation_sin;
-//     let x = distance_plane * azimuth_sin - horizontal_offset * azimuth_cos;
-//     let y = distance_plane * azimuth_cos + horizontal_offset * azimuth_sin;
-//     let z = distance * elevation_sin + vertical_offset * elevation_cos;
-//     [x, y, z]
-// }
+#[cfg(all(test, feature = "velodyne-params"))]
+mod tests {
+    use super::*;
+    use crate::{config::Config, packet::ReturnMode};
+
+    const PARAMS_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../velodyne-params/params");
+
+    /// Expected (laser ID, raw distance, raw intensity, azimuth in
+    /// degrees) -> (distance, x, y, z, intensity) pairs, computed with
+    /// the reference driver's formulas.
+    type Golden = ((usize, u16, u8, f64), (f64, [f64; 3], u8));
+
+    fn check_golden(file: &str, cases: &[Golden]) {
+        let config =
+            Config::from_params_path(format!("{PARAMS_DIR}/{file}"), ReturnMode::Strongest)
+                .unwrap();
+
+        for &((laser_id, distance, intensity, azimuth), (exp_distance, exp_xyz, exp_intensity)) in
+            cases
+        {
+            let laser = &config.lasers[laser_id];
+            let azimuth = (Angle::from_degrees(azimuth) + laser.azimuth_offset).wrap_to_2pi();
+            let channel = Channel {
+                distance,
+                intensity,
+            };
+            let Measurement {
+                distance,
+                intensity,
+                xyz,
+            } = channel_to_measurement(&channel, laser, azimuth, config.distance_resolution);
+
+            assert!((distance.as_meters() - exp_distance).abs() < 1e-6);
+            for (value, expect) in izip!(xyz, exp_xyz) {
+                assert!(
+                    (value.as_meters() - expect).abs() < 1e-6,
+                    "{file} laser {laser_id}: got {value:?}, expect {expect}"
+                );
+            }
+            assert_eq!(intensity, exp_intensity);
+        }
+    }
+
+    #[test]
+    fn calibrated_conversion_golden_64e() {
+        check_golden(
+            "64e_utexas.yaml",
+            &[
+                (
+                    (0, 5000, 40, 12.34),
+                    (10.100000001, [2.791956910, 9.552968062, -1.250224619], 40),
+                ),
+                (
+                    (17, 12345, 100, 181.0),
+                    (
+                        24.749999999,
+                        [0.538153488, -24.727287898, -0.890243602],
+                        100,
+                    ),
+                ),
+                (
+                    (63, 3000, 7, 300.5),
+                    (6.230000004, [-5.173050776, 2.822768276, -1.252128562], 7),
+                ),
+            ],
+        );
+
+        // exercises the focal intensity correction
+        check_golden(
+            "64e_s2.1-sztaki.yaml",
+            &[
+                (
+                    (5, 20000, 10, 45.0),
+                    (
+                        41.439578700,
+                        [27.738657793, 30.356655517, -5.042064033],
+                        142,
+                    ),
+                ),
+                (
+                    (40, 800, 200, 90.0),
+                    (2.844738000, [2.742027164, 0.327399342, -0.951423729], 206),
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn calibrated_conversion_golden_32() {
+        check_golden(
+            "32db.yaml",
+            &[
+                (
+                    (0, 5000, 40, 12.34),
+                    (10.0, [1.838182320, 8.402478004, -5.100926304], 40),
+                ),
+                (
+                    (31, 20000, 90, 270.0),
+                    (40.0, [-39.308395066, 0.0, 7.406083804], 90),
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn zero_distance_is_not_corrected() {
+        let config = Config::from_params_path(
+            format!("{PARAMS_DIR}/64e_utexas.yaml"),
+            ReturnMode::Strongest,
+        )
+        .unwrap();
+        let channel = Channel {
+            distance: 0,
+            intensity: 0,
+        };
+        let measurement = channel_to_measurement(
+            &channel,
+            &config.lasers[0],
+            Angle::from_degrees(0.0),
+            config.distance_resolution,
+        );
+        assert_eq!(measurement.distance, Length::from_meters(0.0));
+    }
+}
@@ -0,0 +1,249 @@
+//! Motion compensation of frames.
+//!
+//! The sensor keeps moving while it sweeps a frame, so points
+//! measured at different times are expressed in different sensor
+//! poses. [deskew_frame_xyz] moves every point into the sensor pose
+//! at one reference time using a [PoseProvider].
+//!
+//! Poses follow the convention that a pose maps points in the sensor
+//! frame to a fixed world frame. Times are the time of hour of the
+//! points. A frame that spans the top of the hour is handled by
+//! extending the times past one hour, so a provider covering such a
+//! frame is queried with times like `3600.05` seconds.
+//!
+//! The poses and providers are re-exported from [lidar_pose], which
+//! is shared with the Ouster crate.
+
+use crate::{
+    types::{
+        frame_xyz::FrameXyz,
+        measurements::Measurement,
+        point::{PointD, PointS},
+    },
+    Error,
+};
+pub use lidar_pose::{
+    ConstantTwist, ImuPoses, ImuSample, InterpolatedPoses, Pose, PoseProvider, ReferenceTime,
+};
+use measurements::Length;
+use std::time::Duration;
+
+const HOUR: Duration = Duration::from_secs(3600);
+
+/// Moves the points of a frame into the sensor pose at the reference
+/// time.
+///
+/// Points without a return are left as they are. It fails if the
+/// provider does not cover the time of any point, in which case the
+/// frame is left unchanged.
+pub fn deskew_frame_xyz<P>(
+    frame: &mut FrameXyz,
+    provider: &P,
+    reference: ReferenceTime,
+) -> Result<(), Error>
+where
+    P: PoseProvider + ?Sized,
+{
+    macro_rules! deskew {
+        ($frame:expr, $point_fn:ident) => {{
+            let mut points = $frame
+                .firings
+                .iter_mut()
+                .flat_map(|firing| firing.points.iter_mut());
+            let Some(first) = points.next() else {
+                return Ok(());
+            };
+            let start = first.toh;
+            let end = $frame
+                .firings
+                .iter()
+                .flat_map(|firing| firing.points.iter())
+                .map(|point| unwrap_toh(point.toh, start))
+                .max()
+                .unwrap();
+            let reference = match reference {
+                ReferenceTime::Start => start,
+                ReferenceTime::End => end,
+                ReferenceTime::At(time) => unwrap_toh(time, start),
+            };
+
+            // compute all transforms first to keep the frame intact on errors
+            let transforms: Vec<_> = $frame
+                .firings
+                .iter()
+                .flat_map(|firing| firing.points.iter())
+                .map(|point| {
+                    let time = unwrap_toh(point.toh, start);
+                    match provider.relative_pose(reference, time) {
+                        Some(pose) => Ok(pose),
+                        None => Err(lidar_pose::Error::PoseUnavailable(time).into()),
+                    }
+                })
+                .collect::<Result<_, Error>>()?;
+
+            let points = $frame
+                .firings
+                .iter_mut()
+                .flat_map(|firing| firing.points.iter_mut());
+            for (point, pose) in points.zip(transforms) {
+                $point_fn(point, &pose);
+            }
+        }};
+    }
+
+    match frame {
+        FrameXyz::Single16(frame) => deskew!(frame, deskew_point_s),
+        FrameXyz::Single32(frame) => deskew!(frame, deskew_point_s),
+        FrameXyz::Single64(frame) => deskew!(frame, deskew_point_s),
+        FrameXyz::Single128(frame) => deskew!(frame, deskew_point_s),
+        FrameXyz::Dual16(frame) => deskew!(frame, deskew_point_d),
+        FrameXyz::Dual32(frame) => deskew!(frame, deskew_point_d),
+        FrameXyz::Dual64(frame) => deskew!(frame, deskew_point_d),
+        FrameXyz::Dual128(frame) => deskew!(frame, deskew_point_d),
+    }
+
+    Ok(())
+}
+
+/// Extends `toh` past one hour if it wraps around after `start`.
+fn unwrap_toh(toh: Duration, start: Duration) -> Duration {
+    if toh + HOUR / 2 < start {
+        toh + HOUR
+    } else {
+        toh
+    }
+}
+
+fn deskew_point_s(point: &mut PointS, pose: &Pose) {
+    deskew_measurement(&mut point.measurement, pose);
+}
+
+fn deskew_point_d(point: &mut PointD, pose: &Pose) {
+    deskew_measurement(&mut point.measurements.strongest, pose);
+    deskew_measurement(&mut point.measurements.last, pose);
+}
+
+fn deskew_measurement(measurement: &mut Measurement, pose: &Pose) {
+    if measurement.distance.as_meters() == 0.0 {
+        return;
+    }
+    let [x, y, z] = measurement.xyz.map(|v| v.as_meters());
+    let xyz = pose.transform_point([x, y, z]);
+    measurement.xyz = xyz.map(Length::from_meters);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        packet::{Channel, DataPacketSynthesizer, ProductID, ReturnMode},
+        Config, FrameBuilder,
+    };
+
+    fn assert_close(a: [f64; 3], b: [f64; 3]) {
+        let dist = a.iter().zip(&b).map(|(a, b)| (a - b).powi(2)).sum::<f64>();
+        assert!(dist.sqrt() < 1e-6, "{a:?} != {b:?}");
+    }
+
+    fn add(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+        [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+    }
+
+    fn frame(start_toh: Duration) -> FrameXyz {
+        let packets = DataPacketSynthesizer::new(
+            ProductID::VLP16,
+            ReturnMode::Strongest,
+            600.0,
+            |_, laser_id| Channel {
+                distance: 5000 + laser_id as u16,
+                intensity: 100,
+            },
+        )
+        .unwrap()
+        .start_toh(start_toh)
+        .take(100);
+
+        let mut builder = FrameBuilder::new(Config::new_vlp_16_strongest()).unwrap();
+        packets.for_each(|packet| {
+            builder.push_packet(&packet);
+        });
+        builder.finish().unwrap()
+    }
+
+    fn points(frame: &FrameXyz) -> Vec<(Duration, [f64; 3])> {
+        frame
+            .clone()
+            .into_point_iter()
+            .map(|point| {
+                let point = point.try_into_single().unwrap();
+                let xyz = point.measurement.xyz.map(|v| v.as_meters());
+                (point.toh, xyz)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn deskew_constant_velocity() {
+        let mut frame = frame(Duration::from_secs(10));
+        let before = points(&frame);
+        let provider = ConstantTwist {
+            linear: [10.0, 0.0, 0.0],
+            angular: [0.0; 3],
+        };
+        deskew_frame_xyz(&mut frame, &provider, ReferenceTime::Start).unwrap();
+        let after = points(&frame);
+
+        let start = before[0].0;
+        for ((toh, xyz), (_, deskewed)) in before.iter().zip(&after) {
+            let dx = 10.0 * (*toh - start).as_secs_f64();
+            assert_close(*deskewed, add(*xyz, [dx, 0.0, 0.0]));
+        }
+    }
+
+    #[test]
+    fn deskew_across_hour() {
+        // the frame starts 50ms before the top of the hour
+        let mut frame = frame(HOUR - Duration::from_millis(50));
+        let before = points(&frame);
+        assert!(before.iter().any(|(toh, _)| *toh < Duration::from_secs(1)));
+
+        let provider = ConstantTwist {
+            linear: [0.0, 10.0, 0.0],
+            angular: [0.0; 3],
+        };
+        deskew_frame_xyz(&mut frame, &provider, ReferenceTime::End).unwrap();
+        let after = points(&frame);
+
+        let end = before
+            .iter()
+            .map(|(toh, _)| unwrap_toh(*toh, before[0].0))
+            .max()
+            .unwrap();
+        for ((toh, xyz), (_, deskewed)) in before.iter().zip(&after) {
+            let time = unwrap_toh(*toh, before[0].0);
+            let dy = -10.0 * (end - time).as_secs_f64();
+            assert!(dy.abs() < 1.1);
+            assert_close(*deskewed, add(*xyz, [0.0, dy, 0.0]));
+        }
+    }
+
+    #[test]
+    fn keep_frame_without_poses() {
+        let poses = InterpolatedPoses::new(vec![
+            (Duration::ZERO, Pose::identity()),
+            (
+                Duration::from_secs(1),
+                Pose::from_translation([1.0, 0.0, 0.0]),
+            ),
+        ])
+        .unwrap();
+
+        let mut frame = frame(Duration::from_secs(10));
+        let before = points(&frame);
+        assert!(matches!(
+            deskew_frame_xyz(&mut frame, &poses, ReferenceTime::Start),
+            Err(Error::Pose(lidar_pose::Error::PoseUnavailable(_)))
+        ));
+        assert_eq!(points(&frame), before);
+    }
+}
@@ -0,0 +1,511 @@
+//! Packet loss and timing anomaly detection.
+//!
+//! [StreamDiagnostics] inspects a sequence of data packets and reports
+//! [StreamAnomaly]s, such as dropped, duplicated and out-of-order
+//! packets. It compares the time of hour between packets against the
+//! packet period of the format, and the azimuth against the rotation
+//! rate learned from the stream.
+//!
+//! [FrameStats] summarizes the completeness of a produced
+//! [FrameXyz](crate::types::frame_xyz::FrameXyz). It is recorded while
+//! the frame is built and obtained by
+//! [FrameXyz::stats()](crate::types::frame_xyz::FrameXyz::stats).
+
+use crate::{packet::DataPacket, traits::AzimuthRange, types::format::Format};
+use measurements::Angle;
+use serde::{Deserialize, Serialize};
+use std::{f64::consts::PI, time::Duration};
+
+const HOUR_MICROS: i64 = 3_600_000_000;
+
+/// An anomaly found by [StreamDiagnostics].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StreamAnomaly {
+    /// Packets are missing before the current packet.
+    Dropped {
+        /// The estimated number of missing packets.
+        count: usize,
+        /// The time between the current and the previous packets.
+        gap: Duration,
+    },
+    /// The packet has the same timestamp as the previous one.
+    Duplicated { toh: Duration },
+    /// The packet is older than the latest received packet.
+    OutOfOrder { toh: Duration, latest: Duration },
+    /// The timestamp moves farther than
+    /// [max_gap](DiagnosticsOptions::max_gap) in either direction.
+    ///
+    /// The state is reset and the packet is taken as a new start.
+    TimeJump { from: Duration, to: Duration },
+    /// The interval is not a multiple of the packet period.
+    IrregularInterval {
+        interval: Duration,
+        expected: Duration,
+    },
+    /// The azimuth of the packet is off from the one predicted by the
+    /// rotation rate.
+    ///
+    /// The following packet is checked against the predicted azimuth
+    /// rather than this one.
+    AzimuthJump { expected: Angle, actual: Angle },
+}
+
+/// Options for [StreamDiagnostics].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiagnosticsOptions {
+    /// The period between packets.
+    ///
+    /// If it is `None`, it is derived from the format of the packet.
+    /// HDL-64E packets carry no format, and the shortest interval
+    /// seen so far is used instead.
+    pub packet_period: Option<Duration>,
+    /// The allowed deviation of an interval from a multiple of the
+    /// packet period, in the ratio to the period.
+    pub period_tolerance: f64,
+    /// Intervals longer than this are reported as
+    /// [TimeJump](StreamAnomaly::TimeJump) rather than dropped packets.
+    pub max_gap: Duration,
+    /// The allowed deviation of the azimuth from the predicted one.
+    pub azimuth_tolerance: Angle,
+}
+
+impl Default for DiagnosticsOptions {
+    fn default() -> Self {
+        Self {
+            packet_period: None,
+            period_tolerance: 0.25,
+            max_gap: Duration::from_secs(1),
+            azimuth_tolerance: Angle::from_degrees(1.0),
+        }
+    }
+}
+
+/// Accumulated numbers of [StreamDiagnostics].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DiagnosticsCounts {
+    /// The number of pushed packets.
+    pub packets: usize,
+    /// The estimated number of missing packets.
+    pub dropped: usize,
+    pub duplicated: usize,
+    pub out_of_order: usize,
+    pub time_jumps: usize,
+    pub irregular_intervals: usize,
+    pub azimuth_jumps: usize,
+}
+
+impl DiagnosticsCounts {
+    /// Gets the ratio of missing packets to expected packets.
+    pub fn loss_ratio(&self) -> f64 {
+        let expected = self.packets - self.duplicated + self.dropped;
+        if expected == 0 {
+            0.0
+        } else {
+            self.dropped as f64 / expected as f64
+        }
+    }
+}
+
+/// Detects anomalies in a stream of data packets from one sensor.
+///
+/// Packets from multiple sensors must be separated before, for
+/// example, by [FrameXyzDemux](crate::iter::FrameXyzDemux).
+#[derive(Debug, Clone, Default)]
+pub struct StreamDiagnostics {
+    options: DiagnosticsOptions,
+    counts: DiagnosticsCounts,
+    last: Option<LastPacket>,
+    /// The shortest interval seen so far in microseconds.
+    min_interval: Option<i64>,
+    /// The rotation rate in radians per microsecond.
+    azimuth_rate: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct LastPacket {
+    toh: i64,
+    azimuth: f64,
+}
+
+impl StreamDiagnostics {
+    pub fn new(options: DiagnosticsOptions) -> Self {
+        Self {
+            options,
+            ..Self::default()
+        }
+    }
+
+    pub fn options(&self) -> &DiagnosticsOptions {
+        &self.options
+    }
+
+    pub fn counts(&self) -> &DiagnosticsCounts {
+        &self.counts
+    }
+
+    /// Forgets the previous packets while keeping the counts.
+    pub fn reset(&mut self) {
+        self.last = None;
+        self.min_interval = None;
+        self.azimuth_rate = None;
+    }
+
+    /// Pushes a data packet and returns the anomalies found on it.
+    pub fn push(&mut self, packet: &DataPacket) -> Vec<StreamAnomaly> {
+        use StreamAnomaly as A;
+
+        self.counts.packets += 1;
+
+        let curr = LastPacket {
+            toh: packet.toh().as_micros() as i64,
+            azimuth: packet.blocks[0].azimuth_radians(),
+        };
+        let Some(prev) = self.last else {
+            self.last = Some(curr);
+            return vec![];
+        };
+
+        let interval = wrap_toh_diff(curr.toh - prev.toh);
+        let max_gap = self.options.max_gap.as_micros() as i64;
+
+        if interval == 0 {
+            self.counts.duplicated += 1;
+            return vec![A::Duplicated {
+                toh: micros(curr.toh),
+            }];
+        }
+
+        if interval.abs() > max_gap {
+            self.counts.time_jumps += 1;
+            self.reset();
+            self.last = Some(curr);
+            return vec![A::TimeJump {
+                from: micros(prev.toh),
+                to: micros(curr.toh),
+            }];
+        }
+
+        if interval < 0 {
+            // Keep the latest packet as the reference.
+            self.counts.out_of_order += 1;
+            return vec![A::OutOfOrder {
+                toh: micros(curr.toh),
+                latest: micros(prev.toh),
+            }];
+        }
+
+        let mut anomalies = vec![];
+        self.last = Some(curr);
+
+        let period = self
+            .options
+            .packet_period
+            .or_else(|| {
+                let format = packet.try_format()?;
+                Some(format.packet_period(packet.product_id()))
+            })
+            .map(|period| period.as_micros() as i64)
+            .unwrap_or_else(|| {
+                let min = self.min_interval.map_or(interval, |min| min.min(interval));
+                self.min_interval = Some(min);
+                min
+            });
+
+        let steps = (interval as f64 / period as f64).round() as i64;
+        let deviation = (interval - steps * period).abs() as f64;
+        let regular = steps > 0 && deviation <= period as f64 * self.options.period_tolerance;
+
+        if !regular {
+            self.counts.irregular_intervals += 1;
+            anomalies.push(A::IrregularInterval {
+                interval: micros(interval),
+                expected: micros(period),
+            });
+        } else if steps > 1 {
+            let count = steps as usize - 1;
+            self.counts.dropped += count;
+            anomalies.push(A::Dropped {
+                count,
+                gap: micros(interval),
+            });
+        }
+
+        let azimuth_diff = (curr.azimuth - prev.azimuth).rem_euclid(PI * 2.0);
+        match self.azimuth_rate {
+            Some(rate) => {
+                let expected = (prev.azimuth + rate * interval as f64).rem_euclid(PI * 2.0);
+                let error = wrap_to_pi(curr.azimuth - expected);
+
+                if error.abs() > self.options.azimuth_tolerance.as_radians() {
+                    // Predict the next packet from the expected azimuth,
+                    // so that one corrupted packet is reported once.
+                    self.last = Some(LastPacket {
+                        azimuth: expected,
+                        ..curr
+                    });
+                    self.counts.azimuth_jumps += 1;
+                    anomalies.push(A::AzimuthJump {
+                        expected: Angle::from_radians(expected),
+                        actual: Angle::from_radians(curr.azimuth),
+                    });
+                } else if regular && steps == 1 {
+                    self.azimuth_rate = Some(azimuth_diff / interval as f64);
+                }
+            }
+            None if regular && steps == 1 => {
+                self.azimuth_rate = Some(azimuth_diff / interval as f64);
+            }
+            None => {}
+        }
+
+        anomalies
+    }
+}
+
+/// The completeness of a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FrameStats {
+    /// The number of firings in the frame.
+    pub received_firings: usize,
+    /// The number of firings in the packets that were dropped while
+    /// the frame was being built.
+    pub missing_firings: usize,
+    /// The number of firings the frame would have without dropped
+    /// packets.
+    pub expected_firings: usize,
+    /// The sum of azimuth ranges covered by the firings.
+    #[serde(with = "crate::serde_units::angle")]
+    pub azimuth_coverage: Angle,
+}
+
+impl FrameStats {
+    /// Gets the ratio of received firings to expected firings.
+    pub fn completeness(&self) -> f64 {
+        if self.expected_firings == 0 {
+            0.0
+        } else {
+            self.received_firings as f64 / self.expected_firings as f64
+        }
+    }
+}
+
+/// Records [FrameStats] while frames are built.
+///
+/// Packets are fed to a [StreamDiagnostics], and the firings of the
+/// dropped packets are counted into the frame being built when the
+/// drop is detected.
+#[derive(Debug, Clone)]
+pub(crate) struct FrameStatsRecorder {
+    diagnostics: StreamDiagnostics,
+    firings_per_packet: usize,
+    missing_firings: usize,
+}
+
+impl FrameStatsRecorder {
+    pub fn new(format: Format) -> Self {
+        Self {
+            diagnostics: StreamDiagnostics::default(),
+            firings_per_packet: format.firings_per_packet(),
+            missing_firings: 0,
+        }
+    }
+
+    pub fn diagnostics(&self) -> &StreamDiagnostics {
+        &self.diagnostics
+    }
+
+    /// Pushes a packet before its firings are batched.
+    pub fn push_packet(&mut self, packet: &DataPacket) {
+        for anomaly in self.diagnostics.push(packet) {
+            if let StreamAnomaly::Dropped { count, .. } = anomaly {
+                self.missing_firings += count * self.firings_per_packet;
+            }
+        }
+    }
+
+    /// Takes the stats of a completed frame.
+    pub fn take<E>(&mut self, firings: &[E]) -> FrameStats
+    where
+        E: AzimuthRange,
+    {
+        let coverage: f64 = firings
+            .iter()
+            .map(|firing| {
+                let range = firing.azimuth_range();
+                (range.end - range.start).as_radians().max(0.0)
+            })
+            .sum();
+        let received_firings = firings.len();
+        let missing_firings = std::mem::take(&mut self.missing_firings);
+
+        FrameStats {
+            received_firings,
+            missing_firings,
+            expected_firings: received_firings + missing_firings,
+            azimuth_coverage: Angle::from_radians(coverage.min(PI * 2.0)),
+        }
+    }
+}
+
+fn micros(value: i64) -> Duration {
+    Duration::from_micros(value.unsigned_abs())
+}
+
+/// Wraps a time of hour difference into half an hour in either
+/// direction.
+fn wrap_toh_diff(diff: i64) -> i64 {
+    let diff = diff.rem_euclid(HOUR_MICROS);
+    if diff > HOUR_MICROS / 2 {
+        diff - HOUR_MICROS
+    } else {
+        diff
+    }
+}
+
+fn wrap_to_pi(radians: f64) -> f64 {
+    let radians = radians.rem_euclid(PI * 2.0);
+    if radians > PI {
+        radians - PI * 2.0
+    } else {
+        radians
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        iter::data_packet_to_frame_xyz,
+        packet::{Channel, DataPacketSynthesizer, ProductID, ReturnMode},
+        types::frame_xyz::FrameXyz,
+        Config, FrameBuilder,
+    };
+
+    fn packets(count: usize) -> Vec<DataPacket> {
+        let channel = Channel {
+            distance: 500,
+            intensity: 10,
+        };
+        DataPacketSynthesizer::new(ProductID::VLP16, ReturnMode::Strongest, 600.0, |_, _| {
+            channel
+        })
+        .unwrap()
+        .start_toh(Duration::from_secs(3599))
+        .take(count)
+        .collect()
+    }
+
+    fn push_all(packets: &[DataPacket]) -> (StreamDiagnostics, Vec<StreamAnomaly>) {
+        let mut diag = StreamDiagnostics::default();
+        let anomalies = packets
+            .iter()
+            .flat_map(|packet| diag.push(packet))
+            .collect();
+        (diag, anomalies)
+    }
+
+    fn frames(config: Config, packets: &[DataPacket]) -> Vec<FrameXyz> {
+        let mut builder = FrameBuilder::new(config).unwrap();
+        packets
+            .iter()
+            .flat_map(|packet| builder.push_packet(packet))
+            .collect()
+    }
+
+    #[test]
+    fn regular_stream_across_hour() {
+        let (diag, anomalies) = push_all(&packets(1000));
+        assert_eq!(anomalies, vec![]);
+        assert_eq!(diag.counts().packets, 1000);
+        assert_eq!(diag.counts().loss_ratio(), 0.0);
+    }
+
+    #[test]
+    fn dropped_duplicated_and_out_of_order() {
+        let mut packets = packets(20);
+        let period = packets[1].toh() - packets[0].toh();
+        packets.drain(5..8);
+        packets.insert(10, packets[9]);
+        packets.swap(14, 15);
+
+        let (diag, anomalies) = push_all(&packets);
+        assert!(matches!(
+            anomalies[0],
+            StreamAnomaly::Dropped { count: 3, gap } if gap.abs_diff(period * 4) < Duration::from_micros(4)
+        ));
+        assert_eq!(
+            anomalies[1],
+            StreamAnomaly::Duplicated {
+                toh: packets[10].toh()
+            }
+        );
+        assert!(matches!(
+            anomalies[2],
+            StreamAnomaly::Dropped { count: 1, .. }
+        ));
+        assert_eq!(
+            anomalies[3],
+            StreamAnomaly::OutOfOrder {
+                toh: packets[15].toh(),
+                latest: packets[14].toh()
+            }
+        );
+        assert_eq!(anomalies.len(), 4);
+
+        let counts = diag.counts();
+        assert_eq!(counts.dropped, 4);
+        assert_eq!(counts.duplicated, 1);
+        assert_eq!(counts.out_of_order, 1);
+    }
+
+    #[test]
+    fn azimuth_and_time_jumps() {
+        let mut packets = packets(20);
+        packets[10].blocks[0].azimuth_count = (packets[10].blocks[0].azimuth_count + 9000) % 36000;
+        packets[15].toh += 2_000_000;
+
+        let (diag, anomalies) = push_all(&packets);
+        assert!(matches!(
+            anomalies[0],
+            StreamAnomaly::AzimuthJump { expected, actual }
+                if ((actual - expected).as_degrees().rem_euclid(360.0) - 90.0).abs() < 1.0
+        ));
+        assert!(matches!(anomalies[1], StreamAnomaly::TimeJump { .. }));
+        assert!(matches!(anomalies[2], StreamAnomaly::TimeJump { .. }));
+        assert_eq!(anomalies.len(), 3);
+        assert_eq!(diag.counts().azimuth_jumps, 1);
+        assert_eq!(diag.counts().time_jumps, 2);
+    }
+
+    #[test]
+    fn frame_completeness() {
+        let config = Config::new_vlp_16_strongest();
+        let packets = packets(200);
+
+        let full = frames(config.clone(), &packets);
+        let stats = full[1].stats();
+        assert_eq!(stats.missing_firings, 0);
+        assert!(stats.completeness() > 0.99);
+        assert!(stats.azimuth_coverage.as_degrees() > 359.0);
+
+        let mut lossy: Vec<_> = packets.clone();
+        lossy.drain(100..110);
+        let lossy_frames = frames(config.clone(), &lossy);
+        let stats = lossy_frames[1].stats();
+        assert_eq!(stats.missing_firings, 10 * 24);
+        assert_eq!(
+            stats.received_firings + 10 * 24,
+            full[1].stats().received_firings
+        );
+        assert_eq!(stats.expected_firings, full[1].stats().expected_firings);
+        assert!(stats.completeness() < 0.9);
+
+        // the iterators record the same stats
+        let iter_stats: Vec<_> = data_packet_to_frame_xyz(config, lossy)
+            .unwrap()
+            .map(|frame| *frame.stats())
+            .collect();
+        let builder_stats: Vec<_> = lossy_frames.iter().map(|frame| *frame.stats()).collect();
+        assert_eq!(iter_stats, builder_stats);
+    }
+}
@@ -0,0 +1,79 @@
+//! The error type of this crate.
+
+use crate::{
+    iter::SourceFilter,
+    packet::{PacketError, ProductID, ReturnMode},
+    types::format::Format,
+};
+use std::io;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[cfg(feature = "pcap")]
+    #[error("pcap error: {0}")]
+    Pcap(#[from] pcap::Error),
+    #[cfg(feature = "velodyne-params")]
+    #[error("calibration error: {0}")]
+    Params(#[from] velodyne_params::Error),
+    #[error("packet error: {0}")]
+    Packet(#[from] PacketError),
+    #[error("unknown product ID 0x{0:02x}")]
+    UnknownProductId(u8),
+    #[error("unknown return mode 0x{0:02x}")]
+    UnknownReturnMode(u8),
+    #[error("unsupported product {0}")]
+    UnsupportedProduct(ProductID),
+    /// The number of lasers and the return mode of a config match no
+    /// [Format].
+    #[error("unsupported config with {num_lasers} lasers in {return_mode:?} mode")]
+    UnsupportedConfig {
+        return_mode: ReturnMode,
+        num_lasers: usize,
+    },
+    /// The config does not fit the format of the data.
+    #[error("expect a config in {expect:?} format, but get {num_lasers} lasers")]
+    ConfigMismatch { expect: Format, num_lasers: usize },
+    /// The sensor model or the return mode changes in a packet stream.
+    ///
+    /// A model is `None` for packets without a product ID, which are
+    /// converted with the fallback config.
+    #[error("sensor changed from {} to {}", model_name(from), model_name(to))]
+    FormatMismatch {
+        from: Option<(ProductID, ReturnMode)>,
+        to: Option<(ProductID, ReturnMode)>,
+    },
+    #[error("the source {0:?} is already registered")]
+    DuplicateSource(SourceFilter),
+    #[error("pose error: {0}")]
+    Pose(#[from] lidar_pose::Error),
+    #[error("invalid argument: {0}")]
+    InvalidArgument(String),
+}
+
+impl Error {
+    pub fn invalid_argument(desc: impl Into<String>) -> Self {
+        Self::InvalidArgument(desc.into())
+    }
+}
+
+/// Names a sensor model in messages.
+pub(crate) fn model_name(model: &Option<(ProductID, ReturnMode)>) -> String {
+    match model {
+        Some((product_id, return_mode)) => format!("{product_id} ({return_mode:?})"),
+        None => "a sensor without product ID".to_string(),
+    }
+}
+
+/// Returns an [Error::InvalidArgument] with the formatted message if
+/// the condition is false.
+macro_rules! ensure_argument {
+    ($cond:expr, $($arg:tt)+) => {
+        if !$cond {
+            return Err($crate::Error::invalid_argument(format!($($arg)+)));
+        }
+    };
+}
+pub(crate) use ensure_argument;
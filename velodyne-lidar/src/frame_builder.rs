@@ -0,0 +1,261 @@
+//! Push-based frame building.
+
+use crate::{
+    batcher::{BatchStrategy, FiringXyzBatcher},
+    diagnostics::{FrameStatsRecorder, StreamDiagnostics},
+    iter::audit_format,
+    trig_table::XyzConverter,
+    types::{
+        format::{Format, FormatKind},
+        frame_xyz::{
+            FrameXyz, FrameXyzD128, FrameXyzD16, FrameXyzD32, FrameXyzD64, FrameXyzS128,
+            FrameXyzS16, FrameXyzS32, FrameXyzS64,
+        },
+    },
+    Config, ConfigKind, DataPacket, Error,
+};
+
+/// Gathers [DataPacket]s into [FrameXyz] frames one packet at a
+/// time.
+///
+/// It is the push-style counterpart of
+/// [data_packet_to_frame_xyz](crate::iter::data_packet_to_frame_xyz),
+/// suited to packets arriving from callbacks or channels. One type
+/// handles all formats, which is chosen by the config.
+///
+/// The packets are checked by a [StreamDiagnostics], and each frame
+/// carries the [FrameStats](crate::diagnostics::FrameStats) of the
+/// packets dropped while it was built.
+///
+/// ```rust
+/// # fn main() -> anyhow::Result<()> {
+/// use velodyne_lidar::{capture::PacketReader, frame_builder::FrameBuilder, Config, Packet};
+///
+/// let mut builder = FrameBuilder::new(Config::new_vlp_32c_strongest())?;
+///
+/// for packet in PacketReader::open("tests/velodyne_vlp32.pcap")?.packets() {
+///     if let Packet::Data(packet) = packet?.packet {
+///         for frame in builder.push_packet(&packet) {
+///             println!("{} firings", frame.firing_iter().count());
+///         }
+///     }
+/// }
+///
+/// if let Some(frame) = builder.finish() {
+///     println!("{} firings in the last frame", frame.firing_iter().count());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct FrameBuilder {
+    config: ConfigKind,
+    batcher: FiringXyzBatcher,
+    converter: Option<XyzConverter>,
+    recorder: FrameStatsRecorder,
+}
+
+impl FrameBuilder {
+    /// Creates a builder that cuts frames at zero azimuth.
+    pub fn new(config: Config) -> Result<Self, Error> {
+        Self::with_strategy(config, BatchStrategy::default())
+    }
+
+    /// Creates a builder that cuts frames by the strategy.
+    pub fn with_strategy(config: Config, strategy: BatchStrategy) -> Result<Self, Error> {
+        let config = config.try_into_kind()?;
+        let batcher = FiringXyzBatcher::from_format_with_strategy(config.format(), strategy)?;
+        let recorder = FrameStatsRecorder::new(config.format());
+        Ok(Self {
+            config,
+            batcher,
+            converter: None,
+            recorder,
+        })
+    }
+
+    /// Converts points with precomputed trigonometric tables.
+    ///
+    /// The tables are interpolated as in
+    /// [Precision::Exact](crate::trig_table::Precision::Exact). See
+    /// [trig_table](crate::trig_table) for the error bounds.
+    pub fn with_trig_table(mut self) -> Self {
+        let converter = XyzConverter::from_config_kind(&self.config);
+        self.converter = Some(converter);
+        self
+    }
+
+    /// Gets the format of the frames.
+    pub fn format(&self) -> Format {
+        self.config.format()
+    }
+
+    /// Gets the diagnostics of the pushed packets.
+    pub fn diagnostics(&self) -> &StreamDiagnostics {
+        self.recorder.diagnostics()
+    }
+
+    /// Pushes a packet and returns completed frames.
+    ///
+    /// A packet of another format than the config is converted
+    /// anyway with a warning.
+    pub fn push_packet(&mut self, packet: &DataPacket) -> Vec<FrameXyz> {
+        use FormatKind as K;
+
+        audit_format(packet.try_format(), self.config.format());
+        self.recorder.push_packet(packet);
+        let converter = &self.converter;
+        let recorder = &mut self.recorder;
+
+        macro_rules! push {
+            ($config:ident, $batcher:ident, $iter_fn:ident, $frame:ident, $into:ident) => {{
+                let firings = packet.$iter_fn().map(|block| match converter {
+                    Some(converter) => {
+                        block.to_firing_xyz_with($config, |laser_id, channel, azimuth| {
+                            converter.measurement(laser_id, channel, azimuth)
+                        })
+                    }
+                    None => block.to_firing_xyz($config),
+                });
+                $batcher
+                    .push_many(firings)
+                    .map(|firings| {
+                        let stats = recorder.take(&firings);
+                        K::$into($frame { firings, stats })
+                    })
+                    .collect()
+            }};
+        }
+
+        match (&self.config, &mut self.batcher) {
+            (K::Single16(c), K::Single16(b)) => {
+                push!(c, b, firing_block_iter_s16, FrameXyzS16, from_s16)
+            }
+            (K::Single32(c), K::Single32(b)) => {
+                push!(c, b, firing_block_iter_s32, FrameXyzS32, from_s32)
+            }
+            (K::Single64(c), K::Single64(b)) => {
+                push!(c, b, firing_block_iter_s64, FrameXyzS64, from_s64)
+            }
+            (K::Single128(c), K::Single128(b)) => {
+                push!(c, b, firing_block_iter_s128, FrameXyzS128, from_s128)
+            }
+            (K::Dual16(c), K::Dual16(b)) => {
+                push!(c, b, firing_block_iter_d16, FrameXyzD16, from_d16)
+            }
+            (K::Dual32(c), K::Dual32(b)) => {
+                push!(c, b, firing_block_iter_d32, FrameXyzD32, from_d32)
+            }
+            (K::Dual64(c), K::Dual64(b)) => {
+                push!(c, b, firing_block_iter_d64, FrameXyzD64, from_d64)
+            }
+            (K::Dual128(c), K::Dual128(b)) => {
+                push!(c, b, firing_block_iter_d128, FrameXyzD128, from_d128)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Takes the incomplete frame, if any, and starts over.
+    pub fn flush(&mut self) -> Option<FrameXyz> {
+        use FormatKind as K;
+
+        let recorder = &mut self.recorder;
+
+        macro_rules! flush {
+            ($batcher:ident, $frame:ident, $into:ident) => {{
+                let firings = $batcher.take()?;
+                let stats = recorder.take(&firings);
+                K::$into($frame { firings, stats })
+            }};
+        }
+
+        Some(match &mut self.batcher {
+            K::Single16(b) => flush!(b, FrameXyzS16, from_s16),
+            K::Single32(b) => flush!(b, FrameXyzS32, from_s32),
+            K::Single64(b) => flush!(b, FrameXyzS64, from_s64),
+            K::Single128(b) => flush!(b, FrameXyzS128, from_s128),
+            K::Dual16(b) => flush!(b, FrameXyzD16, from_d16),
+            K::Dual32(b) => flush!(b, FrameXyzD32, from_d32),
+            K::Dual64(b) => flush!(b, FrameXyzD64, from_d64),
+            K::Dual128(b) => flush!(b, FrameXyzD128, from_d128),
+        })
+    }
+
+    /// Consumes the builder and outputs the last maybe incomplete
+    /// frame.
+    pub fn finish(mut self) -> Option<FrameXyz> {
+        self.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{batcher::BatchStrategy, capture::PacketReader, iter::data_packet_to_frame_xyz};
+
+    fn data_packets() -> Vec<DataPacket> {
+        PacketReader::open("tests/velodyne_vlp32.pcap")
+            .unwrap()
+            .packets()
+            .filter_map(|packet| packet.unwrap().packet.try_into_data().ok())
+            .collect()
+    }
+
+    #[test]
+    fn push_matches_iter() {
+        let config = Config::new_vlp_32c_strongest();
+        let packets = data_packets();
+
+        let expect: Vec<usize> = data_packet_to_frame_xyz(config.clone(), packets.iter().cloned())
+            .unwrap()
+            .map(|frame| frame.firing_iter().count())
+            .collect();
+
+        let mut builder = FrameBuilder::new(config).unwrap();
+        assert_eq!(builder.format(), Format::Single32);
+        let mut counts = vec![];
+        for packet in &packets {
+            counts.extend(
+                builder
+                    .push_packet(packet)
+                    .into_iter()
+                    .map(|frame| frame.firing_iter().count()),
+            );
+        }
+
+        assert!(!counts.is_empty());
+        assert_eq!(counts, expect);
+
+        let last = builder.finish().unwrap();
+        let total: usize = packets
+            .iter()
+            .map(|_| Format::Single32.firings_per_packet())
+            .sum();
+        assert_eq!(
+            counts.iter().sum::<usize>() + last.firing_iter().count(),
+            total
+        );
+    }
+
+    #[test]
+    fn flush_on_demand() {
+        let packets = data_packets();
+        let strategy = BatchStrategy::packet_count(Format::Single32, 4);
+        let mut builder =
+            FrameBuilder::with_strategy(Config::new_vlp_32c_strongest(), strategy).unwrap();
+
+        assert!(builder.flush().is_none());
+        assert!(builder.push_packet(&packets[0]).is_empty());
+        let frame = builder.flush().unwrap();
+        assert_eq!(frame.firing_iter().count(), 12);
+        assert!(builder.flush().is_none());
+
+        let frames: Vec<_> = packets[..8]
+            .iter()
+            .flat_map(|packet| builder.push_packet(packet))
+            .collect();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].firing_iter().count(), 48);
+    }
+}
@@ -3,6 +3,22 @@
 mod convert;
 pub use convert::*;
 
+mod demux;
+pub use demux::*;
+
+mod telemetry;
+pub use telemetry::*;
+
+mod udp;
+pub use udp::*;
+#[cfg(feature = "tokio")]
+pub(crate) use udp::{bind_socket, DATAGRAM_BUFFER_SIZE};
+
+pub use crate::capture::{
+    frame_xyz_iter_from_file, frame_xyz_iter_from_file_auto, packet_iter_from_file,
+    received_packet_iter_from_file,
+};
+
 #[cfg(feature = "pcap")]
 mod pcap;
 #[cfg(feature = "pcap")]
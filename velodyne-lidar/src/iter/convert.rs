@@ -6,7 +6,13 @@ pub(crate) type FrameXyzIter<'a> = Box<dyn Iterator<Item = FrameXyz> + Send + 'a
 pub(crate) type ResultFrameXyzIter<'a, E> =
     Box<dyn Iterator<Item = Result<FrameXyz, E>> + Send + 'a>;
 
-fn audit_format(packet_format: Option<Format>, config_format: Format) {
+pub(crate) fn audit_format(packet_format: Option<Format>, config_format: Format) {
+    // HDL-64E sends status bytes in place of the model and return
+    // mode, so its packets carry no format to compare with.
+    if matches!(config_format, Format::Single64 | Format::Dual64) {
+        return;
+    }
+
     match packet_format {
         Some(packet_format) => {
             if packet_format != config_format {
@@ -28,26 +34,33 @@ mod data_packet_to_frame_xyz {
     use super::{audit_format, FrameXyzIter};
     use crate::{
         batcher::Batcher,
+        diagnostics::FrameStatsRecorder,
         types::{
-            firing_xyz::{FiringXyzD16, FiringXyzD32, FiringXyzS16, FiringXyzS32},
+            firing_xyz::{
+                FiringXyzD128, FiringXyzD16, FiringXyzD32, FiringXyzD64, FiringXyzS128,
+                FiringXyzS16, FiringXyzS32, FiringXyzS64,
+            },
             format::FormatKind,
-            frame_xyz::{FrameXyzD16, FrameXyzD32, FrameXyzS16, FrameXyzS32},
+            frame_xyz::{
+                FrameXyzD128, FrameXyzD16, FrameXyzD32, FrameXyzD64, FrameXyzS128, FrameXyzS16,
+                FrameXyzS32, FrameXyzS64,
+            },
         },
-        Config, Config16, Config32, DataPacket,
+        Config, Config128, Config16, Config32, Config64, DataPacket, Error,
     };
-    use anyhow::{anyhow, Result};
 
     /// Converts an iterator of packets to an iterator of [crate::types::frame_xyz::FrameXyz].
-    pub fn data_packet_to_frame_xyz<'a, I>(config: Config, packets: I) -> Result<FrameXyzIter<'a>>
+    pub fn data_packet_to_frame_xyz<'a, I>(
+        config: Config,
+        packets: I,
+    ) -> Result<FrameXyzIter<'a>, Error>
     where
         I: IntoIterator<Item = DataPacket> + 'a,
         I::IntoIter: Send,
     {
         use FormatKind as K;
 
-        let config_kinds = config
-            .try_into_kind()
-            .map_err(|_| anyhow!("invalid configuration"))?;
+        let config_kinds = config.try_into_kind()?;
 
         let iter: FrameXyzIter = match config_kinds {
             K::Single16(config) => {
@@ -62,6 +75,18 @@ mod data_packet_to_frame_xyz {
             K::Dual32(config) => {
                 Box::new(data_packet_to_frame_xyz_d32(config, packets).map(K::from_d32))
             }
+            K::Single64(config) => {
+                Box::new(data_packet_to_frame_xyz_s64(config, packets).map(K::from_s64))
+            }
+            K::Single128(config) => {
+                Box::new(data_packet_to_frame_xyz_s128(config, packets).map(K::from_s128))
+            }
+            K::Dual64(config) => {
+                Box::new(data_packet_to_frame_xyz_d64(config, packets).map(K::from_d64))
+            }
+            K::Dual128(config) => {
+                Box::new(data_packet_to_frame_xyz_d128(config, packets).map(K::from_d128))
+            }
         };
 
         Ok(iter)
@@ -74,27 +99,23 @@ mod data_packet_to_frame_xyz {
                 I: IntoIterator<Item = DataPacket>,
                 I::IntoIter: Send,
             {
-                let batcher: Batcher<$firing> = Batcher::new();
+                let mut batcher: Batcher<$firing> = Batcher::new();
+                let mut recorder = FrameStatsRecorder::new(config.format());
 
-                packets
-                    .into_iter()
-                    .map(move |packet| {
-                        audit_format(packet.try_format(), config.format());
+                packets.into_iter().flat_map(move |packet| {
+                    audit_format(packet.try_format(), config.format());
+                    recorder.push_packet(&packet);
 
-                        let firings: Vec<$firing> = packet
-                            .$iter_fn()
-                            .map(|block| block.to_firing_xyz(&config))
-                            .collect();
-                        firings
-                    })
-                    .scan(batcher, |batcher, firings| {
-                        let frames: Vec<_> = batcher
-                            .push_many(firings)
-                            .map(|firings| $frame { firings })
-                            .collect();
-                        Some(frames)
-                    })
-                    .flatten()
+                    let firings = packet.$iter_fn().map(|block| block.to_firing_xyz(&config));
+                    let frames: Vec<_> = batcher
+                        .push_many(firings)
+                        .map(|firings| {
+                            let stats = recorder.take(&firings);
+                            $frame { firings, stats }
+                        })
+                        .collect();
+                    frames
+                })
             }
         };
     }
@@ -127,6 +148,34 @@ mod data_packet_to_frame_xyz {
         FrameXyzD32,
         firing_block_iter_d32
     );
+    declare_data_packet_to_frame_xyz_fn!(
+        data_packet_to_frame_xyz_s64,
+        Config64,
+        FiringXyzS64,
+        FrameXyzS64,
+        firing_block_iter_s64
+    );
+    declare_data_packet_to_frame_xyz_fn!(
+        data_packet_to_frame_xyz_s128,
+        Config128,
+        FiringXyzS128,
+        FrameXyzS128,
+        firing_block_iter_s128
+    );
+    declare_data_packet_to_frame_xyz_fn!(
+        data_packet_to_frame_xyz_d64,
+        Config64,
+        FiringXyzD64,
+        FrameXyzD64,
+        firing_block_iter_d64
+    );
+    declare_data_packet_to_frame_xyz_fn!(
+        data_packet_to_frame_xyz_d128,
+        Config128,
+        FiringXyzD128,
+        FrameXyzD128,
+        firing_block_iter_d128
+    );
 }
 
 pub use try_packet_to_frame_xyz::*;
@@ -134,22 +183,28 @@ mod try_packet_to_frame_xyz {
     use super::audit_format;
     use crate::{
         batcher::Batcher,
+        diagnostics::FrameStatsRecorder,
         iter::convert::ResultFrameXyzIter,
         types::{
-            firing_xyz::{FiringXyzD16, FiringXyzD32, FiringXyzS16, FiringXyzS32},
+            firing_xyz::{
+                FiringXyzD128, FiringXyzD16, FiringXyzD32, FiringXyzD64, FiringXyzS128,
+                FiringXyzS16, FiringXyzS32, FiringXyzS64,
+            },
             format::FormatKind,
-            frame_xyz::{FrameXyzD16, FrameXyzD32, FrameXyzS16, FrameXyzS32},
+            frame_xyz::{
+                FrameXyzD128, FrameXyzD16, FrameXyzD32, FrameXyzD64, FrameXyzS128, FrameXyzS16,
+                FrameXyzS32, FrameXyzS64,
+            },
         },
-        Config, Config16, Config32, Packet,
+        Config, Config128, Config16, Config32, Config64, Error, Packet,
     };
-    use anyhow::{anyhow, Result};
     use itertools::Itertools;
 
     /// Converts an iterator of packets to an iterator of [crate::types::frame_xyz::FrameXyz].
     pub fn try_packet_to_frame_xyz<'a, E, I>(
         config: Config,
         packets: I,
-    ) -> Result<ResultFrameXyzIter<'a, E>>
+    ) -> Result<ResultFrameXyzIter<'a, E>, Error>
     where
         I: IntoIterator<Item = Result<Packet, E>> + 'a,
         I::IntoIter: Send,
@@ -157,9 +212,7 @@ mod try_packet_to_frame_xyz {
     {
         use FormatKind as K;
 
-        let config_kinds = config
-            .try_into_kind()
-            .map_err(|_| anyhow!("invalid configuration"))?;
+        let config_kinds = config.try_into_kind()?;
 
         let iter: ResultFrameXyzIter<_> = match config_kinds {
             K::Single16(config) => {
@@ -174,6 +227,18 @@ mod try_packet_to_frame_xyz {
             K::Dual32(config) => {
                 Box::new(try_packet_to_frame_xyz_d32(config, packets).map_ok(K::from_d32))
             }
+            K::Single64(config) => {
+                Box::new(try_packet_to_frame_xyz_s64(config, packets).map_ok(K::from_s64))
+            }
+            K::Single128(config) => {
+                Box::new(try_packet_to_frame_xyz_s128(config, packets).map_ok(K::from_s128))
+            }
+            K::Dual64(config) => {
+                Box::new(try_packet_to_frame_xyz_d64(config, packets).map_ok(K::from_d64))
+            }
+            K::Dual128(config) => {
+                Box::new(try_packet_to_frame_xyz_d128(config, packets).map_ok(K::from_d128))
+            }
         };
 
         Ok(iter)
@@ -190,35 +255,34 @@ mod try_packet_to_frame_xyz {
                 I::IntoIter: Send,
             {
                 let batcher: Batcher<$firing> = Batcher::new();
+                let recorder = FrameStatsRecorder::new(config.format());
 
                 packets
                     .into_iter()
                     .map_ok(|packet| packet.try_into_data().ok())
                     .flatten_ok()
-                    .map_ok(move |packet| {
-                        audit_format(packet.try_format(), config.format());
-
-                        let firings: Vec<$firing> = packet
-                            .$iter_fn()
-                            .map(|block| block.to_firing_xyz(&config))
-                            .collect();
-                        firings
-                    })
-                    .scan(Some(batcher), |batcher, firings| {
-                        let firings = match firings {
-                            Ok(firings) => firings,
+                    .scan(Some((batcher, recorder)), move |state, packet| {
+                        let packet = match packet {
+                            Ok(packet) => packet,
                             Err(err) => {
-                                *batcher = None;
+                                *state = None;
                                 return Some(Err(err));
                             }
                         };
-                        let Some(batcher) = batcher else {
+                        let Some((batcher, recorder)) = state else {
                             return None;
                         };
 
+                        audit_format(packet.try_format(), config.format());
+                        recorder.push_packet(&packet);
+
+                        let firings = packet.$iter_fn().map(|block| block.to_firing_xyz(&config));
                         let frames: Vec<_> = batcher
                             .push_many(firings)
-                            .map(|firings| $frame { firings })
+                            .map(|firings| {
+                                let stats = recorder.take(&firings);
+                                $frame { firings, stats }
+                            })
                             .collect();
                         Some(Ok(frames))
                     })
@@ -255,4 +319,200 @@ mod try_packet_to_frame_xyz {
         FrameXyzD32,
         firing_block_iter_d32
     );
+    declare_try_packet_to_frame_xyz_fn!(
+        try_packet_to_frame_xyz_s64,
+        Config64,
+        FiringXyzS64,
+        FrameXyzS64,
+        firing_block_iter_s64
+    );
+    declare_try_packet_to_frame_xyz_fn!(
+        try_packet_to_frame_xyz_s128,
+        Config128,
+        FiringXyzS128,
+        FrameXyzS128,
+        firing_block_iter_s128
+    );
+    declare_try_packet_to_frame_xyz_fn!(
+        try_packet_to_frame_xyz_d64,
+        Config64,
+        FiringXyzD64,
+        FrameXyzD64,
+        firing_block_iter_d64
+    );
+    declare_try_packet_to_frame_xyz_fn!(
+        try_packet_to_frame_xyz_d128,
+        Config128,
+        FiringXyzD128,
+        FrameXyzD128,
+        firing_block_iter_d128
+    );
+}
+
+pub use auto_packet_to_frame_xyz::*;
+mod auto_packet_to_frame_xyz {
+    use super::ResultFrameXyzIter;
+    use crate::{
+        error::model_name,
+        frame_builder::FrameBuilder,
+        packet::{ProductID, ReturnMode},
+        types::frame_xyz::FrameXyz,
+        Config, DataPacket, Error, Packet,
+    };
+    use itertools::Itertools;
+    use log::info;
+
+    /// The action to take when the sensor model or the return mode
+    /// changes in the middle of a packet stream.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    pub enum FormatChangePolicy {
+        /// Emits the incomplete frame and switches to the config of
+        /// the new model.
+        #[default]
+        Reconfigure,
+        /// Stops with an error.
+        Error,
+    }
+
+    /// Converts an iterator of packets to an iterator of
+    /// [FrameXyz](crate::types::frame_xyz::FrameXyz), choosing the
+    /// built-in config from the product ID and return mode of the
+    /// first data packet.
+    ///
+    /// See [try_packet_to_frame_xyz_auto] for the `fallback` config.
+    pub fn data_packet_to_frame_xyz_auto<'a, I>(
+        packets: I,
+        policy: FormatChangePolicy,
+        fallback: Option<Config>,
+    ) -> ResultFrameXyzIter<'a, Error>
+    where
+        I: IntoIterator<Item = DataPacket> + 'a,
+        I::IntoIter: Send,
+    {
+        let packets = packets
+            .into_iter()
+            .map(|packet| Ok::<_, Error>(packet.into()));
+        try_packet_to_frame_xyz_auto(packets, policy, fallback)
+    }
+
+    /// Converts an iterator of packets to an iterator of
+    /// [FrameXyz](crate::types::frame_xyz::FrameXyz), choosing the
+    /// built-in config from the product ID and return mode of the
+    /// first data packet.
+    ///
+    /// HDL-64E packets carry status bytes in place of the product ID
+    /// and the return mode, so the model cannot be detected. Such
+    /// packets are converted with the `fallback` config, or fail with
+    /// [Error::UnknownProductId] if it is `None`. A change between
+    /// detected models and the fallback is handled by the `policy`
+    /// like a change of models.
+    ///
+    /// Status bytes that happen to match a known product ID and
+    /// return mode are taken as that model. Convert HDL-64E streams
+    /// with [try_packet_to_frame_xyz](crate::iter::try_packet_to_frame_xyz)
+    /// to rule this out.
+    ///
+    /// The iterator stops after the first error.
+    pub fn try_packet_to_frame_xyz_auto<'a, E, I>(
+        packets: I,
+        policy: FormatChangePolicy,
+        fallback: Option<Config>,
+    ) -> ResultFrameXyzIter<'a, Error>
+    where
+        I: IntoIterator<Item = Result<Packet, E>> + 'a,
+        I::IntoIter: Send,
+        E: Into<Error>,
+    {
+        let mut converter: Option<Converter> = None;
+
+        let iter = packets
+            .into_iter()
+            .map(|packet| packet.map_err(Into::into))
+            .filter_map_ok(|packet| packet.try_into_data().ok())
+            .scan(false, move |failed, packet| {
+                if *failed {
+                    return None;
+                }
+
+                let frames = packet.and_then(|packet| {
+                    convert_packet(&mut converter, &packet, policy, fallback.as_ref())
+                });
+                *failed = frames.is_err();
+                Some(frames)
+            })
+            .flatten_ok();
+
+        Box::new(iter)
+    }
+
+    fn convert_packet(
+        converter: &mut Option<Converter>,
+        packet: &DataPacket,
+        policy: FormatChangePolicy,
+        fallback: Option<&Config>,
+    ) -> Result<Vec<FrameXyz>, Error> {
+        let model = match (packet.product_id(), packet.return_mode()) {
+            (Some(product_id), Some(return_mode)) => Some((product_id, return_mode)),
+            _ if fallback.is_some() => None,
+            (None, _) => return Err(Error::UnknownProductId(packet.product_id)),
+            (Some(_), None) => return Err(Error::UnknownReturnMode(packet.return_mode)),
+        };
+
+        let mut frames = vec![];
+
+        match converter {
+            Some(conv) if conv.model == model => {}
+            Some(conv) => match policy {
+                FormatChangePolicy::Reconfigure => {
+                    info!(
+                        "sensor changed from {} to {}, reconfiguring",
+                        model_name(&conv.model),
+                        model_name(&model)
+                    );
+                    frames.extend(conv.inner.flush());
+                    *conv = Converter::new(model, fallback)?;
+                }
+                FormatChangePolicy::Error => {
+                    return Err(Error::FormatMismatch {
+                        from: conv.model,
+                        to: model,
+                    });
+                }
+            },
+            None => {
+                *converter = Some(Converter::new(model, fallback)?);
+            }
+        }
+
+        let conv = converter.as_mut().unwrap();
+        frames.extend(conv.inner.push_packet(packet));
+        Ok(frames)
+    }
+
+    /// A frame converter bound to one sensor model, or to the
+    /// fallback config if the model is `None`.
+    struct Converter {
+        model: Option<(ProductID, ReturnMode)>,
+        inner: FrameBuilder,
+    }
+
+    impl Converter {
+        fn new(
+            model: Option<(ProductID, ReturnMode)>,
+            fallback: Option<&Config>,
+        ) -> Result<Self, Error> {
+            let config = match model {
+                Some((product_id, return_mode)) => Config::try_from_model(product_id, return_mode)
+                    .ok_or(Error::UnsupportedProduct(product_id))?,
+                None => fallback
+                    .cloned()
+                    .expect("the fallback config is checked by the caller"),
+            };
+
+            Ok(Self {
+                model,
+                inner: FrameBuilder::new(config)?,
+            })
+        }
+    }
 }
@@ -0,0 +1,494 @@
+//! Splitting a packet stream of multiple sensors by source address.
+
+use super::ReceivedPacket;
+use crate::{frame_builder::FrameBuilder, types::frame_xyz::FrameXyz, Config, Error, Packet};
+use log::debug;
+use std::{
+    collections::VecDeque,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    sync::{Arc, Mutex},
+};
+
+/// Selects packets by the source address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SourceFilter {
+    /// Matches packets from an IP address on any port.
+    Ip(IpAddr),
+    /// Matches packets from an IP address and a port.
+    Addr(SocketAddr),
+}
+
+impl SourceFilter {
+    pub fn matches(&self, source: &SocketAddr) -> bool {
+        match self {
+            Self::Ip(ip) => source.ip() == *ip,
+            Self::Addr(addr) => source == addr,
+        }
+    }
+}
+
+impl From<IpAddr> for SourceFilter {
+    fn from(ip: IpAddr) -> Self {
+        Self::Ip(ip)
+    }
+}
+
+impl From<Ipv4Addr> for SourceFilter {
+    fn from(ip: Ipv4Addr) -> Self {
+        Self::Ip(ip.into())
+    }
+}
+
+impl From<Ipv6Addr> for SourceFilter {
+    fn from(ip: Ipv6Addr) -> Self {
+        Self::Ip(ip.into())
+    }
+}
+
+impl From<SocketAddr> for SourceFilter {
+    fn from(addr: SocketAddr) -> Self {
+        Self::Addr(addr)
+    }
+}
+
+#[derive(Debug)]
+struct Sensor<K> {
+    id: K,
+    filter: SourceFilter,
+    builder: FrameBuilder,
+}
+
+/// Routes data packets of multiple sensors to per-sensor frame
+/// builders by the source address.
+///
+/// Each sensor is registered with an ID of type `K`, a
+/// [SourceFilter] and its own [Config]. A packet goes to the first
+/// sensor whose filter matches. Packets matching no sensor are
+/// counted and dropped, and position packets are ignored.
+#[derive(Debug)]
+pub struct FrameXyzDemux<K> {
+    sensors: Vec<Sensor<K>>,
+    unmatched_count: usize,
+}
+
+impl<K> FrameXyzDemux<K> {
+    pub fn new() -> Self {
+        Self {
+            sensors: vec![],
+            unmatched_count: 0,
+        }
+    }
+
+    /// Registers a sensor.
+    ///
+    /// It fails if the config is invalid or if the filter is already
+    /// registered.
+    pub fn add_sensor<S>(&mut self, id: K, source: S, config: Config) -> Result<(), Error>
+    where
+        S: Into<SourceFilter>,
+    {
+        let filter = source.into();
+        if self.sensors.iter().any(|sensor| sensor.filter == filter) {
+            return Err(Error::DuplicateSource(filter));
+        }
+
+        self.sensors.push(Sensor {
+            id,
+            filter,
+            builder: FrameBuilder::new(config)?,
+        });
+        Ok(())
+    }
+
+    /// Registers a sensor in builder style.
+    pub fn with_sensor<S>(mut self, id: K, source: S, config: Config) -> Result<Self, Error>
+    where
+        S: Into<SourceFilter>,
+    {
+        self.add_sensor(id, source, config)?;
+        Ok(self)
+    }
+
+    /// Iterates over the registered sensor IDs in insertion order.
+    pub fn sensors(&self) -> impl Iterator<Item = &K> {
+        self.sensors.iter().map(|sensor| &sensor.id)
+    }
+
+    /// Gets the number of data packets that match no sensor so far.
+    pub fn unmatched_count(&self) -> usize {
+        self.unmatched_count
+    }
+
+    /// Pushes a packet and returns completed frames with the IDs of
+    /// the sensors they come from.
+    pub fn push(&mut self, packet: &ReceivedPacket) -> Vec<(K, FrameXyz)>
+    where
+        K: Clone,
+    {
+        self.push_indexed(packet)
+            .into_iter()
+            .map(|(index, frame)| (self.sensors[index].id.clone(), frame))
+            .collect()
+    }
+
+    /// Takes the incomplete frames of all sensors.
+    pub fn finish(&mut self) -> Vec<(K, FrameXyz)>
+    where
+        K: Clone,
+    {
+        self.sensors
+            .iter_mut()
+            .filter_map(|sensor| Some((sensor.id.clone(), sensor.builder.flush()?)))
+            .collect()
+    }
+
+    fn push_indexed(&mut self, packet: &ReceivedPacket) -> Vec<(usize, FrameXyz)> {
+        let Packet::Data(data) = &packet.packet else {
+            return vec![];
+        };
+
+        let Some(index) = self
+            .sensors
+            .iter()
+            .position(|sensor| sensor.filter.matches(&packet.source))
+        else {
+            debug!("drop a packet from unknown source {}", packet.source);
+            self.unmatched_count += 1;
+            return vec![];
+        };
+
+        self.sensors[index]
+            .builder
+            .push_packet(data)
+            .into_iter()
+            .map(|frame| (index, frame))
+            .collect()
+    }
+}
+
+impl<K> Default for FrameXyzDemux<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Converts an iterator of received packets from multiple sensors to
+/// an iterator of frames tagged with sensor IDs.
+///
+/// Like [try_packet_to_frame_xyz](super::try_packet_to_frame_xyz),
+/// the incomplete frames at the end are dropped. The iterator stops
+/// after the first error.
+pub fn try_demux_packet_to_frame_xyz<'a, K, E, I>(
+    mut demux: FrameXyzDemux<K>,
+    packets: I,
+) -> impl Iterator<Item = Result<(K, FrameXyz), E>> + Send + 'a
+where
+    K: Clone + Send + 'a,
+    E: Send + 'a,
+    I: IntoIterator<Item = Result<ReceivedPacket, E>>,
+    I::IntoIter: Send + 'a,
+{
+    let mut packets = packets.into_iter();
+    let mut pending = VecDeque::new();
+    let mut finished = false;
+
+    std::iter::from_fn(move || loop {
+        if let Some(frame) = pending.pop_front() {
+            return Some(Ok(frame));
+        }
+        if finished {
+            return None;
+        }
+
+        match packets.next()? {
+            Ok(packet) => pending.extend(demux.push(&packet)),
+            Err(err) => {
+                finished = true;
+                return Some(Err(err));
+            }
+        }
+    })
+}
+
+/// Splits an iterator of received packets from multiple sensors into
+/// one frame iterator per sensor, in the order of registration.
+///
+/// The iterators share the packet source. Whichever iterator runs
+/// out of frames reads more packets and queues the frames of other
+/// sensors, so an iterator that is never advanced keeps its frames
+/// in memory. An error is returned by the iterator that reads it,
+/// after which all iterators stop once their queues are drained. The
+/// incomplete frames at the end are dropped.
+pub fn try_split_packet_to_frame_xyz<'a, K, E, I>(
+    demux: FrameXyzDemux<K>,
+    packets: I,
+) -> Vec<SensorFrameXyzIter<'a, K, E>>
+where
+    K: Clone,
+    I: IntoIterator<Item = Result<ReceivedPacket, E>>,
+    I::IntoIter: Send + 'a,
+{
+    let ids: Vec<K> = demux.sensors().cloned().collect();
+    let shared = Arc::new(Mutex::new(SharedDemux {
+        queues: ids.iter().map(|_| VecDeque::new()).collect(),
+        packets: Some(Box::new(packets.into_iter())),
+        demux: demux_without_ids(demux),
+    }));
+
+    ids.into_iter()
+        .enumerate()
+        .map(|(index, id)| SensorFrameXyzIter {
+            id,
+            index,
+            shared: shared.clone(),
+        })
+        .collect()
+}
+
+fn demux_without_ids<K>(demux: FrameXyzDemux<K>) -> FrameXyzDemux<()> {
+    let FrameXyzDemux {
+        sensors,
+        unmatched_count,
+    } = demux;
+    let sensors = sensors
+        .into_iter()
+        .map(|sensor| Sensor {
+            id: (),
+            filter: sensor.filter,
+            builder: sensor.builder,
+        })
+        .collect();
+    FrameXyzDemux {
+        sensors,
+        unmatched_count,
+    }
+}
+
+type PacketIter<'a, E> = Box<dyn Iterator<Item = Result<ReceivedPacket, E>> + Send + 'a>;
+
+struct SharedDemux<'a, E> {
+    demux: FrameXyzDemux<()>,
+    queues: Vec<VecDeque<FrameXyz>>,
+    /// The packet source, or `None` after it is exhausted or fails.
+    packets: Option<PacketIter<'a, E>>,
+}
+
+/// The frame iterator of one sensor created by
+/// [try_split_packet_to_frame_xyz].
+pub struct SensorFrameXyzIter<'a, K, E> {
+    id: K,
+    index: usize,
+    shared: Arc<Mutex<SharedDemux<'a, E>>>,
+}
+
+impl<'a, K, E> SensorFrameXyzIter<'a, K, E> {
+    /// Gets the sensor ID.
+    pub fn id(&self) -> &K {
+        &self.id
+    }
+}
+
+impl<'a, K, E> Iterator for SensorFrameXyzIter<'a, K, E> {
+    type Item = Result<FrameXyz, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut guard = self.shared.lock().unwrap();
+        let shared = &mut *guard;
+
+        loop {
+            if let Some(frame) = shared.queues[self.index].pop_front() {
+                return Some(Ok(frame));
+            }
+            let packets = shared.packets.as_mut()?;
+
+            let frames = match packets.next() {
+                Some(Ok(packet)) => shared.demux.push_indexed(&packet),
+                Some(Err(err)) => {
+                    shared.packets = None;
+                    return Some(Err(err));
+                }
+                None => {
+                    shared.packets = None;
+                    return None;
+                }
+            };
+            for (index, frame) in frames {
+                shared.queues[index].push_back(frame);
+            }
+        }
+    }
+}
+
+impl<'a, K, E> std::fmt::Debug for SensorFrameXyzIter<'a, K, E>
+where
+    K: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SensorFrameXyzIter")
+            .field("id", &self.id)
+            .field("index", &self.index)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        iter::try_packet_to_frame_xyz,
+        packet::{Channel, DataPacketSynthesizer, ProductID, ReturnMode},
+        types::format::Format,
+    };
+    use std::{convert::Infallible, time::SystemTime};
+
+    const SENSOR_A: Ipv4Addr = Ipv4Addr::new(192, 168, 1, 201);
+    const SENSOR_B: Ipv4Addr = Ipv4Addr::new(192, 168, 1, 202);
+
+    fn synthesize(product_id: ProductID, count: usize) -> Vec<Packet> {
+        DataPacketSynthesizer::new(product_id, ReturnMode::Strongest, 600.0, |_, _| Channel {
+            distance: 1000,
+            intensity: 100,
+        })
+        .unwrap()
+        .take(count)
+        .map(|packet| Packet::Data(Box::new(packet)))
+        .collect()
+    }
+
+    fn received(packet: Packet, ip: Ipv4Addr) -> Result<ReceivedPacket, Infallible> {
+        Ok(ReceivedPacket {
+            packet,
+            source: SocketAddr::new(ip.into(), 2368),
+            received_at: SystemTime::UNIX_EPOCH,
+        })
+    }
+
+    /// Interleaves packets of two sensors and a sensor not registered.
+    fn interleaved() -> (
+        Vec<Packet>,
+        Vec<Packet>,
+        Vec<Result<ReceivedPacket, Infallible>>,
+    ) {
+        let packets_a = synthesize(ProductID::VLP16, 400);
+        let packets_b = synthesize(ProductID::VLP32C, 600);
+        let unknown = synthesize(ProductID::VLP16, 100);
+
+        let mut packets = vec![];
+        let mut iter_a = packets_a.iter().cloned();
+        let mut iter_b = packets_b.iter().cloned();
+        let mut iter_unknown = unknown.into_iter();
+        loop {
+            let a = iter_a.next();
+            let b = iter_b.next();
+            let c = iter_unknown.next();
+            if a.is_none() && b.is_none() {
+                break;
+            }
+            packets.extend(a.map(|packet| received(packet, SENSOR_A)));
+            packets.extend(b.map(|packet| received(packet, SENSOR_B)));
+            packets.extend(c.map(|packet| received(packet, Ipv4Addr::new(10, 0, 0, 1))));
+        }
+
+        (packets_a, packets_b, packets)
+    }
+
+    fn demux() -> FrameXyzDemux<&'static str> {
+        FrameXyzDemux::new()
+            .with_sensor("a", SENSOR_A, Config::new_vlp_16_strongest())
+            .unwrap()
+            .with_sensor("b", SENSOR_B, Config::new_vlp_32c_strongest())
+            .unwrap()
+    }
+
+    fn firing_counts(config: Config, packets: Vec<Packet>) -> Vec<usize> {
+        let packets = packets.into_iter().map(Ok::<_, Infallible>);
+        try_packet_to_frame_xyz(config, packets)
+            .unwrap()
+            .map(|frame| frame.unwrap().firing_iter().count())
+            .collect()
+    }
+
+    #[test]
+    fn demux_by_source_ip() {
+        let (packets_a, packets_b, packets) = interleaved();
+        let frames: Vec<_> = try_demux_packet_to_frame_xyz(demux(), packets)
+            .map(|frame| frame.unwrap())
+            .collect();
+
+        let counts = |sensor: &str, format: Format| -> Vec<usize> {
+            frames
+                .iter()
+                .filter(|(id, _)| *id == sensor)
+                .map(|(_, frame)| {
+                    assert_eq!(frame.format(), format);
+                    frame.firing_iter().count()
+                })
+                .collect()
+        };
+
+        let expect_a = firing_counts(Config::new_vlp_16_strongest(), packets_a);
+        let expect_b = firing_counts(Config::new_vlp_32c_strongest(), packets_b);
+        assert!(expect_a.len() > 2 && expect_b.len() > 2);
+        assert_eq!(counts("a", Format::Single16), expect_a);
+        assert_eq!(counts("b", Format::Single32), expect_b);
+    }
+
+    #[test]
+    fn demux_by_source_port() {
+        let mut demux = FrameXyzDemux::new();
+        let addr_a = SocketAddr::new(SENSOR_A.into(), 2368);
+        let addr_b = SocketAddr::new(SENSOR_A.into(), 2369);
+        demux
+            .add_sensor(0, addr_a, Config::new_vlp_16_strongest())
+            .unwrap();
+        demux
+            .add_sensor(1, addr_b, Config::new_vlp_16_strongest())
+            .unwrap();
+        assert!(demux
+            .add_sensor(2, addr_b, Config::new_vlp_16_strongest())
+            .is_err());
+
+        let mut frames = vec![];
+        for packet in synthesize(ProductID::VLP16, 200) {
+            let packet = ReceivedPacket {
+                packet,
+                source: addr_b,
+                received_at: SystemTime::UNIX_EPOCH,
+            };
+            frames.extend(demux.push(&packet));
+        }
+        frames.extend(demux.finish());
+
+        assert!(frames.len() > 2);
+        assert!(frames.iter().all(|(id, _)| *id == 1));
+        assert_eq!(demux.unmatched_count(), 0);
+    }
+
+    #[test]
+    fn split_into_sensor_iters() {
+        let (packets_a, packets_b, packets) = interleaved();
+        let mut iters = try_split_packet_to_frame_xyz(demux(), packets);
+        assert_eq!(iters.len(), 2);
+        let iter_b = iters.pop().unwrap();
+        let iter_a = iters.pop().unwrap();
+        assert_eq!(*iter_a.id(), "a");
+        assert_eq!(*iter_b.id(), "b");
+
+        // drain one sensor first to exercise the queue of the other
+        let counts_b: Vec<_> = iter_b
+            .map(|frame| frame.unwrap().firing_iter().count())
+            .collect();
+        let counts_a: Vec<_> = iter_a
+            .map(|frame| frame.unwrap().firing_iter().count())
+            .collect();
+
+        assert_eq!(
+            counts_a,
+            firing_counts(Config::new_vlp_16_strongest(), packets_a)
+        );
+        assert_eq!(
+            counts_b,
+            firing_counts(Config::new_vlp_32c_strongest(), packets_b)
+        );
+    }
+}
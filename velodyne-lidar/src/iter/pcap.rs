@@ -1,49 +1,130 @@
-//! Packet iterator creation functions.
+//! Packet iterator creation functions backed by libpcap.
+//!
+//! Capture files are read without libpcap by the
+//! [capture](crate::capture) functions, which are re-exported in
+//! [iter](crate::iter).
 
-use super::convert::{try_packet_to_frame_xyz, ResultFrameXyzIter};
-use crate::{Config, Packet};
-use anyhow::Result;
+use super::{
+    convert::{try_packet_to_frame_xyz, ResultFrameXyzIter},
+    ReceivedPacket,
+};
+use crate::{
+    capture::{CaptureEvent, EventDecoder, SkipReason},
+    Config, Error, Packet,
+};
 use pcap::{Capture, Device};
-use std::{iter, path::Path};
+use std::{
+    collections::HashMap,
+    time::{Duration, UNIX_EPOCH},
+};
 
-const UDP_HEADER_SIZE: usize = 42;
+/// Reads Velodyne packets from a [pcap::Capture].
+///
+/// It is the libpcap counterpart of
+/// [PacketReader](crate::capture::PacketReader). Frames are decoded
+/// without a BPF filter, so VLAN tagged and fragmented datagrams are
+/// kept, and every frame that does not yield a packet is reported
+/// with a [SkipReason].
+pub struct LibpcapPacketReader<A>
+where
+    A: pcap::Activated,
+{
+    capture: Capture<A>,
+    link_type: u32,
+    events: EventDecoder,
+}
 
-/// Creates a packet iterator from [pcap::Capture].
-pub fn packet_iter_from_capture<A>(
-    mut capture: Capture<A>,
-) -> Result<impl Iterator<Item = Result<Packet, pcap::Error>> + Send, pcap::Error>
+impl<A> LibpcapPacketReader<A>
 where
     A: pcap::Activated,
 {
-    capture.filter("udp", true)?;
-    let iter = iter::from_fn(move || {
-        Some(loop {
-            let packet = match capture.next_packet() {
+    pub fn new(capture: Capture<A>) -> Self {
+        let link_type = capture.get_datalink().0 as u32;
+        Self {
+            capture,
+            link_type,
+            events: EventDecoder::new(),
+        }
+    }
+
+    /// Gets the number of skipped frames for each reason so far.
+    pub fn skip_counts(&self) -> &HashMap<SkipReason, usize> {
+        self.events.skip_counts()
+    }
+
+    /// Iterates over packets and drops skipped frames.
+    ///
+    /// The skipped frames are still counted in
+    /// [skip_counts()](Self::skip_counts).
+    pub fn packets(self) -> impl Iterator<Item = Result<ReceivedPacket, pcap::Error>> {
+        self.filter_map(|event| match event {
+            Ok(CaptureEvent::Packet(packet)) => Some(Ok(packet)),
+            Ok(CaptureEvent::Skipped(_)) => None,
+            Err(err) => Some(Err(err)),
+        })
+    }
+
+    fn next_event(&mut self) -> Result<Option<CaptureEvent>, pcap::Error> {
+        loop {
+            if let Some(event) = self.events.take_dropped() {
+                return Ok(Some(event));
+            }
+
+            let packet = match self.capture.next_packet() {
                 Ok(packet) => packet,
-                Err(pcap::Error::NoMorePackets) => return None,
-                Err(err) => break Err(err),
-            };
-            let Some(slice) = packet.data.get(UDP_HEADER_SIZE..) else {
-                continue;
+                Err(pcap::Error::NoMorePackets) => return Ok(self.events.finish()),
+                Err(err) => return Err(err),
             };
-            let Ok(packet) = Packet::from_slice(slice) else {
-                continue;
-            };
-            break Ok(packet);
-        })
-    });
-    Ok(iter)
+
+            let ts = packet.header.ts;
+            let timestamp = UNIX_EPOCH
+                + Duration::from_secs(ts.tv_sec as u64)
+                + Duration::from_micros(ts.tv_usec as u64);
+
+            if let Some(event) = self.events.decode(self.link_type, packet.data, timestamp) {
+                return Ok(Some(event));
+            }
+        }
+    }
+}
+
+impl<A> Iterator for LibpcapPacketReader<A>
+where
+    A: pcap::Activated,
+{
+    type Item = Result<CaptureEvent, pcap::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_event().transpose()
+    }
 }
 
-/// Creates a packet iterator by loading from a file.
-pub fn packet_iter_from_file<P>(
-    path: P,
+/// Creates a packet iterator from [pcap::Capture], keeping the
+/// source address and the capture time of each packet.
+///
+/// Skipped frames are dropped. Use [LibpcapPacketReader] to inspect
+/// them.
+pub fn received_packet_iter_from_capture<A>(
+    capture: Capture<A>,
+) -> Result<impl Iterator<Item = Result<ReceivedPacket, pcap::Error>> + Send, pcap::Error>
+where
+    A: pcap::Activated,
+{
+    Ok(LibpcapPacketReader::new(capture).packets())
+}
+
+/// Creates a packet iterator from [pcap::Capture].
+///
+/// Skipped frames are dropped. Use [LibpcapPacketReader] to inspect
+/// them.
+pub fn packet_iter_from_capture<A>(
+    capture: Capture<A>,
 ) -> Result<impl Iterator<Item = Result<Packet, pcap::Error>> + Send, pcap::Error>
 where
-    P: AsRef<Path>,
+    A: pcap::Activated,
 {
-    let capture: Capture<pcap::Offline> = Capture::from_file(path)?;
-    packet_iter_from_capture(capture)
+    let iter = received_packet_iter_from_capture(capture)?;
+    Ok(iter.map(|packet| packet.map(|packet| packet.packet)))
 }
 
 /// Creates a packet iterator by reading a device.
@@ -61,7 +142,7 @@ where
 pub fn frame_xyz_iter_from_capture<A>(
     config: Config,
     capture: Capture<A>,
-) -> Result<ResultFrameXyzIter<'static, pcap::Error>>
+) -> Result<ResultFrameXyzIter<'static, pcap::Error>, Error>
 where
     A: pcap::Activated + 'static,
 {
@@ -70,21 +151,10 @@ where
     Ok(iter)
 }
 
-pub fn frame_xyz_iter_from_file<P>(
-    config: Config,
-    path: P,
-) -> Result<ResultFrameXyzIter<'static, pcap::Error>>
-where
-    P: AsRef<Path>,
-{
-    let capture: Capture<pcap::Offline> = Capture::from_file(path)?;
-    frame_xyz_iter_from_capture(config, capture)
-}
-
 pub fn frame_xyz_iter_from_device<D>(
     config: Config,
     device: D,
-) -> Result<ResultFrameXyzIter<'static, pcap::Error>>
+) -> Result<ResultFrameXyzIter<'static, pcap::Error>, Error>
 where
     D: Into<Device>,
 {
@@ -0,0 +1,34 @@
+//! Iterator conversion from packets to telemetry.
+
+use crate::{packet::PacketError, telemetry::Telemetry, Packet};
+
+/// Converts an iterator of packets to a time series of [Telemetry],
+/// skipping data packets.
+///
+/// It yields an error for position packets with unrecognized status
+/// fields.
+pub fn packet_to_telemetry<I>(packets: I) -> impl Iterator<Item = Result<Telemetry, PacketError>>
+where
+    I: IntoIterator<Item = Packet>,
+{
+    packets.into_iter().filter_map(|packet| match packet {
+        Packet::Position(packet) => Some(Telemetry::from_position_packet(&packet)),
+        Packet::Data(_) => None,
+    })
+}
+
+/// Converts an iterator of fallible packets to a time series of
+/// [Telemetry], skipping data packets.
+pub fn try_packet_to_telemetry<I, E>(packets: I) -> impl Iterator<Item = Result<Telemetry, E>>
+where
+    I: IntoIterator<Item = Result<Packet, E>>,
+    E: From<PacketError>,
+{
+    packets.into_iter().filter_map(|packet| match packet {
+        Ok(Packet::Position(packet)) => {
+            Some(Telemetry::from_position_packet(&packet).map_err(E::from))
+        }
+        Ok(Packet::Data(_)) => None,
+        Err(err) => Some(Err(err)),
+    })
+}
@@ -0,0 +1,308 @@
+//! Packet iterator creation functions on UDP sockets.
+
+use super::convert::{try_packet_to_frame_xyz, ResultFrameXyzIter};
+use crate::{
+    consts::{DATA_PORT, POSITION_PORT},
+    Config, Error, Packet,
+};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::{
+    io,
+    net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, SyncSender},
+        Arc,
+    },
+    thread,
+    time::{Duration, SystemTime},
+};
+
+/// Size of the buffer for one datagram, which fits both data and
+/// position packets.
+pub(crate) const DATAGRAM_BUFFER_SIZE: usize = 2048;
+
+/// Interval at which the receiving threads check if the receiver
+/// is dropped.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Options for [UdpReceiver].
+#[derive(Debug, Clone)]
+pub struct UdpOptions {
+    /// The local address to bind.
+    pub bind_ip: IpAddr,
+    /// The port to receive data packets, or `None` to skip data packets.
+    pub data_port: Option<u16>,
+    /// The port to receive position packets, or `None` to skip
+    /// position packets.
+    pub position_port: Option<u16>,
+    /// Accepts packets only from these addresses. Any address is
+    /// accepted if it is empty.
+    pub sources: Vec<IpAddr>,
+    /// The socket receive buffer size in bytes, or `None` to use the
+    /// system default.
+    pub socket_buffer_size: Option<usize>,
+    /// Maximum number of received packets waiting to be consumed.
+    /// It is not used by async streams, which rely on the socket
+    /// receive buffer instead.
+    pub queue_size: usize,
+}
+
+impl Default for UdpOptions {
+    fn default() -> Self {
+        Self {
+            bind_ip: Ipv4Addr::UNSPECIFIED.into(),
+            data_port: Some(DATA_PORT),
+            position_port: Some(POSITION_PORT),
+            sources: vec![],
+            socket_buffer_size: None,
+            queue_size: 1024,
+        }
+    }
+}
+
+/// A packet received from a socket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReceivedPacket {
+    pub packet: Packet,
+    /// The address of the sender.
+    pub source: SocketAddr,
+    /// The system time when the packet is received.
+    pub received_at: SystemTime,
+}
+
+/// Receives Velodyne packets from UDP sockets.
+///
+/// Each port is read in a background thread. The threads stop once
+/// the receiver is dropped. Datagrams that are not data or position
+/// packets are discarded.
+#[derive(Debug)]
+pub struct UdpReceiver {
+    rx: Receiver<io::Result<ReceivedPacket>>,
+    data_addr: Option<SocketAddr>,
+    position_addr: Option<SocketAddr>,
+    stop: Arc<AtomicBool>,
+}
+
+impl UdpReceiver {
+    /// Binds sockets and starts receiving packets.
+    pub fn bind(options: UdpOptions) -> io::Result<Self> {
+        let UdpOptions {
+            bind_ip,
+            data_port,
+            position_port,
+            sources,
+            socket_buffer_size,
+            queue_size,
+        } = options;
+
+        let bind = |port: u16| -> io::Result<_> {
+            let socket = bind_socket(SocketAddr::new(bind_ip, port), socket_buffer_size)?;
+            socket.set_read_timeout(Some(POLL_INTERVAL))?;
+            Ok(socket)
+        };
+        let data_socket = data_port.map(bind).transpose()?;
+        let position_socket = position_port.map(bind).transpose()?;
+
+        let data_addr = data_socket
+            .as_ref()
+            .map(UdpSocket::local_addr)
+            .transpose()?;
+        let position_addr = position_socket
+            .as_ref()
+            .map(UdpSocket::local_addr)
+            .transpose()?;
+
+        let (tx, rx) = mpsc::sync_channel(queue_size);
+        let stop = Arc::new(AtomicBool::new(false));
+
+        for socket in [data_socket, position_socket].into_iter().flatten() {
+            spawn_receiver(socket, sources.clone(), tx.clone(), stop.clone());
+        }
+
+        Ok(Self {
+            rx,
+            data_addr,
+            position_addr,
+            stop,
+        })
+    }
+
+    /// Gets the local address receiving data packets.
+    pub fn data_addr(&self) -> Option<SocketAddr> {
+        self.data_addr
+    }
+
+    /// Gets the local address receiving position packets.
+    pub fn position_addr(&self) -> Option<SocketAddr> {
+        self.position_addr
+    }
+
+    /// Waits for the next packet.
+    ///
+    /// It returns `None` if all sockets stopped due to errors.
+    pub fn recv(&self) -> Option<io::Result<ReceivedPacket>> {
+        self.rx.recv().ok()
+    }
+
+    /// Waits for the next packet for at most `timeout`.
+    pub fn recv_timeout(
+        &self,
+        timeout: Duration,
+    ) -> Result<io::Result<ReceivedPacket>, RecvTimeoutError> {
+        self.rx.recv_timeout(timeout)
+    }
+}
+
+impl Iterator for UdpReceiver {
+    type Item = io::Result<ReceivedPacket>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.recv()
+    }
+}
+
+impl Drop for UdpReceiver {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+pub(crate) fn bind_socket(addr: SocketAddr, buffer_size: Option<usize>) -> io::Result<UdpSocket> {
+    let socket = Socket::new(Domain::for_address(addr), Type::DGRAM, Some(Protocol::UDP))?;
+    if let Some(size) = buffer_size {
+        socket.set_recv_buffer_size(size)?;
+    }
+    socket.bind(&addr.into())?;
+    Ok(socket.into())
+}
+
+fn spawn_receiver(
+    socket: UdpSocket,
+    sources: Vec<IpAddr>,
+    tx: SyncSender<io::Result<ReceivedPacket>>,
+    stop: Arc<AtomicBool>,
+) {
+    thread::spawn(move || {
+        let mut buffer = [0u8; DATAGRAM_BUFFER_SIZE];
+
+        while !stop.load(Ordering::Relaxed) {
+            let (len, source) = match socket.recv_from(&mut buffer) {
+                Ok(output) => output,
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    continue
+                }
+                Err(err) => {
+                    let _ = tx.send(Err(err));
+                    break;
+                }
+            };
+            let received_at = SystemTime::now();
+
+            if !sources.is_empty() && !sources.contains(&source.ip()) {
+                continue;
+            }
+            let Ok(packet) = Packet::from_slice(&buffer[..len]) else {
+                continue;
+            };
+
+            let packet = ReceivedPacket {
+                packet,
+                source,
+                received_at,
+            };
+            if tx.send(Ok(packet)).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Creates a packet iterator by receiving from UDP sockets.
+pub fn packet_iter_from_udp(
+    options: UdpOptions,
+) -> io::Result<impl Iterator<Item = io::Result<Packet>> + Send> {
+    let receiver = UdpReceiver::bind(options)?;
+    let iter = receiver.map(|packet| packet.map(|packet| packet.packet));
+    Ok(iter)
+}
+
+pub fn frame_xyz_iter_from_udp(
+    config: Config,
+    options: UdpOptions,
+) -> Result<ResultFrameXyzIter<'static, io::Error>, Error> {
+    let packets = packet_iter_from_udp(options)?;
+    let iter = try_packet_to_frame_xyz(config, packets)?;
+    Ok(iter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataPacket, PositionPacket};
+    use std::mem;
+
+    fn loopback_options() -> UdpOptions {
+        UdpOptions {
+            bind_ip: Ipv4Addr::LOCALHOST.into(),
+            data_port: Some(0),
+            position_port: Some(0),
+            ..Default::default()
+        }
+    }
+
+    fn data_packet_bytes() -> Vec<u8> {
+        let mut bytes = vec![0; mem::size_of::<DataPacket>()];
+        for block in bytes[..1200].chunks_mut(100) {
+            block[..2].copy_from_slice(&[0xff, 0xee]);
+        }
+        bytes[1204] = 0x37;
+        bytes[1205] = 0x22;
+        bytes
+    }
+
+    fn position_packet_bytes() -> Vec<u8> {
+        // zeros are valid for all status fields
+        vec![0; mem::size_of::<PositionPacket>()]
+    }
+
+    #[test]
+    fn receive_over_loopback() -> io::Result<()> {
+        let receiver = UdpReceiver::bind(loopback_options())?;
+        let sender = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0))?;
+
+        sender.send_to(&data_packet_bytes(), receiver.data_addr().unwrap())?;
+        let received = receiver.recv_timeout(Duration::from_secs(5)).unwrap()?;
+        assert!(received.packet.as_data().is_some());
+        assert_eq!(received.source, sender.local_addr()?);
+
+        sender.send_to(&position_packet_bytes(), receiver.position_addr().unwrap())?;
+        let received = receiver.recv_timeout(Duration::from_secs(5)).unwrap()?;
+        assert!(received.packet.as_position().is_some());
+
+        // datagrams of other sizes are discarded
+        sender.send_to(&[0; 16], receiver.data_addr().unwrap())?;
+        assert!(receiver.recv_timeout(Duration::from_millis(300)).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn filter_sources() -> io::Result<()> {
+        let receiver = UdpReceiver::bind(UdpOptions {
+            sources: vec![Ipv4Addr::new(10, 0, 0, 1).into()],
+            ..loopback_options()
+        })?;
+        let sender = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0))?;
+
+        sender.send_to(&data_packet_bytes(), receiver.data_addr().unwrap())?;
+        assert!(receiver.recv_timeout(Duration::from_millis(300)).is_err());
+
+        Ok(())
+    }
+}
@@ -32,20 +32,35 @@
 //! ```
 
 pub mod batcher;
+pub mod capture;
+pub mod columnar;
 pub mod config;
 pub mod consts;
 mod convert;
+pub mod deskew;
+pub mod diagnostics;
+mod error;
+pub mod frame_builder;
 pub mod iter;
 pub mod packet;
 #[cfg(feature = "parallel")]
 pub mod par_iter;
+pub mod range_image;
+mod serde_units;
+#[cfg(feature = "tokio")]
+pub mod stream;
+pub mod telemetry;
 pub mod traits;
+pub mod trig_table;
 pub mod types;
+pub mod utc;
 mod utils;
 
 pub use config::*;
+pub use error::Error;
+pub use frame_builder::FrameBuilder;
 pub use packet::{DataPacket, Packet, PositionPacket, ProductID, ReturnMode};
 
 pub mod prelude {
-    pub use crate::traits::{AzimuthRange, FiringLike, PointField};
+    pub use crate::traits::{AzimuthRange, FiringLike, PointField, TimeOfHour};
 }
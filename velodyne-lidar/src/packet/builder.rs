@@ -0,0 +1,587 @@
+use super::{Block, BlockIdentifier, Channel, DataPacket, ProductID, ReturnMode, Status};
+use crate::{
+    consts::{AZIMUTH_COUNT_PER_REV, BLOCKS_PER_PACKET, CHANNELS_PER_BLOCK},
+    error::{ensure_argument, Error},
+    types::format::Format,
+};
+use std::{array, time::Duration};
+
+/// Number of distinct azimuth counts in one revolution.
+const AZIMUTH_COUNT_MODULO: u32 = AZIMUTH_COUNT_PER_REV as u32 - 1;
+
+/// Timestamps wrap around at the top of each hour.
+const TOH_MODULO_MICROS: u128 = 3_600_000_000;
+
+/// Builds a [DataPacket] from firings.
+///
+/// Firings are laid out in blocks the same way the sensor does, so
+/// the packet decodes back to the pushed firings. The number of
+/// pushed firings must be
+/// [firings_per_packet()](Format::firings_per_packet) of the format.
+///
+/// The sensor reports one azimuth per block. On 16-line models, two
+/// firings share a block and the azimuth of the latter firing is not
+/// encoded.
+#[derive(Debug, Clone)]
+pub struct DataPacketBuilder {
+    format: Format,
+    return_mode: u8,
+    product_id: u8,
+    toh: Duration,
+    firings: Vec<FiringChannels>,
+}
+
+#[derive(Debug, Clone)]
+struct FiringChannels {
+    azimuth_count: u16,
+    strongest: Vec<Channel>,
+    last: Vec<Channel>,
+}
+
+impl DataPacketBuilder {
+    /// Creates a builder for a sensor model.
+    ///
+    /// It fails if the model is not supported.
+    pub fn new(product_id: ProductID, return_mode: ReturnMode) -> Result<Self, Error> {
+        let format = Format::try_from_model(product_id, return_mode)
+            .ok_or(Error::UnsupportedProduct(product_id))?;
+
+        Ok(Self {
+            format,
+            return_mode: return_mode as u8,
+            product_id: product_id as u8,
+            toh: Duration::ZERO,
+            firings: vec![],
+        })
+    }
+
+    /// Creates a builder for HDL-64E.
+    ///
+    /// The status bytes are zeros unless set by [status()](Self::status).
+    pub fn new_hdl_64e(return_mode: ReturnMode) -> Self {
+        Self {
+            format: Format::new(64, return_mode),
+            return_mode: 0,
+            product_id: 0,
+            toh: Duration::ZERO,
+            firings: vec![],
+        }
+    }
+
+    /// Sets the HDL-64E status bytes, which take the place of the
+    /// return mode and product ID.
+    pub fn status(mut self, status: Status) -> Self {
+        self.return_mode = status.status_type;
+        self.product_id = status.value;
+        self
+    }
+
+    /// Sets the packet timestamp, which must be less than one hour.
+    pub fn toh(mut self, toh: Duration) -> Self {
+        self.toh = toh;
+        self
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Appends a firing in single return mode.
+    ///
+    /// The length of `channels` must be the number of lasers.
+    pub fn push_firing(
+        &mut self,
+        azimuth_count: u16,
+        channels: &[Channel],
+    ) -> Result<&mut Self, Error> {
+        ensure_argument!(
+            !self.format.is_dual(),
+            "format {:?} expects dual return firings",
+            self.format
+        );
+        self.push(azimuth_count, channels, &[])
+    }
+
+    /// Appends a firing in dual return mode.
+    ///
+    /// The lengths of `strongest` and `last` must be the number of
+    /// lasers.
+    pub fn push_firing_dual(
+        &mut self,
+        azimuth_count: u16,
+        strongest: &[Channel],
+        last: &[Channel],
+    ) -> Result<&mut Self, Error> {
+        ensure_argument!(
+            self.format.is_dual(),
+            "format {:?} expects single return firings",
+            self.format
+        );
+        ensure_argument!(
+            last.len() == self.format.num_lines(),
+            "expect {} channels, but get {}",
+            self.format.num_lines(),
+            last.len()
+        );
+        self.push(azimuth_count, strongest, last)
+    }
+
+    fn push(
+        &mut self,
+        azimuth_count: u16,
+        strongest: &[Channel],
+        last: &[Channel],
+    ) -> Result<&mut Self, Error> {
+        ensure_argument!(
+            self.firings.len() < self.format.firings_per_packet(),
+            "a packet of format {:?} holds at most {} firings",
+            self.format,
+            self.format.firings_per_packet()
+        );
+        ensure_argument!(
+            (azimuth_count as u32) < AZIMUTH_COUNT_MODULO,
+            "azimuth count {azimuth_count} is out of range"
+        );
+        ensure_argument!(
+            strongest.len() == self.format.num_lines(),
+            "expect {} channels, but get {}",
+            self.format.num_lines(),
+            strongest.len()
+        );
+
+        self.firings.push(FiringChannels {
+            azimuth_count,
+            strongest: strongest.to_vec(),
+            last: last.to_vec(),
+        });
+        Ok(self)
+    }
+
+    /// Builds the packet.
+    pub fn build(&self) -> Result<DataPacket, Error> {
+        use BlockIdentifier as B;
+        use Format::*;
+
+        ensure_argument!(
+            self.firings.len() == self.format.firings_per_packet(),
+            "a packet of format {:?} requires {} firings, but get {}",
+            self.format,
+            self.format.firings_per_packet(),
+            self.firings.len()
+        );
+        let toh = self.toh.as_micros();
+        ensure_argument!(
+            toh < TOH_MODULO_MICROS,
+            "timestamp {:?} exceeds one hour",
+            self.toh
+        );
+
+        let banks = [
+            B::Block0To31,
+            B::Block32To63,
+            B::Block64To95,
+            B::Block96To127,
+        ];
+        let mut blocks: Vec<Block> = Vec::with_capacity(BLOCKS_PER_PACKET);
+
+        match self.format {
+            Single16 | Dual16 => {
+                for pair in self.firings.chunks(2) {
+                    let [former, latter] = pair else {
+                        unreachable!();
+                    };
+                    let azimuth_count = former.azimuth_count;
+                    let strongest = [&former.strongest[..], &latter.strongest].concat();
+                    blocks.push(new_block(B::Block0To31, azimuth_count, &strongest));
+
+                    if self.format == Dual16 {
+                        let last = [&former.last[..], &latter.last].concat();
+                        blocks.push(new_block(B::Block0To31, azimuth_count, &last));
+                    }
+                }
+            }
+            Single32 | Dual32 => {
+                for firing in &self.firings {
+                    let azimuth_count = firing.azimuth_count;
+                    blocks.push(new_block(B::Block0To31, azimuth_count, &firing.strongest));
+
+                    if self.format == Dual32 {
+                        blocks.push(new_block(B::Block0To31, azimuth_count, &firing.last));
+                    }
+                }
+            }
+            Single64 | Single128 => {
+                for firing in &self.firings {
+                    let channels = firing.strongest.chunks(CHANNELS_PER_BLOCK);
+                    for (bank, channels) in banks.into_iter().zip(channels) {
+                        blocks.push(new_block(bank, firing.azimuth_count, channels));
+                    }
+                }
+            }
+            Dual64 => {
+                // the strongest returns of both banks are followed by
+                // the last returns
+                for firing in &self.firings {
+                    let channels = firing
+                        .strongest
+                        .chunks(CHANNELS_PER_BLOCK)
+                        .chain(firing.last.chunks(CHANNELS_PER_BLOCK));
+                    for (bank, channels) in banks[..2].iter().cycle().zip(channels) {
+                        blocks.push(new_block(*bank, firing.azimuth_count, channels));
+                    }
+                }
+            }
+            Dual128 => {
+                // each bank stores the strongest and the last returns
+                // in a pair of blocks
+                let firing = &self.firings[0];
+                let strongest = firing.strongest.chunks(CHANNELS_PER_BLOCK);
+                let last = firing.last.chunks(CHANNELS_PER_BLOCK);
+
+                for ((bank, strongest), last) in banks.into_iter().zip(strongest).zip(last) {
+                    blocks.push(new_block(bank, firing.azimuth_count, strongest));
+                    blocks.push(new_block(bank, firing.azimuth_count, last));
+                }
+
+                // the remaining blocks are unused
+                let empty = [Channel {
+                    distance: 0,
+                    intensity: 0,
+                }; CHANNELS_PER_BLOCK];
+                for bank in banks {
+                    blocks.push(new_block(bank, firing.azimuth_count, &empty));
+                }
+            }
+        }
+
+        let blocks: [Block; BLOCKS_PER_PACKET] =
+            blocks.try_into().unwrap_or_else(|_| unreachable!());
+
+        Ok(DataPacket {
+            blocks,
+            toh: toh as u32,
+            return_mode: self.return_mode,
+            product_id: self.product_id,
+        })
+    }
+}
+
+fn new_block(block_identifier: BlockIdentifier, azimuth_count: u16, channels: &[Channel]) -> Block {
+    Block {
+        block_identifier: block_identifier as u16,
+        azimuth_count,
+        channels: array::from_fn(|index| channels[index]),
+    }
+}
+
+/// Generates data packets of a rotating sensor.
+///
+/// The channels of each laser are computed by a function of the
+/// azimuth count and the laser index. In dual return mode, the
+/// channel is used for both the strongest and the last returns.
+/// The timestamp wraps around at the top of each hour.
+pub struct DataPacketSynthesizer<F>
+where
+    F: FnMut(u16, usize) -> Channel,
+{
+    template: DataPacketBuilder,
+    firing_period: Duration,
+    /// Azimuth counts advanced per firing.
+    azimuth_step: f64,
+    toh: Duration,
+    azimuth: f64,
+    channel_fn: F,
+}
+
+impl<F> DataPacketSynthesizer<F>
+where
+    F: FnMut(u16, usize) -> Channel,
+{
+    /// Creates a synthesizer for a sensor model spinning at `rpm`.
+    pub fn new(
+        product_id: ProductID,
+        return_mode: ReturnMode,
+        rpm: f64,
+        channel_fn: F,
+    ) -> Result<Self, Error> {
+        let template = DataPacketBuilder::new(product_id, return_mode)?;
+        Self::from_template(template, Some(product_id), rpm, channel_fn)
+    }
+
+    /// Creates a synthesizer for HDL-64E spinning at `rpm`.
+    pub fn new_hdl_64e(return_mode: ReturnMode, rpm: f64, channel_fn: F) -> Result<Self, Error> {
+        let template = DataPacketBuilder::new_hdl_64e(return_mode);
+        Self::from_template(template, None, rpm, channel_fn)
+    }
+
+    fn from_template(
+        template: DataPacketBuilder,
+        product_id: Option<ProductID>,
+        rpm: f64,
+        channel_fn: F,
+    ) -> Result<Self, Error> {
+        if !(rpm.is_finite() && rpm > 0.0) {
+            return Err(Error::invalid_argument(format!(
+                "rpm must be positive, but get {rpm}"
+            )));
+        }
+
+        let firing_period = template.format.firing_period(product_id);
+        let azimuth_step = rpm / 60.0 * AZIMUTH_COUNT_MODULO as f64 * firing_period.as_secs_f64();
+
+        Ok(Self {
+            template,
+            firing_period,
+            azimuth_step,
+            toh: Duration::ZERO,
+            azimuth: 0.0,
+            channel_fn,
+        })
+    }
+
+    /// Sets the timestamp of the next packet.
+    pub fn start_toh(mut self, toh: Duration) -> Self {
+        self.toh = toh;
+        self
+    }
+
+    /// Sets the azimuth count of the next firing.
+    pub fn start_azimuth_count(mut self, azimuth_count: u16) -> Self {
+        self.azimuth = (azimuth_count as u32 % AZIMUTH_COUNT_MODULO) as f64;
+        self
+    }
+
+    fn next_packet(&mut self) -> DataPacket {
+        let format = self.template.format;
+        let num_lines = format.num_lines();
+        let num_firings = format.firings_per_packet();
+
+        let toh_micros = self.toh.as_micros() % TOH_MODULO_MICROS;
+        let mut builder = self
+            .template
+            .clone()
+            .toh(Duration::from_micros(toh_micros as u64));
+
+        for _ in 0..num_firings {
+            let azimuth_count = self.azimuth as u16;
+            let channels: Vec<_> = (0..num_lines)
+                .map(|laser_id| (self.channel_fn)(azimuth_count, laser_id))
+                .collect();
+
+            let result = if format.is_dual() {
+                builder.push_firing_dual(azimuth_count, &channels, &channels)
+            } else {
+                builder.push_firing(azimuth_count, &channels)
+            };
+            result.unwrap();
+
+            self.azimuth = (self.azimuth + self.azimuth_step) % AZIMUTH_COUNT_MODULO as f64;
+        }
+
+        self.toh += self.firing_period * num_firings as u32;
+        builder.build().unwrap()
+    }
+}
+
+impl<F> Iterator for DataPacketSynthesizer<F>
+where
+    F: FnMut(u16, usize) -> Channel,
+{
+    type Item = DataPacket;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_packet())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{types::firing_raw::FiringRaw, Packet, PositionPacket};
+
+    fn channels(len: usize, offset: usize) -> Vec<Channel> {
+        (0..len)
+            .map(|index| Channel {
+                distance: (offset + index) as u16 + 1,
+                intensity: index as u8,
+            })
+            .collect()
+    }
+
+    fn build(mut builder: DataPacketBuilder) -> DataPacket {
+        let format = builder.format();
+        for index in 0..format.firings_per_packet() {
+            let azimuth_count = index as u16 * 20;
+            let strongest = channels(format.num_lines(), index * 1000);
+            if format.is_dual() {
+                let last = channels(format.num_lines(), index * 1000 + 500);
+                builder
+                    .push_firing_dual(azimuth_count, &strongest, &last)
+                    .unwrap();
+            } else {
+                builder.push_firing(azimuth_count, &strongest).unwrap();
+            }
+        }
+        builder.toh(Duration::from_micros(1000)).build().unwrap()
+    }
+
+    #[test]
+    fn round_trip_all_formats() {
+        use ProductID as P;
+        use ReturnMode as R;
+
+        let builders = [
+            DataPacketBuilder::new(P::VLP16, R::Strongest).unwrap(),
+            DataPacketBuilder::new(P::VLP16, R::Dual).unwrap(),
+            DataPacketBuilder::new(P::VLP32C, R::Last).unwrap(),
+            DataPacketBuilder::new(P::HDL32E, R::Dual).unwrap(),
+            DataPacketBuilder::new_hdl_64e(R::Strongest),
+            DataPacketBuilder::new_hdl_64e(R::Dual),
+            DataPacketBuilder::new(P::VLS128, R::Strongest).unwrap(),
+            DataPacketBuilder::new(P::VLS128, R::Dual).unwrap(),
+        ];
+
+        for builder in builders {
+            let format = builder.format();
+            let packet = build(builder);
+
+            // the bytes decode to the same packet
+            let decoded = DataPacket::from_slice(packet.as_bytes()).unwrap();
+            assert_eq!(*decoded, packet);
+            assert_eq!(DataPacket::from_bytes(packet.to_bytes()), packet);
+
+            let firings: Vec<FiringRaw> = match format {
+                Format::Single16 => packet
+                    .firing_block_iter_s16()
+                    .map(|f| FiringRaw::from_s16(f.to_firing_raw()))
+                    .collect(),
+                Format::Single32 => packet
+                    .firing_block_iter_s32()
+                    .map(|f| FiringRaw::from_s32(f.to_firing_raw()))
+                    .collect(),
+                Format::Single64 => packet
+                    .firing_block_iter_s64()
+                    .map(|f| FiringRaw::from_s64(f.to_firing_raw()))
+                    .collect(),
+                Format::Single128 => packet
+                    .firing_block_iter_s128()
+                    .map(|f| FiringRaw::from_s128(f.to_firing_raw()))
+                    .collect(),
+                Format::Dual16 => packet
+                    .firing_block_iter_d16()
+                    .map(|f| FiringRaw::from_d16(f.to_firing_raw()))
+                    .collect(),
+                Format::Dual32 => packet
+                    .firing_block_iter_d32()
+                    .map(|f| FiringRaw::from_d32(f.to_firing_raw()))
+                    .collect(),
+                Format::Dual64 => packet
+                    .firing_block_iter_d64()
+                    .map(|f| FiringRaw::from_d64(f.to_firing_raw()))
+                    .collect(),
+                Format::Dual128 => packet
+                    .firing_block_iter_d128()
+                    .map(|f| FiringRaw::from_d128(f.to_firing_raw()))
+                    .collect(),
+            };
+            assert_eq!(firings.len(), format.firings_per_packet());
+
+            for (index, firing) in firings.iter().enumerate() {
+                let strongest = channels(format.num_lines(), index * 1000);
+                let last = channels(format.num_lines(), index * 1000 + 500);
+
+                let ok = match firing {
+                    FiringRaw::Single16(f) => f.channels[..] == strongest[..],
+                    FiringRaw::Single32(f) => f.channels[..] == strongest[..],
+                    FiringRaw::Single64(f) => f.channels[..] == strongest[..],
+                    FiringRaw::Single128(f) => f.channels[..] == strongest[..],
+                    FiringRaw::Dual16(f) => {
+                        f.channels.strongest[..] == strongest[..] && f.channels.last[..] == last[..]
+                    }
+                    FiringRaw::Dual32(f) => {
+                        f.channels.strongest[..] == strongest[..] && f.channels.last[..] == last[..]
+                    }
+                    FiringRaw::Dual64(f) => {
+                        f.channels.strongest[..] == strongest[..] && f.channels.last[..] == last[..]
+                    }
+                    FiringRaw::Dual128(f) => {
+                        f.channels.strongest[..] == strongest[..] && f.channels.last[..] == last[..]
+                    }
+                };
+                assert!(ok, "channels of firing {index} differ in format {format:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn reject_invalid_firings() {
+        let mut builder = DataPacketBuilder::new(ProductID::VLP32C, ReturnMode::Strongest).unwrap();
+        assert!(builder.push_firing(0, &channels(16, 0)).is_err());
+        assert!(builder
+            .push_firing_dual(0, &channels(32, 0), &channels(32, 0))
+            .is_err());
+        assert!(builder.push_firing(36000, &channels(32, 0)).is_err());
+        assert!(builder.build().is_err());
+
+        for _ in 0..12 {
+            builder.push_firing(0, &channels(32, 0)).unwrap();
+        }
+        assert!(builder.push_firing(0, &channels(32, 0)).is_err());
+        assert!(builder.build().is_ok());
+
+        assert!(DataPacketBuilder::new(ProductID::Velarray, ReturnMode::Strongest).is_err());
+    }
+
+    #[test]
+    fn synthesize_packets() {
+        let packets: Vec<_> = DataPacketSynthesizer::new(
+            ProductID::VLP16,
+            ReturnMode::Strongest,
+            600.0,
+            |_, laser_id| Channel {
+                distance: 1000 + laser_id as u16,
+                intensity: 100,
+            },
+        )
+        .unwrap()
+        .start_toh(Duration::from_secs(3599))
+        .take(1000)
+        .collect();
+
+        for packet in &packets {
+            assert_eq!(packet.format().unwrap(), Format::Single16);
+        }
+
+        // 24 firings of 55.296us in each packet
+        let toh_step = packets[1].toh - packets[0].toh;
+        assert_eq!(toh_step, 1327);
+
+        // 600 rpm advances about 4.78 degrees each packet
+        let azimuth_step = packets[1].blocks[0].azimuth_count - packets[0].blocks[0].azimuth_count;
+        assert!((477..=478).contains(&azimuth_step));
+
+        // the timestamp wraps around at the top of the hour
+        assert!(packets.iter().any(|packet| packet.toh < 1_000_000));
+    }
+
+    #[test]
+    fn position_packet_with_nmea() {
+        let sentence = "$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6A";
+
+        let mut packet = PositionPacket {
+            toh: 1000,
+            ..Default::default()
+        };
+        packet.set_nmea(sentence).unwrap();
+        assert!(packet.set_nmea(&"$".repeat(129)).is_err());
+
+        let bytes = Packet::from(packet).to_bytes();
+        let decoded = Packet::from_slice(&bytes).unwrap();
+        let decoded = decoded.as_position().unwrap();
+        assert_eq!(decoded.nmea_sentence(), sentence.as_bytes());
+        assert_eq!(decoded.toh(), Duration::from_millis(1));
+
+        #[cfg(feature = "nmea")]
+        assert!(decoded.parse_nmea().is_ok());
+    }
+}
@@ -1,30 +1,46 @@
+use super::{PacketError, PacketKind};
 use crate::{
-    consts::{AZIMUTH_COUNT_PER_REV, BLOCKS_PER_PACKET, CHANNELS_PER_BLOCK, FIRING_PERIOD},
+    consts::{
+        self, AZIMUTH_COUNT_PER_REV, BLOCKS_PER_PACKET, CHANNELS_PER_BLOCK, CHANNEL_PERIOD,
+        FIRING_PERIOD,
+    },
     types::{
         channel_array::ChannelArrayDRef,
-        firing_block::{FiringBlockD16, FiringBlockD32, FiringBlockS16, FiringBlockS32},
-        firing_xyz::{FiringXyzD16, FiringXyzD32, FiringXyzS16, FiringXyzS32},
+        firing_block::{
+            FiringBlockD128, FiringBlockD16, FiringBlockD32, FiringBlockD64, FiringBlockS128,
+            FiringBlockS16, FiringBlockS32, FiringBlockS64,
+        },
+        firing_xyz::{
+            FiringXyzD128, FiringXyzD16, FiringXyzD32, FiringXyzD64, FiringXyzS128, FiringXyzS16,
+            FiringXyzS32, FiringXyzS64,
+        },
         format::{Format, FormatKind},
     },
     utils::AngleExt as _,
-    Config16, Config32,
+    Config128, Config16, Config32, Config64, Error,
 };
-use anyhow::{ensure, Result};
 use itertools::{chain, izip, Itertools as _};
 use measurements::Angle;
-use std::{f64::consts::PI, iter, mem, time::Duration};
+use std::{array, f64::consts::PI, iter, mem, time::Duration};
+use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned};
 
-/// Represents the block index in range from 0 to 31, or from 32 to 63.
+/// Represents the laser bank of a block.
+///
+/// VLS-128 uses all four banks, while other models use the first two.
 #[repr(u16)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::FromRepr)]
 pub enum BlockIdentifier {
     Block0To31 = 0xeeff,
     Block32To63 = 0xddff,
+    Block64To95 = 0xccff,
+    Block96To127 = 0xbbff,
 }
 
 /// Represents the way the sensor measures the laser signal.
 #[repr(u8)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, strum::FromRepr, serde::Serialize, serde::Deserialize,
+)]
 pub enum ReturnMode {
     Strongest = 0x37,
     Last = 0x38,
@@ -44,7 +60,16 @@ impl ReturnMode {
 /// Represents the hardware model.
 #[repr(u8)]
 #[derive(
-    Debug, Clone, Copy, PartialEq, Eq, Hash, strum::AsRefStr, strum::Display, strum::EnumString,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::Display,
+    strum::EnumString,
+    strum::FromRepr,
 )]
 pub enum ProductID {
     HDL32E = 0x21,
@@ -57,22 +82,83 @@ pub enum ProductID {
 }
 
 impl ProductID {
-    pub fn num_lines(&self) -> usize {
-        match self {
-            Self::HDL32E => 16,
+    /// Gets the number of lasers, or `None` if the model is not
+    /// supported.
+    ///
+    /// Velarray is a non-rotating sensor whose data does not fit in the
+    /// firing layouts here, and is not supported yet.
+    pub fn num_lines(&self) -> Option<usize> {
+        Some(match self {
+            Self::HDL32E => 32,
             Self::VLP16 => 16,
             Self::PuckLite => 16,
             Self::PuckHiRes => 16,
             Self::VLP32C => 32,
-            Self::Velarray => todo!(),
+            Self::Velarray => return None,
             Self::VLS128 => 128,
+        })
+    }
+
+    /// Period of one vertical scan of all lasers.
+    pub fn firing_period(&self) -> Duration {
+        match self {
+            Self::HDL32E => consts::hdl_32e::FIRING_PERIOD,
+            Self::VLS128 => consts::vls_128::FIRING_PERIOD,
+            _ => FIRING_PERIOD,
+        }
+    }
+
+    /// Period between two consecutive laser returns in a firing.
+    pub fn channel_period(&self) -> Duration {
+        match self {
+            Self::HDL32E => consts::hdl_32e::CHANNEL_PERIOD,
+            Self::VLS128 => consts::vls_128::CHANNEL_PERIOD,
+            _ => CHANNEL_PERIOD,
         }
     }
 }
 
+/// Kinds of status values sent by HDL-64E.
+///
+/// HDL-64E sends one status byte pair per packet in place of the
+/// return mode and product ID, cycling through the status kinds
+/// across packets.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::FromRepr)]
+pub enum StatusType {
+    Hours = b'H',
+    Minutes = b'M',
+    Seconds = b'S',
+    Date = b'D',
+    Month = b'N',
+    Year = b'Y',
+    GpsStatus = b'G',
+    Temperature = b'T',
+    Version = b'V',
+}
+
+/// The status byte pair of an HDL-64E packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Status {
+    /// The raw status type byte.
+    pub status_type: u8,
+    /// The status value.
+    pub value: u8,
+}
+
+impl Status {
+    /// Gets the kind of the status value, or `None` for types not
+    /// listed in [StatusType].
+    pub fn kind(&self) -> Option<StatusType> {
+        StatusType::from_repr(self.status_type)
+    }
+}
+
 /// Represents a point of measurement.
 #[repr(C, packed)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, FromBytes, IntoBytes, KnownLayout, Immutable, Unaligned,
+)]
 pub struct Channel {
     /// The raw distance of laser return.
     pub distance: u16,
@@ -82,10 +168,14 @@ pub struct Channel {
 
 /// Represents a sequence of measurements with meta data.
 #[repr(C, packed)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, FromBytes, IntoBytes, KnownLayout, Immutable, Unaligned,
+)]
 pub struct Block {
-    /// Represents the block that the firing belongs to.
-    pub block_identifier: BlockIdentifier,
+    /// The raw identifier of the laser bank of the block.
+    ///
+    /// See [BlockIdentifier] for valid values.
+    pub block_identifier: u16,
     /// Encoder count of rotation motor ranging from 0 to 36000 (inclusive).
     pub azimuth_count: u16,
     /// Array of channels.
@@ -93,6 +183,12 @@ pub struct Block {
 }
 
 impl Block {
+    /// Gets the laser bank, or `None` if the identifier is not
+    /// recognized.
+    pub fn block_identifier(&self) -> Option<BlockIdentifier> {
+        BlockIdentifier::from_repr(self.block_identifier)
+    }
+
     pub fn azimuth_radians(&self) -> f64 {
         2.0 * PI * self.azimuth_count as f64 / (AZIMUTH_COUNT_PER_REV - 1) as f64
     }
@@ -107,66 +203,211 @@ impl Block {
 }
 
 /// Represents a data packet from Velodyne sensor.
+///
+/// All fields are plain integers, so that any byte sequence of the
+/// right size is a packet. Enumerated fields are checked by the
+/// accessors, and by [from_slice()](Self::from_slice) for block
+/// identifiers.
 #[repr(C, packed)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, FromBytes, IntoBytes, KnownLayout, Immutable, Unaligned,
+)]
 pub struct DataPacket {
     /// Sensor data.
     pub blocks: [Block; BLOCKS_PER_PACKET],
     /// Timestamp in microseconds.
     pub toh: u32,
     /// Indicates single return mode or dual return mode.
-    pub return_mode: ReturnMode,
+    ///
+    /// It is the status type byte on HDL-64E. Use
+    /// [return_mode()](Self::return_mode) to get the [ReturnMode].
+    pub return_mode: u8,
     /// Sensor model.
-    pub product_id: ProductID,
+    ///
+    /// It is the status value byte on HDL-64E. Use
+    /// [product_id()](Self::product_id) to get the [ProductID].
+    pub product_id: u8,
 }
 
 impl DataPacket {
     /// Construct packet from binary buffer.
+    ///
+    /// Block identifiers are not checked. Use [validate()](Self::validate)
+    /// to check them.
     pub fn from_bytes(buffer: [u8; mem::size_of::<Self>()]) -> Self {
-        unsafe { mem::transmute::<_, Self>(buffer) }
-    }
-
-    /// Construct packet from slice of bytes. Fail if the slice size is not correct.
-    pub fn from_slice(buffer: &[u8]) -> Result<&Self> {
-        ensure!(
-            buffer.len() == mem::size_of::<Self>(),
-            "Requre the slice length to be {}, but get {}",
-            mem::size_of::<Self>(),
-            buffer.len(),
-        );
-        let packet = unsafe { &*(buffer.as_ptr() as *const Self) };
+        zerocopy::transmute!(buffer)
+    }
+
+    /// Construct packet from slice of bytes.
+    ///
+    /// It fails if the slice size is not correct or the identifier of
+    /// a decoded block is not recognized, see [validate()](Self::validate).
+    pub fn from_slice(buffer: &[u8]) -> Result<&Self, PacketError> {
+        let packet = Self::ref_from_bytes(buffer).map_err(|_| PacketError::SizeMismatch {
+            kind: PacketKind::Data,
+            expect: mem::size_of::<Self>(),
+            actual: buffer.len(),
+        })?;
+        packet.validate()?;
         Ok(packet)
     }
 
+    /// Checks that the identifiers of the decoded blocks are
+    /// recognized.
+    ///
+    /// The unused blocks after the firing of a VLS-128 dual return
+    /// packet are not checked.
+    pub fn validate(&self) -> Result<(), PacketError> {
+        let num_blocks = match self.try_format() {
+            Some(Format::Dual128) => consts::vls_128::BLOCKS_PER_FIRING * 2,
+            _ => BLOCKS_PER_PACKET,
+        };
+        let blocks = self.blocks;
+        for (index, block) in blocks[..num_blocks].iter().enumerate() {
+            if block.block_identifier().is_none() {
+                return Err(PacketError::InvalidBlockIdentifier {
+                    index,
+                    value: block.block_identifier,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Converts the packet to binary buffer.
+    pub fn to_bytes(&self) -> [u8; mem::size_of::<Self>()] {
+        zerocopy::transmute!(*self)
+    }
+
+    /// Views the packet as a slice of bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        IntoBytes::as_bytes(self)
+    }
+
     pub fn toh(&self) -> Duration {
         Duration::from_micros(self.toh as u64)
     }
 
+    /// Gets the return mode, or `None` if the byte is not recognized.
+    pub fn return_mode(&self) -> Option<ReturnMode> {
+        ReturnMode::from_repr(self.return_mode)
+    }
+
+    /// Gets the sensor model, or `None` if the byte is not recognized.
+    pub fn product_id(&self) -> Option<ProductID> {
+        ProductID::from_repr(self.product_id)
+    }
+
+    /// Interprets the last two bytes as the HDL-64E status.
+    ///
+    /// The result is meaningless for packets from other models.
+    pub fn status(&self) -> Status {
+        Status {
+            status_type: self.return_mode,
+            value: self.product_id,
+        }
+    }
+
+    /// Gets the format from the model and return mode.
+    ///
+    /// HDL-64E packets carry no model, so the format cannot be
+    /// determined from them and must be taken from the [Config](crate::Config).
     pub fn try_format(&self) -> Option<Format> {
-        Format::try_from_model(self.product_id, self.return_mode)
+        Format::try_from_model(self.product_id()?, self.return_mode()?)
     }
 
-    pub fn format(&self) -> Format {
-        Format::from_model(self.product_id, self.return_mode)
+    /// Gets the format from the model and return mode.
+    ///
+    /// It fails if the product ID or the return mode is unknown, or
+    /// the model is not supported.
+    pub fn format(&self) -> Result<Format, Error> {
+        let product_id = self
+            .product_id()
+            .ok_or(Error::UnknownProductId(self.product_id))?;
+        let return_mode = self
+            .return_mode()
+            .ok_or(Error::UnknownReturnMode(self.return_mode))?;
+        Format::from_model(product_id, return_mode)
     }
 
+    /// Iterates over firings in the format of the packet.
+    ///
+    /// It fails if the format cannot be determined, see
+    /// [format()](Self::format), or if the blocks of a VLS-128 packet
+    /// are not in laser bank order.
+    #[allow(clippy::type_complexity)]
     pub fn firing_block_iter(
         &self,
-    ) -> FormatKind<
-        impl Iterator<Item = FiringBlockS16<'_>> + Clone,
-        impl Iterator<Item = FiringBlockS32<'_>> + Clone,
-        impl Iterator<Item = FiringBlockD16<'_>> + Clone,
-        impl Iterator<Item = FiringBlockD32<'_>> + Clone,
+    ) -> Result<
+        FormatKind<
+            impl Iterator<Item = FiringBlockS16<'_>> + Clone,
+            impl Iterator<Item = FiringBlockS32<'_>> + Clone,
+            impl Iterator<Item = FiringBlockS64<'_>> + Clone,
+            impl Iterator<Item = FiringBlockS128<'_>> + Clone,
+            impl Iterator<Item = FiringBlockD16<'_>> + Clone,
+            impl Iterator<Item = FiringBlockD32<'_>> + Clone,
+            impl Iterator<Item = FiringBlockD64<'_>> + Clone,
+            impl Iterator<Item = FiringBlockD128<'_>> + Clone,
+        >,
+        Error,
     > {
         use Format::*;
         use FormatKind as F;
 
-        match self.format() {
+        let format = self.format()?;
+        if matches!(format, Single128 | Dual128) {
+            self.check_bank_order(format.is_dual())?;
+        }
+
+        let iter = match format {
             Single16 => F::from_s16(self.firing_block_iter_s16()),
             Dual16 => F::from_d16(self.firing_block_iter_d16()),
             Single32 => F::from_s32(self.firing_block_iter_s32()),
             Dual32 => F::from_d32(self.firing_block_iter_d32()),
+            Single64 => F::from_s64(self.firing_block_iter_s64()),
+            Dual64 => F::from_d64(self.firing_block_iter_d64()),
+            Single128 => F::from_s128(self.firing_block_iter_s128()),
+            Dual128 => F::from_d128(self.firing_block_iter_d128()),
+        };
+        Ok(iter)
+    }
+
+    /// Checks that the blocks of a VLS-128 packet follow the laser bank
+    /// order.
+    ///
+    /// Single return packets cycle through the 4 banks, while dual
+    /// return packets store each bank twice in the first 8 blocks.
+    fn check_bank_order(&self, dual: bool) -> Result<(), PacketError> {
+        use BlockIdentifier as B;
+
+        let banks = [
+            B::Block0To31,
+            B::Block32To63,
+            B::Block64To95,
+            B::Block96To127,
+        ];
+        let blocks = self.blocks;
+        let blocks = if dual {
+            &blocks[..consts::vls_128::BLOCKS_PER_FIRING * 2]
+        } else {
+            &blocks[..]
+        };
+
+        for (index, block) in blocks.iter().enumerate() {
+            let expect = if dual {
+                banks[index / 2]
+            } else {
+                banks[index % banks.len()]
+            };
+            if block.block_identifier != expect as u16 {
+                return Err(PacketError::UnexpectedBlockIdentifier {
+                    index,
+                    expect,
+                    value: block.block_identifier,
+                });
+            }
         }
+        Ok(())
     }
 
     pub fn firing_block_iter_s16(
@@ -301,7 +542,9 @@ impl DataPacket {
     pub fn firing_block_iter_s32(
         &self,
     ) -> impl Iterator<Item = FiringBlockS32<'_>> + Clone + Sync + Send {
-        let tohs = iter::successors(Some(self.toh()), move |prev| Some(*prev + FIRING_PERIOD));
+        let product_id = self.product_id().unwrap_or(ProductID::VLP32C);
+        let firing_period = product_id.firing_period();
+        let tohs = iter::successors(Some(self.toh()), move |prev| Some(*prev + firing_period));
         let azimuths = {
             let block_azimuths: Vec<_> = self.blocks.iter().map(|block| block.azimuth()).collect();
             let block_azimuth_diffs: Vec<_> = block_azimuths
@@ -322,13 +565,18 @@ impl DataPacket {
             })
         };
 
-        izip!(tohs, azimuths, &self.blocks).map(move |(block_toh, azimuth_range, block)| {
-            let former_toh = block_toh;
-            let latter_toh = former_toh + FIRING_PERIOD;
+        // VLP-32C stamps a single-return firing at the end of its first
+        // firing period, while HDL-32E stamps it at the block start.
+        let toh_offset = match product_id {
+            ProductID::HDL32E => Duration::ZERO,
+            _ => firing_period,
+        };
 
+        izip!(tohs, azimuths, &self.blocks).map(move |(block_toh, azimuth_range, block)| {
             FiringBlockS32 {
-                toh: latter_toh,
+                toh: block_toh + toh_offset,
                 azimuth_range,
+                product_id,
                 block,
                 channels: &block.channels,
             }
@@ -338,7 +586,9 @@ impl DataPacket {
     pub fn firing_block_iter_d32(
         &self,
     ) -> impl Iterator<Item = FiringBlockD32<'_>> + Clone + Sync + Send {
-        let tohs = iter::successors(Some(self.toh()), move |prev| Some(*prev + FIRING_PERIOD));
+        let product_id = self.product_id().unwrap_or(ProductID::VLP32C);
+        let firing_period = product_id.firing_period();
+        let tohs = iter::successors(Some(self.toh()), move |prev| Some(*prev + firing_period));
         let azimuths = {
             let azimuths: Vec<_> = self
                 .blocks
@@ -372,6 +622,7 @@ impl DataPacket {
                 FiringBlockD32 {
                     toh: block_toh,
                     azimuth_range,
+                    product_id,
                     block_strongest,
                     block_last,
                     channels: ChannelArrayDRef {
@@ -383,6 +634,183 @@ impl DataPacket {
         )
     }
 
+    /// Iterates over firings of an HDL-64E packet in single return mode.
+    ///
+    /// Each firing spans an upper block followed by a lower block.
+    pub fn firing_block_iter_s64(
+        &self,
+    ) -> impl Iterator<Item = FiringBlockS64<'_>> + Clone + Sync + Send {
+        use consts::hdl_64e::{BLOCKS_PER_FIRING, FIRING_PERIOD};
+
+        let tohs = iter::successors(Some(self.toh()), move |prev| Some(*prev + FIRING_PERIOD));
+        let azimuths = {
+            let azimuths: Vec<_> = self
+                .blocks
+                .iter()
+                .step_by(BLOCKS_PER_FIRING)
+                .map(|block| block.azimuth())
+                .collect();
+            let azimuth_diffs: Vec<_> = azimuths
+                .iter()
+                .cloned()
+                .tuple_windows()
+                .map(|(curr, next)| (next - curr).wrap_to_2pi())
+                .collect();
+            let last_azimuth_diff = *azimuth_diffs.last().unwrap();
+
+            izip!(azimuths, chain!(azimuth_diffs, [last_azimuth_diff])).map(
+                |(former_azimuth, azimuth_diff)| {
+                    let latter_azimuth = former_azimuth + azimuth_diff;
+                    former_azimuth..latter_azimuth
+                },
+            )
+        };
+
+        izip!(tohs, azimuths, self.blocks.chunks(BLOCKS_PER_FIRING)).map(
+            |(toh, azimuth_range, chunk)| {
+                let blocks = match chunk {
+                    [upper, lower] => [upper, lower],
+                    _ => unreachable!(),
+                };
+
+                FiringBlockS64 {
+                    toh,
+                    azimuth_range,
+                    blocks,
+                }
+            },
+        )
+    }
+
+    /// Iterates over firings of an HDL-64E packet in dual return mode.
+    ///
+    /// Each firing spans 4 blocks, where the strongest returns of the
+    /// upper and lower blocks are followed by the last returns.
+    pub fn firing_block_iter_d64(
+        &self,
+    ) -> impl Iterator<Item = FiringBlockD64<'_>> + Clone + Sync + Send {
+        use consts::hdl_64e::{BLOCKS_PER_FIRING, DUAL_FIRING_PERIOD};
+
+        let tohs = iter::successors(Some(self.toh()), move |prev| {
+            Some(*prev + DUAL_FIRING_PERIOD)
+        });
+        let azimuths = {
+            let azimuths: Vec<_> = self
+                .blocks
+                .iter()
+                .step_by(BLOCKS_PER_FIRING * 2)
+                .map(|block| block.azimuth())
+                .collect();
+            let azimuth_diffs: Vec<_> = azimuths
+                .iter()
+                .cloned()
+                .tuple_windows()
+                .map(|(curr, next)| (next - curr).wrap_to_2pi())
+                .collect();
+            let last_azimuth_diff = *azimuth_diffs.last().unwrap();
+
+            izip!(azimuths, chain!(azimuth_diffs, [last_azimuth_diff])).map(
+                |(former_azimuth, azimuth_diff)| {
+                    let latter_azimuth = former_azimuth + azimuth_diff;
+                    former_azimuth..latter_azimuth
+                },
+            )
+        };
+
+        izip!(tohs, azimuths, self.blocks.chunks(BLOCKS_PER_FIRING * 2)).map(
+            |(toh, azimuth_range, chunk)| {
+                let (blocks_strongest, blocks_last) = match chunk {
+                    [upper_strongest, lower_strongest, upper_last, lower_last] => {
+                        ([upper_strongest, lower_strongest], [upper_last, lower_last])
+                    }
+                    _ => unreachable!(),
+                };
+
+                FiringBlockD64 {
+                    toh,
+                    azimuth_range,
+                    blocks_strongest,
+                    blocks_last,
+                }
+            },
+        )
+    }
+
+    /// Iterates over firings of a VLS-128 packet in single return mode.
+    ///
+    /// Each firing spans 4 blocks, one for each laser bank.
+    pub fn firing_block_iter_s128(
+        &self,
+    ) -> impl Iterator<Item = FiringBlockS128<'_>> + Clone + Sync + Send {
+        use consts::vls_128::{BLOCKS_PER_FIRING, TOH_ADJUSTMENT};
+
+        let firing_period = consts::vls_128::FIRING_PERIOD;
+        let first_toh = self.toh().saturating_sub(TOH_ADJUSTMENT);
+        let tohs = iter::successors(Some(first_toh), move |prev| Some(*prev + firing_period));
+        let azimuths = {
+            let azimuths: Vec<_> = self
+                .blocks
+                .iter()
+                .step_by(BLOCKS_PER_FIRING)
+                .map(|block| block.azimuth())
+                .collect();
+            let azimuth_diffs: Vec<_> = azimuths
+                .iter()
+                .cloned()
+                .tuple_windows()
+                .map(|(curr, next)| (next - curr).wrap_to_2pi())
+                .collect();
+            let last_azimuth_diff = *azimuth_diffs.last().unwrap();
+
+            izip!(azimuths, chain!(azimuth_diffs, [last_azimuth_diff])).map(
+                |(former_azimuth, azimuth_diff)| {
+                    let latter_azimuth = former_azimuth + azimuth_diff;
+                    former_azimuth..latter_azimuth
+                },
+            )
+        };
+
+        izip!(tohs, azimuths, self.blocks.chunks(BLOCKS_PER_FIRING)).map(
+            |(toh, azimuth_range, chunk)| {
+                let blocks = match chunk {
+                    [b0, b1, b2, b3] => [b0, b1, b2, b3],
+                    _ => unreachable!(),
+                };
+
+                FiringBlockS128 {
+                    toh,
+                    azimuth_range,
+                    blocks,
+                }
+            },
+        )
+    }
+
+    /// Iterates over firings of a VLS-128 packet in dual return mode.
+    ///
+    /// The packet holds one firing in the first 8 blocks, where each
+    /// pair of blocks stores the strongest and last returns of a laser
+    /// bank. The remaining blocks are unused. The firing spans no
+    /// azimuth, since the next firing is in the next packet.
+    pub fn firing_block_iter_d128(
+        &self,
+    ) -> impl Iterator<Item = FiringBlockD128<'_>> + Clone + Sync + Send {
+        use consts::vls_128::{BLOCKS_PER_FIRING, TOH_ADJUSTMENT};
+
+        let toh = self.toh().saturating_sub(TOH_ADJUSTMENT);
+        let azimuth = self.blocks[0].azimuth();
+        let blocks = &self.blocks[..BLOCKS_PER_FIRING * 2];
+        let blocks_strongest: [&Block; 4] = array::from_fn(|bank| &blocks[bank * 2]);
+        let blocks_last: [&Block; 4] = array::from_fn(|bank| &blocks[bank * 2 + 1]);
+
+        iter::once(FiringBlockD128 {
+            toh,
+            azimuth_range: azimuth..azimuth,
+            blocks_strongest,
+            blocks_last,
+        })
+    }
+
     pub fn firing_xyz_iter_s16<'a>(
         &'a self,
         beams: &'a Config16,
@@ -414,4 +842,36 @@ impl DataPacket {
         self.firing_block_iter_d32()
             .map(|firing| firing.to_firing_xyz(beams))
     }
+
+    pub fn firing_xyz_iter_s64<'a>(
+        &'a self,
+        beams: &'a Config64,
+    ) -> impl Iterator<Item = FiringXyzS64> + Clone + Sync + Send + 'a {
+        self.firing_block_iter_s64()
+            .map(|firing| firing.to_firing_xyz(beams))
+    }
+
+    pub fn firing_xyz_iter_d64<'a>(
+        &'a self,
+        beams: &'a Config64,
+    ) -> impl Iterator<Item = FiringXyzD64> + Clone + Sync + Send + 'a {
+        self.firing_block_iter_d64()
+            .map(|firing| firing.to_firing_xyz(beams))
+    }
+
+    pub fn firing_xyz_iter_s128<'a>(
+        &'a self,
+        beams: &'a Config128,
+    ) -> impl Iterator<Item = FiringXyzS128> + Clone + Sync + Send + 'a {
+        self.firing_block_iter_s128()
+            .map(|firing| firing.to_firing_xyz(beams))
+    }
+
+    pub fn firing_xyz_iter_d128<'a>(
+        &'a self,
+        beams: &'a Config128,
+    ) -> impl Iterator<Item = FiringXyzD128> + Clone + Sync + Send + 'a {
+        self.firing_block_iter_d128()
+            .map(|firing| firing.to_firing_xyz(beams))
+    }
 }
@@ -0,0 +1,38 @@
+use super::BlockIdentifier;
+
+/// The error of decoding packets from bytes.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PacketError {
+    #[error("expect {expect} bytes for a {kind} packet, but get {actual}")]
+    SizeMismatch {
+        kind: PacketKind,
+        expect: usize,
+        actual: usize,
+    },
+    #[error("unable to parse {0} bytes into a data or a position packet")]
+    UnknownSize(usize),
+    #[error("invalid block identifier 0x{value:04x} in block {index}")]
+    InvalidBlockIdentifier { index: usize, value: u16 },
+    #[error("expect block identifier {expect:?} in block {index}, but get 0x{value:04x}")]
+    UnexpectedBlockIdentifier {
+        index: usize,
+        expect: BlockIdentifier,
+        value: u16,
+    },
+    #[error("invalid {field} value 0x{value:02x}")]
+    InvalidField { field: &'static str, value: u8 },
+}
+
+impl From<PacketError> for std::io::Error {
+    fn from(error: PacketError) -> Self {
+        Self::new(std::io::ErrorKind::InvalidData, error)
+    }
+}
+
+/// Kinds of Velodyne packets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum PacketKind {
+    Data,
+    Position,
+}
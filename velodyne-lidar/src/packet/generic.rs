@@ -1,5 +1,5 @@
-use super::{DataPacket, PositionPacket};
-use anyhow::{bail, Result};
+use super::{DataPacket, PacketError, PositionPacket};
+use std::mem;
 
 /// An enumeration of data packet and position packet.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -9,16 +9,34 @@ pub enum Packet {
 }
 
 impl Packet {
-    pub fn from_slice(buffer: &[u8]) -> Result<Self> {
-        Ok(if let Ok(packet) = DataPacket::from_slice(buffer) {
-            (*packet).into()
-        } else if let Ok(packet) = PositionPacket::from_slice(buffer) {
-            (*packet).into()
-        } else {
-            bail!("unable to parse bytes into a data or a positoin packet");
+    /// Parses a data or a position packet by the size of the slice.
+    ///
+    /// It fails if the size matches neither packet, or the packet has
+    /// unrecognized enumerated fields.
+    pub fn from_slice(buffer: &[u8]) -> Result<Self, PacketError> {
+        const DATA_SIZE: usize = mem::size_of::<DataPacket>();
+        const POSITION_SIZE: usize = mem::size_of::<PositionPacket>();
+
+        Ok(match buffer.len() {
+            DATA_SIZE => (*DataPacket::from_slice(buffer)?).into(),
+            POSITION_SIZE => (*PositionPacket::from_slice(buffer)?).into(),
+            len => return Err(PacketError::UnknownSize(len)),
         })
     }
 
+    /// Converts the packet to bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    /// Views the packet as a slice of bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Self::Data(packet) => packet.as_bytes(),
+            Self::Position(packet) => packet.as_bytes(),
+        }
+    }
+
     pub fn try_into_data(self) -> Result<DataPacket, Self> {
         if let Self::Data(v) = self {
             Ok(*v)
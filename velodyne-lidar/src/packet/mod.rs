@@ -9,14 +9,727 @@ pub use position::*;
 mod generic;
 pub use generic::*;
 
+mod builder;
+pub use builder::*;
+
+mod error;
+pub use error::*;
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::mem;
+    #[cfg(feature = "velodyne-params")]
+    use crate::{consts::hdl_64e, Config64};
+    use crate::{
+        consts::{hdl_32e, vls_128, BLOCKS_PER_PACKET, FIRING_PERIOD},
+        iter::{data_packet_to_frame_xyz, data_packet_to_frame_xyz_auto, FormatChangePolicy},
+        traits::FiringLike as _,
+        types::format::{Format, FormatKind},
+        Config, Config128, Config32, Error,
+    };
+    use itertools::Itertools as _;
+    use std::{array, mem, time::Duration};
+
+    fn hdl_32e_packet(toh: u32, start_azimuth: u16, return_mode: ReturnMode) -> DataPacket {
+        let channel = Channel {
+            distance: 500,
+            intensity: 10,
+        };
+        let blocks = array::from_fn(|index| Block {
+            block_identifier: BlockIdentifier::Block0To31 as u16,
+            azimuth_count: start_azimuth + index as u16 * 16,
+            channels: [channel; 32],
+        });
+
+        DataPacket {
+            blocks,
+            toh,
+            return_mode: return_mode as u8,
+            product_id: ProductID::HDL32E as u8,
+        }
+    }
+
+    fn vls_128_packet(toh: u32, start_azimuth: u16, return_mode: ReturnMode) -> DataPacket {
+        use BlockIdentifier as B;
+
+        let banks = [
+            B::Block0To31,
+            B::Block32To63,
+            B::Block64To95,
+            B::Block96To127,
+        ];
+        let blocks = array::from_fn(|index| {
+            // dual return packets store strongest and last blocks in
+            // pairs for each bank
+            let (bank, firing) = match return_mode {
+                ReturnMode::Dual => (index / 2 % 4, 0),
+                _ => (index % 4, index / 4),
+            };
+            let channels = array::from_fn(|channel| Channel {
+                distance: (bank * 32 + channel) as u16 + 1,
+                intensity: index as u8,
+            });
+
+            Block {
+                block_identifier: banks[bank] as u16,
+                azimuth_count: start_azimuth + firing as u16 * 20,
+                channels,
+            }
+        });
+
+        DataPacket {
+            blocks,
+            toh,
+            return_mode: return_mode as u8,
+            product_id: ProductID::VLS128 as u8,
+        }
+    }
+
+    fn hdl_64e_packet(toh: u32, start_azimuth: u16, dual: bool) -> DataPacket {
+        let blocks = array::from_fn(|index| {
+            // upper and lower blocks alternate, and dual return packets
+            // store the strongest pair before the last pair
+            let (bank, firing) = match dual {
+                true => (index % 2, index / 4),
+                false => (index % 2, index / 2),
+            };
+            let block_identifier = match bank {
+                0 => BlockIdentifier::Block0To31,
+                _ => BlockIdentifier::Block32To63,
+            };
+            let channels = array::from_fn(|channel| Channel {
+                distance: (bank * 32 + channel) as u16 + 1,
+                intensity: index as u8,
+            });
+
+            Block {
+                block_identifier: block_identifier as u16,
+                azimuth_count: start_azimuth + firing as u16 * 20,
+                channels,
+            }
+        });
+
+        DataPacket {
+            blocks,
+            toh,
+            return_mode: StatusType::Temperature as u8,
+            product_id: 42,
+        }
+    }
 
     #[test]
     fn velodyne_packet_size_test() {
         assert_eq!(mem::size_of::<DataPacket>(), 1206);
         assert_eq!(mem::size_of::<PositionPacket>(), 512);
     }
+
+    #[test]
+    fn unsupported_product_id() {
+        let mut packet = hdl_32e_packet(1000, 0, ReturnMode::Strongest);
+
+        packet.product_id = ProductID::Velarray as u8;
+        assert_eq!(packet.try_format(), None);
+        assert!(packet.format().is_err());
+        assert!(packet.firing_block_iter().is_err());
+        assert!(matches!(
+            Format::from_model(ProductID::Velarray, ReturnMode::Strongest),
+            Err(Error::UnsupportedProduct(ProductID::Velarray))
+        ));
+
+        packet.product_id = 0xff;
+        assert_eq!(packet.product_id(), None);
+        assert!(packet.format().is_err());
+    }
+
+    #[test]
+    fn malformed_packets() {
+        use crate::telemetry::Telemetry;
+
+        let packet = hdl_32e_packet(1000, 0, ReturnMode::Strongest);
+        let mut bytes = packet.to_bytes();
+        assert_eq!(DataPacket::from_slice(&bytes), Ok(&packet));
+        assert_eq!(
+            Packet::from_slice(&bytes[..100]),
+            Err(PacketError::UnknownSize(100))
+        );
+        assert_eq!(
+            DataPacket::from_slice(&bytes[..512]),
+            Err(PacketError::SizeMismatch {
+                kind: PacketKind::Data,
+                expect: 1206,
+                actual: 512
+            })
+        );
+
+        // corrupt the identifier of the third block
+        let offset = mem::size_of::<Block>() * 2;
+        bytes[offset..offset + 2].copy_from_slice(&0x1234u16.to_ne_bytes());
+        assert_eq!(
+            Packet::from_slice(&bytes),
+            Err(PacketError::InvalidBlockIdentifier {
+                index: 2,
+                value: 0x1234
+            })
+        );
+        let blocks = DataPacket::from_bytes(bytes).blocks;
+        assert_eq!(blocks[2].block_identifier(), None);
+        assert_eq!(
+            blocks[3].block_identifier(),
+            Some(BlockIdentifier::Block0To31)
+        );
+
+        let mut bytes = PositionPacket::default().to_bytes();
+        bytes[mem::offset_of!(PositionPacket, pps_status)] = 7;
+        assert_eq!(
+            Packet::from_slice(&bytes),
+            Err(PacketError::InvalidField {
+                field: "PPS status",
+                value: 7
+            })
+        );
+        let packet = PositionPacket::from_bytes(bytes);
+        assert_eq!(packet.pps_status(), None);
+        assert!(Telemetry::from_position_packet(&packet).is_err());
+    }
+
+    #[test]
+    fn hdl_32e_firing_timing() {
+        let packet = hdl_32e_packet(1000, 0, ReturnMode::Strongest);
+        assert_eq!(packet.format().unwrap(), Format::Single32);
+
+        let config = Config32::new_hdl_32e_strongest();
+        let firings: Vec<_> = packet.firing_xyz_iter_s32(&config).collect();
+        assert_eq!(firings.len(), BLOCKS_PER_PACKET);
+
+        for (index, firing) in firings.iter().enumerate() {
+            let firing_toh = packet.toh() + hdl_32e::FIRING_PERIOD * index as u32;
+            assert_eq!(firing.toh, firing_toh);
+
+            for (laser_id, point) in firing.points.iter().enumerate() {
+                assert_eq!(point.laser_id, laser_id);
+                assert_eq!(
+                    point.toh,
+                    firing_toh + hdl_32e::CHANNEL_PERIOD * laser_id as u32
+                );
+            }
+        }
+
+        let packet = hdl_32e_packet(1000, 0, ReturnMode::Dual);
+        assert_eq!(packet.format().unwrap(), Format::Dual32);
+        let tohs: Vec<_> = packet
+            .firing_block_iter_d32()
+            .map(|firing| firing.toh)
+            .collect();
+        assert_eq!(tohs.len(), BLOCKS_PER_PACKET / 2);
+        assert_eq!(tohs[1] - tohs[0], hdl_32e::FIRING_PERIOD);
+    }
+
+    #[test]
+    fn vlp_32c_firing_timing() {
+        let mut packet = hdl_32e_packet(1000, 0, ReturnMode::Strongest);
+        packet.product_id = ProductID::VLP32C as u8;
+
+        let tohs: Vec<_> = packet
+            .firing_block_iter_s32()
+            .map(|firing| firing.toh)
+            .collect();
+        assert_eq!(tohs.len(), BLOCKS_PER_PACKET);
+        for (index, toh) in tohs.into_iter().enumerate() {
+            assert_eq!(toh, packet.toh() + FIRING_PERIOD * (index as u32 + 1));
+        }
+
+        packet.return_mode = ReturnMode::Dual as u8;
+        let tohs: Vec<_> = packet
+            .firing_block_iter_d32()
+            .map(|firing| firing.toh)
+            .collect();
+        assert_eq!(tohs[0], packet.toh());
+        assert_eq!(tohs[1] - tohs[0], FIRING_PERIOD);
+    }
+
+    #[test]
+    fn hdl_32e_packets_to_frame() {
+        // two packets cover azimuths from 0 to 3.83 degrees, then the
+        // third one wraps back around
+        let packets = [
+            hdl_32e_packet(1000, 0, ReturnMode::Last),
+            hdl_32e_packet(1553, 192, ReturnMode::Last),
+            hdl_32e_packet(2106, 0, ReturnMode::Last),
+        ];
+
+        let frames: Vec<_> = data_packet_to_frame_xyz(Config::new_hdl_32e_last(), packets)
+            .unwrap()
+            .collect();
+        assert_eq!(frames.len(), 1);
+
+        let FormatKind::Single32(frame) = &frames[0] else {
+            panic!("expect a single-return 32-line frame");
+        };
+        assert_eq!(frame.firings.len(), BLOCKS_PER_PACKET * 2);
+        assert_eq!(frame.firings[0].toh, Duration::from_micros(1000));
+        assert_eq!(frame.firings[12].toh, Duration::from_micros(1553));
+    }
+
+    #[test]
+    fn vls_128_single_return_firings() {
+        let packet = vls_128_packet(1000, 0, ReturnMode::Strongest);
+        assert_eq!(packet.format().unwrap(), Format::Single128);
+
+        let config = Config128::new_vls_128_strongest();
+        let firings: Vec<_> = packet.firing_xyz_iter_s128(&config).collect();
+        assert_eq!(firings.len(), 3);
+
+        for (index, firing) in firings.iter().enumerate() {
+            let firing_toh =
+                packet.toh() - vls_128::TOH_ADJUSTMENT + vls_128::FIRING_PERIOD * index as u32;
+            assert_eq!(firing.toh, firing_toh);
+
+            for (laser_id, point) in firing.points.iter().enumerate() {
+                assert_eq!(point.laser_id, laser_id);
+                assert_eq!(
+                    point.measurement.distance,
+                    config.distance_resolution * (laser_id + 1) as f64
+                );
+                assert_eq!(
+                    point.measurement.intensity as usize,
+                    index * 4 + laser_id / 32
+                );
+            }
+
+            // 8 lasers fire at once, with an idle slot after the first 64 lasers
+            let points = &firing.points;
+            assert_eq!(points[7].toh, firing_toh);
+            assert_eq!(points[8].toh, firing_toh + vls_128::CHANNEL_PERIOD);
+            assert_eq!(points[64].toh, firing_toh + vls_128::CHANNEL_PERIOD * 9);
+            assert_eq!(points[127].toh, firing_toh + vls_128::CHANNEL_PERIOD * 16);
+        }
+    }
+
+    #[test]
+    fn vls_128_dual_return_firing() {
+        let packet = vls_128_packet(1000, 0, ReturnMode::Dual);
+        assert_eq!(packet.format().unwrap(), Format::Dual128);
+
+        let config = Config128::new_vls_128_dual();
+        let firings: Vec<_> = packet.firing_xyz_iter_d128(&config).collect();
+        assert_eq!(firings.len(), 1);
+
+        for (laser_id, point) in firings[0].points.iter().enumerate() {
+            let bank = laser_id / 32;
+            let measurements = &point.measurements;
+            assert_eq!(measurements.strongest.intensity as usize, bank * 2);
+            assert_eq!(measurements.last.intensity as usize, bank * 2 + 1);
+            assert_eq!(
+                measurements.last.distance,
+                config.distance_resolution * (laser_id + 1) as f64
+            );
+        }
+    }
+
+    #[test]
+    fn vls_128_time_iter() {
+        let packet = vls_128_packet(1000, 0, ReturnMode::Strongest);
+        let config = Config128::new_vls_128_strongest();
+        for block in packet.firing_block_iter_s128() {
+            let tohs: Vec<_> = block.time_iter().collect();
+            let firing = block.to_firing_xyz(&config);
+            let expect: Vec<_> = firing.points.iter().map(|point| point.toh).collect();
+            assert_eq!(tohs, expect);
+        }
+
+        let packet = vls_128_packet(1000, 0, ReturnMode::Dual);
+        let config = Config128::new_vls_128_dual();
+        for block in packet.firing_block_iter_d128() {
+            let tohs: Vec<_> = block.time_iter().collect();
+            let firing = block.to_firing_xyz(&config);
+            let expect: Vec<_> = firing.points.iter().map(|point| point.toh).collect();
+            assert_eq!(tohs, expect);
+        }
+    }
+
+    #[test]
+    fn vls_128_bank_order() {
+        let mut packet = vls_128_packet(1000, 0, ReturnMode::Strongest);
+        assert!(packet.firing_block_iter().is_ok());
+        packet.blocks[5].block_identifier = BlockIdentifier::Block0To31 as u16;
+        assert!(matches!(
+            packet.firing_block_iter(),
+            Err(Error::Packet(PacketError::UnexpectedBlockIdentifier {
+                index: 5,
+                expect: BlockIdentifier::Block32To63,
+                ..
+            }))
+        ));
+
+        // the blocks after the firing are unused in dual return mode
+        let mut packet = vls_128_packet(1000, 0, ReturnMode::Dual);
+        packet.blocks[9].block_identifier = BlockIdentifier::Block0To31 as u16;
+        assert!(packet.firing_block_iter().is_ok());
+        packet.blocks[3].block_identifier = BlockIdentifier::Block64To95 as u16;
+        assert!(matches!(
+            packet.firing_block_iter(),
+            Err(Error::Packet(PacketError::UnexpectedBlockIdentifier {
+                index: 3,
+                expect: BlockIdentifier::Block32To63,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn vls_128_dual_padding() {
+        let block_size = mem::size_of::<Block>();
+        let mut bytes = vls_128_packet(1000, 0, ReturnMode::Dual).to_bytes();
+        for (index, byte) in bytes[block_size * 8..block_size * 12]
+            .iter_mut()
+            .enumerate()
+        {
+            *byte = (index * 37 + 11) as u8;
+        }
+        assert_eq!(
+            DataPacket::from_bytes(bytes).blocks[8].block_identifier(),
+            None
+        );
+
+        let packet = DataPacket::from_slice(&bytes).unwrap();
+        let firings: Vec<_> = packet.firing_block_iter_d128().collect();
+        assert_eq!(firings.len(), 1);
+        assert!(packet.firing_block_iter().is_ok());
+        let mut builder = crate::FrameBuilder::new(Config::new_vls_128_dual()).unwrap();
+        assert!(builder.push_packet(packet).is_empty());
+        assert_eq!(builder.finish().unwrap().firing_iter().count(), 1);
+
+        // the same blocks are decoded in single return mode
+        bytes[1204] = ReturnMode::Strongest as u8;
+        assert!(matches!(
+            DataPacket::from_slice(&bytes),
+            Err(PacketError::InvalidBlockIdentifier { index: 8, .. })
+        ));
+    }
+
+    #[test]
+    fn vls_128_packets_to_frame() {
+        let packets = [
+            vls_128_packet(1000, 0, ReturnMode::Last),
+            vls_128_packet(1160, 60, ReturnMode::Last),
+            vls_128_packet(1320, 0, ReturnMode::Last),
+        ];
+
+        let frames: Vec<_> = data_packet_to_frame_xyz(Config::new_vls_128_last(), packets)
+            .unwrap()
+            .collect();
+        assert_eq!(frames.len(), 1);
+
+        let FormatKind::Single128(frame) = &frames[0] else {
+            panic!("expect a single-return 128-line frame");
+        };
+        assert_eq!(frame.firings.len(), 6);
+    }
+
+    fn mixed_packets() -> Vec<DataPacket> {
+        vec![
+            hdl_32e_packet(1000, 0, ReturnMode::Last),
+            hdl_32e_packet(1553, 192, ReturnMode::Last),
+            hdl_32e_packet(2106, 0, ReturnMode::Last),
+            vls_128_packet(3000, 0, ReturnMode::Last),
+            vls_128_packet(3160, 60, ReturnMode::Last),
+            vls_128_packet(3320, 0, ReturnMode::Last),
+        ]
+    }
+
+    #[test]
+    fn auto_config_reconfigure() {
+        let frames: Vec<_> =
+            data_packet_to_frame_xyz_auto(mixed_packets(), FormatChangePolicy::Reconfigure, None)
+                .try_collect()
+                .unwrap();
+        let formats: Vec<_> = frames.iter().map(|frame| frame.format()).collect();
+        assert_eq!(
+            formats,
+            [Format::Single32, Format::Single32, Format::Single128]
+        );
+
+        // the incomplete HDL-32E frame is emitted on reconfiguration
+        let FormatKind::Single32(frame) = &frames[1] else {
+            unreachable!();
+        };
+        assert_eq!(frame.firings.len(), BLOCKS_PER_PACKET);
+    }
+
+    #[test]
+    fn auto_config_error_on_change() {
+        let results: Vec<_> =
+            data_packet_to_frame_xyz_auto(mixed_packets(), FormatChangePolicy::Error, None)
+                .collect();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_ok());
+        assert!(results[1].is_err());
+    }
+
+    #[test]
+    fn auto_config_unknown_product() {
+        let mut packet = hdl_32e_packet(1000, 0, ReturnMode::Last);
+        packet.product_id = ProductID::Velarray as u8;
+
+        let results: Vec<_> =
+            data_packet_to_frame_xyz_auto([packet, packet], FormatChangePolicy::Reconfigure, None)
+                .collect();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].is_err());
+    }
+
+    #[test]
+    fn hdl_64e_status() {
+        let packet = hdl_64e_packet(1000, 0, false);
+        assert_eq!(packet.try_format(), None);
+
+        let status = packet.status();
+        assert_eq!(status.kind(), Some(StatusType::Temperature));
+        assert_eq!(status.value, 42);
+    }
+
+    #[cfg(feature = "velodyne-params")]
+    #[test]
+    fn hdl_64e_single_return_firings() {
+        let config: Config64 = hdl_64e_config(ReturnMode::Strongest).try_into().unwrap();
+        let packet = hdl_64e_packet(1000, 0, false);
+        let firings: Vec<_> = packet.firing_xyz_iter_s64(&config).collect();
+        assert_eq!(firings.len(), BLOCKS_PER_PACKET / 2);
+
+        for (index, firing) in firings.iter().enumerate() {
+            let firing_toh = packet.toh() + hdl_64e::FIRING_PERIOD * index as u32;
+            assert_eq!(firing.toh, firing_toh);
+
+            for (laser_id, point) in firing.points.iter().enumerate() {
+                let correction = config.lasers[laser_id].correction.as_ref().unwrap();
+                assert_eq!(point.laser_id, laser_id);
+                assert_eq!(
+                    point.measurement.distance,
+                    config.distance_resolution * (laser_id + 1) as f64 + correction.distance
+                );
+                assert_eq!(
+                    point.measurement.intensity as usize,
+                    index * 2 + laser_id / 32
+                );
+            }
+
+            // upper and lower banks fire in parallel
+            let points = &firing.points;
+            assert_eq!(points[1].toh, firing_toh + hdl_64e::CHANNEL_PERIOD);
+            assert_eq!(points[32].toh, firing_toh);
+            assert_eq!(points[63].toh, firing_toh + hdl_64e::CHANNEL_PERIOD * 31);
+        }
+    }
+
+    #[cfg(feature = "velodyne-params")]
+    #[test]
+    fn hdl_64e_dual_return_firings() {
+        let config: Config64 = hdl_64e_config(ReturnMode::Dual).try_into().unwrap();
+        let packet = hdl_64e_packet(1000, 0, true);
+        let firings: Vec<_> = packet.firing_xyz_iter_d64(&config).collect();
+        assert_eq!(firings.len(), BLOCKS_PER_PACKET / 4);
+
+        for (index, firing) in firings.iter().enumerate() {
+            assert_eq!(
+                firing.toh,
+                packet.toh() + hdl_64e::DUAL_FIRING_PERIOD * index as u32
+            );
+
+            for (laser_id, point) in firing.points.iter().enumerate() {
+                let bank = laser_id / 32;
+                let measurements = &point.measurements;
+                assert_eq!(measurements.strongest.intensity as usize, index * 4 + bank);
+                assert_eq!(measurements.last.intensity as usize, index * 4 + bank + 2);
+            }
+        }
+    }
+
+    #[cfg(feature = "velodyne-params")]
+    #[test]
+    fn hdl_64e_packets_to_frame() {
+        let packets = [
+            hdl_64e_packet(1000, 0, false),
+            hdl_64e_packet(1288, 120, false),
+            hdl_64e_packet(1576, 0, false),
+        ];
+
+        let frames: Vec<_> = data_packet_to_frame_xyz(hdl_64e_config(ReturnMode::Last), packets)
+            .unwrap()
+            .collect();
+        assert_eq!(frames.len(), 1);
+
+        let FormatKind::Single64(frame) = &frames[0] else {
+            panic!("expect a single-return 64-line frame");
+        };
+        assert_eq!(frame.firings.len(), BLOCKS_PER_PACKET);
+    }
+
+    #[cfg(feature = "velodyne-params")]
+    #[test]
+    fn auto_config_hdl_64e_fallback() {
+        let packets = [
+            hdl_64e_packet(1000, 0, false),
+            hdl_64e_packet(1288, 120, false),
+            hdl_64e_packet(1576, 0, false),
+        ];
+        let fallback = hdl_64e_config(ReturnMode::Last);
+
+        // HDL-64E packets carry no product ID
+        let results: Vec<_> =
+            data_packet_to_frame_xyz_auto(packets, FormatChangePolicy::Reconfigure, None).collect();
+        assert!(matches!(results[..], [Err(Error::UnknownProductId(42))]));
+
+        let frames: Vec<_> = data_packet_to_frame_xyz_auto(
+            packets,
+            FormatChangePolicy::Reconfigure,
+            Some(fallback.clone()),
+        )
+        .try_collect()
+        .unwrap();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].format(), Format::Single64);
+        assert_eq!(frames[0].firing_iter().count(), BLOCKS_PER_PACKET);
+
+        // a change to the fallback is a change of models
+        let mixed = [hdl_32e_packet(1000, 0, ReturnMode::Last), packets[0]];
+        let results: Vec<_> =
+            data_packet_to_frame_xyz_auto(mixed, FormatChangePolicy::Error, Some(fallback))
+                .collect();
+        assert!(matches!(
+            results[..],
+            [Err(Error::FormatMismatch {
+                from: Some((ProductID::HDL32E, ReturnMode::Last)),
+                to: None,
+            })]
+        ));
+    }
+
+    #[cfg(feature = "velodyne-params")]
+    fn hdl_64e_config(return_mode: ReturnMode) -> Config {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../velodyne-params/params/64e_utexas.yaml"
+        );
+        Config::from_params_path(path, return_mode).unwrap()
+    }
+
+    mod proptests {
+        use super::*;
+        use proptest::prelude::*;
+
+        const BLOCK_SIZE: usize = mem::size_of::<Block>();
+
+        /// Random data packet bytes with valid block identifiers and
+        /// known product IDs and return modes.
+        fn data_packet_bytes() -> impl Strategy<Value = Vec<u8>> {
+            let identifiers = prop::sample::select(vec![0xeeffu16, 0xddff, 0xccff, 0xbbff]);
+            let product_ids = prop::sample::select(vec![0x21u8, 0x22, 0x23, 0x24, 0x28, 0xa1]);
+            let return_modes = prop::sample::select(vec![0x37u8, 0x38, 0x39]);
+
+            (
+                prop::collection::vec(any::<u8>(), 1206),
+                prop::array::uniform12(identifiers),
+                product_ids,
+                return_modes,
+            )
+                .prop_map(|(mut bytes, identifiers, product_id, return_mode)| {
+                    for (index, identifier) in identifiers.into_iter().enumerate() {
+                        let offset = index * BLOCK_SIZE;
+                        bytes[offset..offset + 2].copy_from_slice(&identifier.to_ne_bytes());
+                    }
+                    bytes[1204] = return_mode;
+                    bytes[1205] = product_id;
+                    bytes
+                })
+        }
+
+        /// Random bytes, of which two thirds have the size of a data
+        /// or a position packet.
+        fn packet_sized_bytes() -> impl Strategy<Value = Vec<u8>> {
+            prop_oneof![
+                Just(mem::size_of::<DataPacket>()),
+                Just(mem::size_of::<PositionPacket>()),
+                0..1300usize,
+            ]
+            .prop_flat_map(|len| prop::collection::vec(any::<u8>(), len))
+        }
+
+        proptest! {
+            #[test]
+            fn arbitrary_bytes_never_panic(bytes in packet_sized_bytes()) {
+                if let Ok(packet) = Packet::from_slice(&bytes) {
+                    prop_assert_eq!(packet.as_bytes(), &bytes[..]);
+                }
+            }
+
+            #[test]
+            fn data_packet_validation(bytes in prop::collection::vec(any::<u8>(), 1206)) {
+                let identifiers: Vec<u16> = (0..12)
+                    .map(|index| {
+                        let offset = index * BLOCK_SIZE;
+                        u16::from_ne_bytes([bytes[offset], bytes[offset + 1]])
+                    })
+                    .collect();
+                // the unused blocks of VLS-128 dual return packets are
+                // not checked
+                let num_blocks = match (bytes[1204], bytes[1205]) {
+                    (0x39, 0xa1) => 8,
+                    _ => 12,
+                };
+                let invalid = identifiers[..num_blocks]
+                    .iter()
+                    .position(|&value| BlockIdentifier::from_repr(value).is_none());
+
+                match (DataPacket::from_slice(&bytes), invalid) {
+                    (Ok(packet), None) => prop_assert_eq!(packet.as_bytes(), &bytes[..]),
+                    (Err(err), Some(index)) => prop_assert_eq!(
+                        err,
+                        PacketError::InvalidBlockIdentifier {
+                            index,
+                            value: identifiers[index]
+                        }
+                    ),
+                    (result, _) => prop_assert!(false, "unexpected result {:?}", result),
+                }
+            }
+
+            #[test]
+            fn decode_valid_data_packets(bytes in data_packet_bytes()) {
+                let packet = Packet::from_slice(&bytes).unwrap().try_into_data().unwrap();
+                prop_assert_eq!(&packet.to_bytes()[..], &bytes[..]);
+
+                let format = packet.format().unwrap();
+                let config =
+                    Config::try_from_model(packet.product_id().unwrap(), packet.return_mode().unwrap())
+                        .unwrap();
+                let mut builder = crate::FrameBuilder::new(config).unwrap();
+                let mut frames = builder.push_packet(&packet);
+                frames.extend(builder.finish());
+                let num_firings: usize = frames
+                    .iter()
+                    .map(|frame| frame.firing_iter().count())
+                    .sum();
+                prop_assert_eq!(num_firings, format.firings_per_packet());
+            }
+
+            #[test]
+            fn position_packet_validation(
+                bytes in prop::collection::vec(any::<u8>(), 512),
+            ) {
+                let packet = PositionPacket::from_bytes(bytes.clone().try_into().unwrap());
+                let valid = packet.last_adc_calibration_reason().is_some()
+                    && packet.pps_status().is_some()
+                    && packet.thermal_status().is_some();
+
+                match Packet::from_slice(&bytes) {
+                    Ok(decoded) => {
+                        prop_assert!(valid);
+                        prop_assert_eq!(decoded.as_position(), Some(&packet));
+                    }
+                    Err(PacketError::InvalidField { .. }) => prop_assert!(!valid),
+                    Err(err) => prop_assert!(false, "unexpected error {}", err),
+                }
+            }
+        }
+    }
 }
@@ -1,9 +1,16 @@
-use anyhow::{ensure, Result};
-use std::mem;
+use super::{PacketError, PacketKind};
+use crate::error::{ensure_argument, Error};
+use std::{mem, time::Duration};
+use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned};
 
 /// Represents a position packet from Velodyne sensor.
+///
+/// Enumerated fields are stored as raw bytes and checked by the
+/// accessors, so that any byte sequence of the right size is a packet.
 #[repr(C, packed)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, FromBytes, IntoBytes, KnownLayout, Immutable, Unaligned,
+)]
 pub struct PositionPacket {
     pub reserved_head: [u8; 187],
     pub top_board_temperature: u8,
@@ -11,11 +18,14 @@ pub struct PositionPacket {
     pub last_adc_calibration_temperature: u8,
     pub last_adc_calibration_temperature_change: u16,
     pub seconds_since_last_adc_calibration: u32,
-    pub last_adc_calibration_reason: LastAdcCalibrationReason,
+    /// The raw [LastAdcCalibrationReason].
+    pub last_adc_calibration_reason: u8,
     pub adc_calibration_bitmask: u8,
     pub toh: u32,
-    pub pps_status: PpsStatus,
-    pub thermal_status: ThermalStatus,
+    /// The raw [PpsStatus].
+    pub pps_status: u8,
+    /// The raw [ThermalStatus].
+    pub thermal_status: u8,
     pub last_shutdown_temperature: u8,
     pub temperature_of_unit_at_power_up: u8,
     pub nmea: [u8; 128],
@@ -24,25 +34,108 @@ pub struct PositionPacket {
 
 impl PositionPacket {
     /// Construct packet from binary buffer.
+    ///
+    /// Enumerated fields are not checked. Use
+    /// [validate()](Self::validate) to check them.
     pub fn from_bytes(buffer: [u8; mem::size_of::<Self>()]) -> Self {
-        unsafe { mem::transmute::<_, Self>(buffer) }
+        zerocopy::transmute!(buffer)
     }
 
-    /// Construct packet from slice of bytes. Fail if the slice size is not correct.
-    pub fn from_slice(buffer: &[u8]) -> Result<&Self> {
-        ensure!(
-            buffer.len() == mem::size_of::<Self>(),
-            "Requre the slice length to be {}, but get {}",
-            mem::size_of::<Self>(),
-            buffer.len(),
-        );
-        let packet = unsafe { &*(buffer.as_ptr() as *const Self) };
+    /// Construct packet from slice of bytes.
+    ///
+    /// It fails if the slice size is not correct or any enumerated
+    /// field is not recognized.
+    pub fn from_slice(buffer: &[u8]) -> Result<&Self, PacketError> {
+        let packet = Self::ref_from_bytes(buffer).map_err(|_| PacketError::SizeMismatch {
+            kind: PacketKind::Position,
+            expect: mem::size_of::<Self>(),
+            actual: buffer.len(),
+        })?;
+        packet.validate()?;
         Ok(packet)
     }
 
+    /// Checks that all enumerated fields are recognized.
+    pub fn validate(&self) -> Result<(), PacketError> {
+        let invalid = |field, value| Err(PacketError::InvalidField { field, value });
+
+        if self.last_adc_calibration_reason().is_none() {
+            return invalid(
+                "last ADC calibration reason",
+                self.last_adc_calibration_reason,
+            );
+        }
+        if self.pps_status().is_none() {
+            return invalid("PPS status", self.pps_status);
+        }
+        if self.thermal_status().is_none() {
+            return invalid("thermal status", self.thermal_status);
+        }
+        Ok(())
+    }
+
+    /// Converts the packet to binary buffer.
+    pub fn to_bytes(&self) -> [u8; mem::size_of::<Self>()] {
+        zerocopy::transmute!(*self)
+    }
+
+    /// Views the packet as a slice of bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        IntoBytes::as_bytes(self)
+    }
+
+    pub fn toh(&self) -> Duration {
+        Duration::from_micros(self.toh as u64)
+    }
+
+    /// Gets the NMEA sentence without the trailing padding.
+    pub fn nmea_sentence(&self) -> &[u8] {
+        let len = self
+            .nmea
+            .iter()
+            .position(|&byte| byte == 0)
+            .unwrap_or(self.nmea.len());
+        &self.nmea[..len]
+    }
+
+    /// Stores an NMEA sentence, padding the rest with zeros.
+    ///
+    /// It fails if the sentence is longer than 128 bytes.
+    pub fn set_nmea(&mut self, sentence: &str) -> Result<(), Error> {
+        let bytes = sentence.as_bytes();
+        ensure_argument!(
+            bytes.len() <= self.nmea.len(),
+            "the NMEA sentence must be at most {} bytes, but get {}",
+            self.nmea.len(),
+            bytes.len()
+        );
+
+        let mut nmea = [0; 128];
+        nmea[..bytes.len()].copy_from_slice(bytes);
+        self.nmea = nmea;
+        Ok(())
+    }
+
     #[cfg(feature = "nmea")]
     pub fn parse_nmea(&self) -> Result<nmea::ParseResult, nmea::Error<'_>> {
-        nmea::parse_bytes(&self.nmea)
+        nmea::parse_bytes(self.nmea_sentence())
+    }
+
+    /// Gets the reason of the last ADC calibration, or `None` if the
+    /// byte is not recognized.
+    pub fn last_adc_calibration_reason(&self) -> Option<LastAdcCalibrationReason> {
+        LastAdcCalibrationReason::from_repr(self.last_adc_calibration_reason)
+    }
+
+    /// Gets the PPS status, or `None` if the byte is not recognized.
+    pub fn pps_status(&self) -> Option<PpsStatus> {
+        PpsStatus::from_repr(self.pps_status)
+    }
+
+    /// Gets the thermal status, or `None` if the byte is not
+    /// recognized.
+    pub fn thermal_status(&self) -> Option<ThermalStatus> {
+        ThermalStatus::from_repr(self.thermal_status)
     }
 
     pub fn calibration_in_progress(&self) -> bool {
@@ -58,8 +151,30 @@ impl PositionPacket {
     }
 }
 
+impl Default for PositionPacket {
+    fn default() -> Self {
+        Self {
+            reserved_head: [0; 187],
+            top_board_temperature: 0,
+            bottom_board_temperature: 0,
+            last_adc_calibration_temperature: 0,
+            last_adc_calibration_temperature_change: 0,
+            seconds_since_last_adc_calibration: 0,
+            last_adc_calibration_reason: LastAdcCalibrationReason::NoCalibration as u8,
+            adc_calibration_bitmask: 0,
+            toh: 0,
+            pps_status: PpsStatus::Abscent as u8,
+            thermal_status: ThermalStatus::Ok as u8,
+            last_shutdown_temperature: 0,
+            temperature_of_unit_at_power_up: 0,
+            nmea: [0; 128],
+            reserved_tail: [0; 178],
+        }
+    }
+}
+
 #[repr(u8)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::FromRepr)]
 pub enum LastAdcCalibrationReason {
     NoCalibration = 0,
     PowerOn = 1,
@@ -69,7 +184,7 @@ pub enum LastAdcCalibrationReason {
 }
 
 #[repr(u8)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::FromRepr)]
 pub enum PpsStatus {
     Abscent = 0,
     Synchronizing = 1,
@@ -78,7 +193,7 @@ pub enum PpsStatus {
 }
 
 #[repr(u8)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::FromRepr)]
 pub enum ThermalStatus {
     Ok = 0,
     ThermalShutdown = 1,
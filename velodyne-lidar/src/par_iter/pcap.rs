@@ -1,10 +1,9 @@
 //! Packet parallel iterator creation functions.
 
 use crate::Packet;
-use anyhow::Result;
 use pcap::{Capture, Device};
 use rayon::{iter::IterBridge, prelude::*};
-use std::path::Path;
+use std::{io, path::Path};
 
 /// Creates a packet iterator from [pcap::Capture].
 pub fn from_capture<A>(
@@ -16,15 +15,14 @@ where
     Ok(crate::iter::packet_iter_from_capture(capture)?.par_bridge())
 }
 
-/// Creates a packet iterator by loading from a file.
+/// Creates a packet iterator by loading from a pcap or pcapng file.
 pub fn from_file<P>(
     path: P,
-) -> Result<IterBridge<impl Iterator<Item = Result<Packet, pcap::Error>> + Send>, pcap::Error>
+) -> io::Result<IterBridge<impl Iterator<Item = io::Result<Packet>> + Send>>
 where
     P: AsRef<Path>,
 {
-    let capture: Capture<pcap::Offline> = Capture::from_file(path)?;
-    from_capture(capture)
+    Ok(crate::capture::packet_iter_from_file(path)?.par_bridge())
 }
 
 /// Creates a packet iterator by reading a device.
@@ -0,0 +1,374 @@
+//! Range image projection of frames.
+//!
+//! A [RangeImage] is a dense grid of range, intensity and validity
+//! mask. Rows are ordered by elevation from top to bottom, and columns
+//! are fixed azimuth bins starting from the front of the sensor in the
+//! clockwise direction. It is suitable as the input of image-based
+//! neural networks.
+//!
+//! The arrays can be viewed as [ndarray] arrays with the `ndarray`
+//! feature.
+//!
+//! # Example
+//!
+//! ```rust
+//! # fn main() -> anyhow::Result<()> {
+//! use velodyne_lidar::{range_image::RangeImageProjector, Config, ProductID};
+//!
+//! let projector = RangeImageProjector::from_product_id(ProductID::VLP32C, 1024)?;
+//! let frames = velodyne_lidar::capture::frame_xyz_iter_from_file(
+//!     Config::new_vlp_32c_strongest(),
+//!     "tests/velodyne_vlp32.pcap",
+//! )?;
+//!
+//! for frame in frames {
+//!     let image = projector.project(&frame?)?;
+//!     assert_eq!((image.height(), image.width()), (32, 1024));
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    config::Config,
+    consts,
+    error::ensure_argument,
+    packet::ProductID,
+    types::{frame_xyz::FrameXyz, measurements::Measurement, point::PointRef},
+    Error,
+};
+use std::f64::consts::PI;
+
+/// Selects the return to fill the image for dual return frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ReturnSelection {
+    #[default]
+    Strongest,
+    Last,
+}
+
+/// Projects frames to [RangeImage]s.
+#[derive(Debug, Clone)]
+pub struct RangeImageProjector {
+    /// The laser ID of each row.
+    row_order: Vec<usize>,
+    /// The row of each laser ID.
+    laser_rows: Vec<usize>,
+    width: usize,
+    return_selection: ReturnSelection,
+}
+
+impl RangeImageProjector {
+    /// Creates a projector with the laser ID of each row from top to
+    /// bottom, and the number of azimuth bins.
+    ///
+    /// The `row_order` must be a permutation of the laser IDs.
+    pub fn new(row_order: Vec<usize>, width: usize) -> Result<Self, Error> {
+        ensure_argument!(width > 0, "the image width must be positive");
+
+        let height = row_order.len();
+        let mut laser_rows = vec![usize::MAX; height];
+        for (row, &laser_id) in row_order.iter().enumerate() {
+            ensure_argument!(
+                laser_id < height && laser_rows[laser_id] == usize::MAX,
+                "the row order is not a permutation of 0..{height}"
+            );
+            laser_rows[laser_id] = row;
+        }
+
+        Ok(Self {
+            row_order,
+            laser_rows,
+            width,
+            return_selection: ReturnSelection::default(),
+        })
+    }
+
+    /// Creates a projector with the elevation order of a sensor model.
+    pub fn from_product_id(product_id: ProductID, width: usize) -> Result<Self, Error> {
+        use ProductID as P;
+
+        let row_order = match product_id {
+            P::VLP16 | P::PuckLite | P::PuckHiRes => consts::vlp_16::ELEVAION_INDEX.to_vec(),
+            P::VLP32C => consts::vlp_32c::ELEVAION_INDEX.to_vec(),
+            P::HDL32E => consts::hdl_32e::ELEVAION_INDEX.to_vec(),
+            P::VLS128 => consts::vls_128::ELEVAION_INDEX.to_vec(),
+            P::Velarray => return Err(Error::UnsupportedProduct(product_id)),
+        };
+        Self::new(row_order, width)
+    }
+
+    /// Creates a projector that sorts the lasers of a config by the
+    /// elevation angle.
+    ///
+    /// It works for calibrated configs and models without a built-in
+    /// elevation order, such as HDL-64E.
+    pub fn from_config(config: &Config, width: usize) -> Result<Self, Error> {
+        let mut row_order: Vec<_> = (0..config.lasers.len()).collect();
+        row_order.sort_by(|&lhs, &rhs| {
+            let lhs = config.lasers[lhs].elevation.as_radians();
+            let rhs = config.lasers[rhs].elevation.as_radians();
+            rhs.total_cmp(&lhs)
+        });
+        Self::new(row_order, width)
+    }
+
+    /// Selects the return to fill the image for dual return frames.
+    pub fn with_return_selection(mut self, return_selection: ReturnSelection) -> Self {
+        self.return_selection = return_selection;
+        self
+    }
+
+    pub fn height(&self) -> usize {
+        self.row_order.len()
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Gets the laser ID of each row.
+    pub fn row_order(&self) -> &[usize] {
+        &self.row_order
+    }
+
+    /// Gets the column of an azimuth angle in radians.
+    pub fn column_of(&self, azimuth: f64) -> usize {
+        let ratio = azimuth.rem_euclid(PI * 2.0) / (PI * 2.0);
+        ((ratio * self.width as f64) as usize).min(self.width - 1)
+    }
+
+    /// Projects a frame to a range image.
+    ///
+    /// If multiple points fall in the same pixel, the nearest one is
+    /// kept. Points without a return are left out of the mask. It fails
+    /// if the number of lines of the frame differs from the height.
+    pub fn project(&self, frame: &FrameXyz) -> Result<RangeImage, Error> {
+        let num_lines = frame.format().num_lines();
+        ensure_argument!(
+            num_lines == self.height(),
+            "expect a frame with {} lines, but get {num_lines}",
+            self.height()
+        );
+
+        let mut image = RangeImage::new(self.height(), self.width);
+
+        for firing in frame.firing_iter() {
+            for point in firing.point_iter() {
+                let measurement = match (&point, self.return_selection) {
+                    (PointRef::Single(point), _) => &point.measurement,
+                    (PointRef::Dual(point), ReturnSelection::Strongest) => {
+                        &point.measurements.strongest
+                    }
+                    (PointRef::Dual(point), ReturnSelection::Last) => &point.measurements.last,
+                };
+                let Measurement {
+                    distance,
+                    intensity,
+                    ..
+                } = *measurement;
+
+                let range = distance.as_meters() as f32;
+                if range <= 0.0 {
+                    continue;
+                }
+
+                let row = self.laser_rows[point.laser_id()];
+                let col = self.column_of(point.azimuth().as_radians());
+                image.insert(row, col, range, intensity);
+            }
+        }
+
+        Ok(image)
+    }
+}
+
+/// A dense range image in row-major order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeImage {
+    height: usize,
+    width: usize,
+    /// Ranges in meters. It is zero on empty pixels.
+    range: Vec<f32>,
+    intensity: Vec<u8>,
+    mask: Vec<bool>,
+}
+
+impl RangeImage {
+    /// Creates an empty image.
+    pub fn new(height: usize, width: usize) -> Self {
+        let len = height * width;
+        Self {
+            height,
+            width,
+            range: vec![0.0; len],
+            intensity: vec![0; len],
+            mask: vec![false; len],
+        }
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Gets the ranges in meters. Empty pixels are zero.
+    pub fn range(&self) -> &[f32] {
+        &self.range
+    }
+
+    /// Gets the intensities. Empty pixels are zero.
+    pub fn intensity(&self) -> &[u8] {
+        &self.intensity
+    }
+
+    /// Gets the mask that is true on pixels with a point.
+    pub fn mask(&self) -> &[bool] {
+        &self.mask
+    }
+
+    /// Gets the range and intensity of a pixel, or `None` if the pixel
+    /// is empty or out of bounds.
+    pub fn get(&self, row: usize, col: usize) -> Option<(f32, u8)> {
+        if row >= self.height || col >= self.width {
+            return None;
+        }
+        let index = row * self.width + col;
+        self.mask[index].then(|| (self.range[index], self.intensity[index]))
+    }
+
+    /// Counts the pixels with a point.
+    pub fn num_valid(&self) -> usize {
+        self.mask.iter().filter(|&&valid| valid).count()
+    }
+
+    /// Decomposes into range, intensity and mask arrays.
+    pub fn into_parts(self) -> (Vec<f32>, Vec<u8>, Vec<bool>) {
+        (self.range, self.intensity, self.mask)
+    }
+
+    fn insert(&mut self, row: usize, col: usize, range: f32, intensity: u8) {
+        let index = row * self.width + col;
+        if !self.mask[index] || range < self.range[index] {
+            self.range[index] = range;
+            self.intensity[index] = intensity;
+            self.mask[index] = true;
+        }
+    }
+}
+
+#[cfg(feature = "ndarray")]
+mod ndarray_ext {
+    use super::RangeImage;
+    use ndarray::{Array2, ArrayView2};
+
+    impl RangeImage {
+        pub fn range_array(&self) -> ArrayView2<'_, f32> {
+            ArrayView2::from_shape((self.height, self.width), &self.range).unwrap()
+        }
+
+        pub fn intensity_array(&self) -> ArrayView2<'_, u8> {
+            ArrayView2::from_shape((self.height, self.width), &self.intensity).unwrap()
+        }
+
+        pub fn mask_array(&self) -> ArrayView2<'_, bool> {
+            ArrayView2::from_shape((self.height, self.width), &self.mask).unwrap()
+        }
+
+        /// Converts into owned range, intensity and mask arrays.
+        pub fn into_arrays(self) -> (Array2<f32>, Array2<u8>, Array2<bool>) {
+            let shape = (self.height, self.width);
+            (
+                Array2::from_shape_vec(shape, self.range).unwrap(),
+                Array2::from_shape_vec(shape, self.intensity).unwrap(),
+                Array2::from_shape_vec(shape, self.mask).unwrap(),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        packet::{Channel, DataPacketSynthesizer, ReturnMode},
+        FrameBuilder,
+    };
+
+    #[test]
+    fn projector_row_order() {
+        let projector = RangeImageProjector::from_product_id(ProductID::VLP16, 360).unwrap();
+        assert_eq!(projector.height(), 16);
+
+        let by_config =
+            RangeImageProjector::from_config(&Config::new_vlp_16_strongest(), 360).unwrap();
+        assert_eq!(by_config.row_order(), projector.row_order());
+
+        for (product_id, config) in [
+            (ProductID::VLP16, Config::new_vlp_16_strongest()),
+            (ProductID::VLP32C, Config::new_vlp_32c_strongest()),
+            (ProductID::HDL32E, Config::new_hdl_32e_strongest()),
+            (ProductID::VLS128, Config::new_vls_128_strongest()),
+        ] {
+            let projector = RangeImageProjector::from_product_id(product_id, 360).unwrap();
+            let elevations: Vec<_> = projector
+                .row_order()
+                .iter()
+                .map(|&laser_id| config.lasers[laser_id].elevation.as_degrees())
+                .collect();
+            assert!(
+                elevations.windows(2).all(|pair| pair[0] >= pair[1]),
+                "rows of {product_id:?} are not sorted by elevation"
+            );
+        }
+
+        assert!(RangeImageProjector::new(vec![0, 0], 360).is_err());
+        assert!(RangeImageProjector::new(vec![1, 0], 0).is_err());
+    }
+
+    #[test]
+    fn project_synthesized_frame() {
+        let packets = DataPacketSynthesizer::new(
+            ProductID::VLP16,
+            ReturnMode::Strongest,
+            600.0,
+            |_, laser_id| Channel {
+                distance: 500 + laser_id as u16,
+                intensity: laser_id as u8,
+            },
+        )
+        .unwrap()
+        .take(200);
+        let mut builder = FrameBuilder::new(Config::new_vlp_16_strongest()).unwrap();
+        let frame = packets
+            .flat_map(|packet| builder.push_packet(&packet))
+            .nth(1)
+            .unwrap();
+
+        let projector = RangeImageProjector::from_product_id(ProductID::VLP16, 900).unwrap();
+        let image = projector.project(&frame).unwrap();
+        assert_eq!(image.num_valid(), 16 * 900);
+
+        // The top row is laser 15, which points upwards the most.
+        for (row, &laser_id) in projector.row_order().iter().enumerate() {
+            let (range, intensity) = image.get(row, 450).unwrap();
+            assert_eq!(intensity, laser_id as u8);
+            assert!((range - (500 + laser_id) as f32 * 0.002).abs() < 1e-6);
+        }
+        assert_eq!(projector.row_order()[0], 15);
+
+        #[cfg(feature = "ndarray")]
+        {
+            let ranges = image.range_array();
+            assert_eq!(ranges.dim(), (16, 900));
+            assert_eq!(ranges[[0, 450]], image.get(0, 450).unwrap().0);
+            assert!(image.mask_array().iter().all(|&valid| valid));
+        }
+
+        let projector = RangeImageProjector::from_product_id(ProductID::VLP32C, 900).unwrap();
+        assert!(projector.project(&frame).is_err());
+    }
+}
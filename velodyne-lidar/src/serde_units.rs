@@ -0,0 +1,263 @@
+//! Serde representations of physical quantities.
+//!
+//! Lengths are serialized in meters and angles in radians, the units
+//! they are stored in, so that values round-trip exactly. Human
+//! readable formats such as YAML and JSON also accept strings with an
+//! explicit unit, for example `"4.2 cm"` or `"-15 deg"`.
+
+use measurements::{Angle, Length};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+/// A unit suffix and the conversion of its values to the base unit.
+type Unit = (&'static str, fn(f64) -> f64);
+
+/// A physical quantity made of a base unit and the accepted unit
+/// suffixes.
+///
+/// Suffixes that end with another suffix must come first.
+struct Quantity {
+    base: &'static str,
+    units: &'static [Unit],
+}
+
+static LENGTH: Quantity = Quantity {
+    base: "meters",
+    units: &[
+        ("mm", |value| Length::from_millimeters(value).as_meters()),
+        ("cm", |value| Length::from_centimeters(value).as_meters()),
+        ("m", |value| value),
+    ],
+};
+
+static ANGLE: Quantity = Quantity {
+    base: "radians",
+    units: &[
+        ("deg", |value| Angle::from_degrees(value).as_radians()),
+        ("°", |value| Angle::from_degrees(value).as_radians()),
+        ("rad", |value| value),
+    ],
+};
+
+impl Quantity {
+    fn deserialize<'de, D>(&'static self, deserializer: D) -> Result<f64, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(self)
+        } else {
+            deserializer.deserialize_f64(self)
+        }
+    }
+
+    fn parse(&self, text: &str) -> Option<f64> {
+        let text = text.trim();
+        self.units.iter().find_map(|&(unit, convert)| {
+            let value: f64 = text.strip_suffix(unit)?.trim_end().parse().ok()?;
+            Some(convert(value))
+        })
+    }
+}
+
+impl<'de> de::Visitor<'de> for &'static Quantity {
+    type Value = f64;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "a number in {} or a string with a unit in ",
+            self.base
+        )?;
+        for (index, (unit, _)) in self.units.iter().enumerate() {
+            if index > 0 {
+                write!(formatter, ", ")?;
+            }
+            write!(formatter, "{unit:?}")?;
+        }
+        Ok(())
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<f64, E>
+    where
+        E: de::Error,
+    {
+        Ok(value)
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<f64, E>
+    where
+        E: de::Error,
+    {
+        Ok(value as f64)
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<f64, E>
+    where
+        E: de::Error,
+    {
+        Ok(value as f64)
+    }
+
+    fn visit_str<E>(self, text: &str) -> Result<f64, E>
+    where
+        E: de::Error,
+    {
+        self.parse(text)
+            .ok_or_else(|| E::invalid_value(de::Unexpected::Str(text), &self))
+    }
+}
+
+/// A [Length] serialized in meters.
+#[derive(Serialize, Deserialize)]
+#[serde(transparent)]
+struct LengthRepr(#[serde(with = "length")] Length);
+
+/// An [Angle] serialized in radians.
+#[derive(Serialize, Deserialize)]
+#[serde(transparent)]
+struct AngleRepr(#[serde(with = "angle")] Angle);
+
+pub(crate) mod length {
+    use super::*;
+
+    pub fn serialize<S>(value: &Length, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_f64(value.as_meters())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Length, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        LENGTH.deserialize(deserializer).map(Length::from_meters)
+    }
+}
+
+pub(crate) mod angle {
+    use super::*;
+
+    pub fn serialize<S>(value: &Angle, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_f64(value.as_radians())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Angle, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        ANGLE.deserialize(deserializer).map(Angle::from_radians)
+    }
+}
+
+/// Serializes a point in 3D Cartesian coordinates as three lengths.
+pub(crate) mod xyz {
+    use super::*;
+
+    pub fn serialize<S>(value: &[Length; 3], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        value.map(LengthRepr).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<[Length; 3], D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let xyz = <[LengthRepr; 3]>::deserialize(deserializer)?;
+        Ok(xyz.map(|LengthRepr(value)| value))
+    }
+}
+
+/// Serializes a range of angles as a struct of `start` and `end`.
+pub(crate) mod angle_range {
+    use super::*;
+    use std::ops::Range;
+
+    pub fn serialize<S>(value: &Range<Angle>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let range = AngleRepr(value.start)..AngleRepr(value.end);
+        range.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Range<Angle>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let Range {
+            start: AngleRepr(start),
+            end: AngleRepr(end),
+        } = Range::deserialize(deserializer)?;
+        Ok(start..end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        capture::frame_xyz_iter_from_file,
+        types::{frame_xyz::FrameXyz, measurements::Measurement},
+        Beam, Config,
+    };
+    use measurements::{Angle, Length};
+
+    #[test]
+    fn parse_units() {
+        let beam: Beam = serde_yaml::from_str(
+            "
+            elevation: -15 deg
+            azimuth_offset: 0.1
+            vertical_offset: 11.2mm
+            horizontal_offset: 4.2 cm
+            ",
+        )
+        .unwrap();
+        assert_eq!(beam.elevation, Angle::from_degrees(-15.0));
+        assert_eq!(beam.azimuth_offset, Angle::from_radians(0.1));
+        assert_eq!(beam.vertical_offset, Length::from_millimeters(11.2));
+        assert_eq!(beam.horizontal_offset, Length::from_centimeters(4.2));
+        assert!(beam.correction.is_none());
+
+        let measurement: Measurement =
+            serde_yaml::from_str("{distance: 2 m, intensity: 3, xyz: [1, 0.5 m, 10 cm]}").unwrap();
+        assert_eq!(measurement.distance, Length::from_meters(2.0));
+        assert_eq!(measurement.xyz, [1.0, 0.5, 0.1].map(Length::from_meters));
+
+        assert!(serde_yaml::from_str::<Beam>(
+            "{elevation: 1 m, azimuth_offset: 0, vertical_offset: 0, horizontal_offset: 0}"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn config_round_trip() {
+        let config = Config::new_vlp_32c_strongest();
+        let text = serde_yaml::to_string(&config).unwrap();
+        let parsed: Config = serde_yaml::from_str(&text).unwrap();
+        assert_eq!(format!("{parsed:?}"), format!("{config:?}"));
+    }
+
+    #[test]
+    fn frame_round_trip() {
+        let config = Config::new_vlp_32c_strongest();
+        let frame = frame_xyz_iter_from_file(config, "tests/velodyne_vlp32.pcap")
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        let text = serde_yaml::to_string(&frame).unwrap();
+        let parsed: FrameXyz = serde_yaml::from_str(&text).unwrap();
+
+        let frame = frame.try_into_s32().unwrap();
+        let parsed = parsed.try_into_s32().unwrap();
+        assert_eq!(parsed.firings, frame.firings);
+        assert_eq!(parsed.stats, frame.stats);
+    }
+}
@@ -0,0 +1,14 @@
+//! Async stream combinators for tokio.
+//!
+//! The streams are pull-based. Packets are read from the socket or
+//! the file only when the frame stream is polled, so a slow consumer
+//! applies backpressure down to the source.
+
+mod convert;
+pub use convert::*;
+
+mod udp;
+pub use udp::*;
+
+mod file;
+pub use file::*;
@@ -0,0 +1,92 @@
+//! Stream conversion functions.
+
+use crate::{frame_builder::FrameBuilder, types::frame_xyz::FrameXyz, Config, Error, Packet};
+use futures::{
+    future,
+    stream::{self, Stream, StreamExt as _, TryStreamExt as _},
+};
+
+/// Converts a stream of packets to a stream of [FrameXyz].
+///
+/// It is the async counterpart of
+/// [try_packet_to_frame_xyz](crate::iter::try_packet_to_frame_xyz).
+/// The stream stops after the first error.
+pub fn try_packet_stream_to_frame_xyz<S, E>(
+    config: Config,
+    packets: S,
+) -> Result<impl Stream<Item = Result<FrameXyz, E>> + Send, Error>
+where
+    S: Stream<Item = Result<Packet, E>> + Send,
+    E: Send,
+{
+    let builder = FrameBuilder::new(config)?;
+
+    let stream = packets
+        .try_filter_map(|packet| future::ready(Ok(packet.try_into_data().ok())))
+        .scan(Some(builder), |builder, packet| {
+            let frames = match (builder.as_mut(), packet) {
+                (None, _) => return future::ready(None),
+                (Some(builder), Ok(packet)) => Ok(builder.push_packet(&packet)),
+                (Some(_), Err(err)) => {
+                    *builder = None;
+                    Err(err)
+                }
+            };
+            future::ready(Some(frames))
+        })
+        .map_ok(|frames| stream::iter(frames.into_iter().map(Ok)))
+        .try_flatten();
+
+    Ok(stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{iter::try_packet_to_frame_xyz, stream::packet_stream_from_file};
+    use std::io;
+
+    #[tokio::test]
+    async fn same_frames_as_iterator() -> Result<(), Error> {
+        let packets: Vec<_> = packet_stream_from_file("tests/velodyne_vlp32.pcap")
+            .await?
+            .try_collect()
+            .await?;
+        let config = Config::new_vlp_32c_strongest();
+
+        let expect: Vec<_> = try_packet_to_frame_xyz(
+            config.clone(),
+            packets.iter().cloned().map(Ok::<_, io::Error>),
+        )?
+        .collect::<Result<_, _>>()?;
+        let frames: Vec<_> = try_packet_stream_to_frame_xyz(
+            config,
+            stream::iter(packets.into_iter().map(Ok::<_, io::Error>)),
+        )?
+        .try_collect()
+        .await?;
+
+        assert!(!frames.is_empty());
+        assert_eq!(frames.len(), expect.len());
+        for (frame, expect) in frames.iter().zip(&expect) {
+            assert_eq!(frame.firing_iter().count(), expect.firing_iter().count());
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn stop_after_error() -> Result<(), Error> {
+        let packets = stream::iter([
+            Err(io::Error::from(io::ErrorKind::Other)),
+            Err(io::Error::from(io::ErrorKind::Other)),
+        ]);
+        let results: Vec<_> =
+            try_packet_stream_to_frame_xyz(Config::new_vlp_32c_strongest(), packets)?
+                .collect()
+                .await;
+
+        assert_eq!(results.len(), 1);
+        assert!(results[0].is_err());
+        Ok(())
+    }
+}
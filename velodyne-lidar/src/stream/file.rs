@@ -0,0 +1,215 @@
+//! Packet stream creation functions on pcap and pcapng files.
+
+use super::convert::try_packet_stream_to_frame_xyz;
+use crate::{
+    capture::{
+        file_header_len, CaptureEvent, CaptureReader, CaptureRecord, EventDecoder, FILE_PREFIX_LEN,
+    },
+    types::frame_xyz::FrameXyz,
+    Config, Error, Packet,
+};
+use futures::stream::{self, Stream};
+use std::{
+    io::{self, Cursor},
+    path::Path,
+};
+use tokio::{
+    fs::File,
+    io::{AsyncRead, AsyncReadExt as _, BufReader},
+};
+
+/// Creates a packet stream from a reader of a pcap or pcapng file.
+///
+/// Files are parsed and frames are decoded in the same way as
+/// [PacketReader](crate::capture::PacketReader), but frames that do
+/// not yield a packet are discarded.
+pub fn packet_stream_from_pcap_reader<R>(reader: R) -> impl Stream<Item = io::Result<Packet>> + Send
+where
+    R: AsyncRead + Unpin + Send,
+{
+    let state = AsyncPacketReader {
+        reader,
+        records: None,
+        events: EventDecoder::new(),
+    };
+
+    stream::unfold(Some(state), |state| async move {
+        let mut state = state?;
+
+        match state.next_packet().await {
+            Ok(Some(packet)) => Some((Ok(packet), Some(state))),
+            Ok(None) => None,
+            Err(err) => Some((Err(err), None)),
+        }
+    })
+}
+
+/// Creates a packet stream by loading from a pcap or pcapng file.
+pub async fn packet_stream_from_file<P>(
+    path: P,
+) -> io::Result<impl Stream<Item = io::Result<Packet>> + Send>
+where
+    P: AsRef<Path>,
+{
+    let file = File::open(path).await?;
+    Ok(packet_stream_from_pcap_reader(BufReader::new(file)))
+}
+
+/// Creates a frame stream by loading from a pcap or pcapng file.
+pub async fn frame_xyz_stream_from_file<P>(
+    config: Config,
+    path: P,
+) -> Result<impl Stream<Item = io::Result<FrameXyz>> + Send, Error>
+where
+    P: AsRef<Path>,
+{
+    let packets = packet_stream_from_file(path).await?;
+    try_packet_stream_to_frame_xyz(config, packets)
+}
+
+/// Reads whole records from an async reader and parses them with a
+/// [CaptureReader] on the buffered bytes.
+struct AsyncPacketReader<R> {
+    reader: R,
+    /// The parser of the buffered records, created once the file
+    /// header is read.
+    records: Option<CaptureReader<Cursor<Vec<u8>>>>,
+    events: EventDecoder,
+}
+
+impl<R> AsyncPacketReader<R>
+where
+    R: AsyncRead + Unpin,
+{
+    async fn next_packet(&mut self) -> io::Result<Option<Packet>> {
+        while let Some(record) = self.next_record().await? {
+            let event = self
+                .events
+                .decode(record.link_type, &record.data, record.timestamp);
+            if let Some(CaptureEvent::Packet(packet)) = event {
+                return Ok(Some(packet.packet));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn next_record(&mut self) -> io::Result<Option<CaptureRecord>> {
+        let Self {
+            reader, records, ..
+        } = self;
+
+        let records = match records {
+            Some(records) => records,
+            None => {
+                let mut prefix = [0u8; FILE_PREFIX_LEN];
+                reader.read_exact(&mut prefix).await?;
+                let header = read_rest(reader, &prefix, file_header_len(&prefix)?).await?;
+                records.insert(CaptureReader::new(Cursor::new(header))?)
+            }
+        };
+
+        loop {
+            let mut prefix = vec![0u8; records.record_prefix_len()];
+            if !read_or_eof(reader, &mut prefix).await? {
+                return Ok(None);
+            }
+            let len = records.record_len(&prefix)?;
+            *records.get_mut() = Cursor::new(read_rest(reader, &prefix, len).await?);
+
+            // blocks without a frame, such as interface descriptions,
+            // are consumed without a record
+            if let Some(record) = records.next_record()? {
+                return Ok(Some(record));
+            }
+        }
+    }
+}
+
+/// Reads the rest of a record of `len` bytes after its `prefix`.
+async fn read_rest<R>(reader: &mut R, prefix: &[u8], len: usize) -> io::Result<Vec<u8>>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut buffer = prefix.to_vec();
+    buffer.resize(len, 0);
+    reader.read_exact(&mut buffer[prefix.len()..]).await?;
+    Ok(buffer)
+}
+
+/// Fills `buffer`, or returns false if the reader is at the end.
+async fn read_or_eof<R>(reader: &mut R, buffer: &mut [u8]) -> io::Result<bool>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]).await? {
+            0 if filled == 0 => return Ok(false),
+            0 => return Err(io::ErrorKind::UnexpectedEof.into()),
+            len => filled += len,
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture::{
+        packet_iter_from_file, CaptureFormat, PacketReader, PcapWriter, PcapWriterOptions,
+    };
+    use futures::TryStreamExt as _;
+    use std::time::UNIX_EPOCH;
+
+    #[tokio::test]
+    async fn read_pcap_file() -> io::Result<()> {
+        let packets: Vec<_> = packet_stream_from_file("tests/velodyne_vlp32.pcap")
+            .await?
+            .try_collect()
+            .await?;
+
+        assert!(!packets.is_empty());
+        assert!(packets.iter().any(|packet| packet.as_data().is_some()));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_same_packets_as_packet_reader() -> io::Result<()> {
+        let packets: Vec<_> = packet_iter_from_file("tests/velodyne_vlp32.pcap")?
+            .take(20)
+            .collect::<io::Result<_>>()?;
+
+        for format in [CaptureFormat::Pcap, CaptureFormat::PcapNg] {
+            let mut writer = PcapWriter::new(
+                vec![],
+                PcapWriterOptions {
+                    format,
+                    ..Default::default()
+                },
+            )?;
+            for packet in &packets {
+                writer.write_packet(packet, UNIX_EPOCH)?;
+            }
+            let bytes = writer.into_inner()?;
+
+            let read: Vec<_> = packet_stream_from_pcap_reader(&bytes[..])
+                .try_collect()
+                .await?;
+            let expect: Vec<_> = PacketReader::new(&bytes[..])?
+                .packets()
+                .map(|packet| packet.map(|packet| packet.packet))
+                .collect::<io::Result<_>>()?;
+            assert_eq!(read, packets);
+            assert_eq!(read, expect);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn reject_non_pcap_data() {
+        let mut stream = Box::pin(packet_stream_from_pcap_reader(&[0u8; 64][..]));
+        let err = stream.try_next().await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(stream.try_next().await.unwrap().is_none());
+    }
+}
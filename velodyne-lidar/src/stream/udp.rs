@@ -0,0 +1,190 @@
+//! Packet stream creation functions on UDP sockets.
+
+use super::convert::try_packet_stream_to_frame_xyz;
+use crate::{
+    iter::{bind_socket, ReceivedPacket, UdpOptions, DATAGRAM_BUFFER_SIZE},
+    types::frame_xyz::FrameXyz,
+    Config, Error, Packet,
+};
+use futures::stream::{self, BoxStream, SelectAll, Stream, StreamExt as _, TryStreamExt as _};
+use std::{
+    fmt, io,
+    net::{IpAddr, SocketAddr},
+    pin::Pin,
+    task::{Context, Poll},
+    time::SystemTime,
+};
+use tokio::net::UdpSocket;
+
+/// Receives Velodyne packets from UDP sockets asynchronously.
+///
+/// It is the async counterpart of [UdpReceiver](crate::iter::UdpReceiver).
+/// Datagrams are read only when the stream is polled, and pending
+/// datagrams are kept in the socket receive buffer, whose size is
+/// set by [UdpOptions::socket_buffer_size]. Datagrams that are not
+/// data or position packets are discarded.
+pub struct UdpStream {
+    streams: SelectAll<BoxStream<'static, io::Result<ReceivedPacket>>>,
+    data_addr: Option<SocketAddr>,
+    position_addr: Option<SocketAddr>,
+}
+
+impl UdpStream {
+    /// Binds sockets in the current tokio runtime.
+    pub fn bind(options: UdpOptions) -> io::Result<Self> {
+        let UdpOptions {
+            bind_ip,
+            data_port,
+            position_port,
+            sources,
+            socket_buffer_size,
+            ..
+        } = options;
+
+        let bind = |port: u16| -> io::Result<_> {
+            let socket = bind_socket(SocketAddr::new(bind_ip, port), socket_buffer_size)?;
+            socket.set_nonblocking(true)?;
+            UdpSocket::from_std(socket)
+        };
+        let data_socket = data_port.map(bind).transpose()?;
+        let position_socket = position_port.map(bind).transpose()?;
+
+        let data_addr = data_socket
+            .as_ref()
+            .map(UdpSocket::local_addr)
+            .transpose()?;
+        let position_addr = position_socket
+            .as_ref()
+            .map(UdpSocket::local_addr)
+            .transpose()?;
+
+        let streams = [data_socket, position_socket]
+            .into_iter()
+            .flatten()
+            .map(|socket| socket_stream(socket, sources.clone()).boxed());
+
+        Ok(Self {
+            streams: stream::select_all(streams),
+            data_addr,
+            position_addr,
+        })
+    }
+
+    /// Gets the local address receiving data packets.
+    pub fn data_addr(&self) -> Option<SocketAddr> {
+        self.data_addr
+    }
+
+    /// Gets the local address receiving position packets.
+    pub fn position_addr(&self) -> Option<SocketAddr> {
+        self.position_addr
+    }
+}
+
+impl Stream for UdpStream {
+    type Item = io::Result<ReceivedPacket>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.streams.poll_next_unpin(cx)
+    }
+}
+
+impl fmt::Debug for UdpStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UdpStream")
+            .field("data_addr", &self.data_addr)
+            .field("position_addr", &self.position_addr)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Creates a packet stream by receiving from UDP sockets.
+///
+/// It must be called within a tokio runtime.
+pub fn packet_stream_from_udp(
+    options: UdpOptions,
+) -> io::Result<impl Stream<Item = io::Result<Packet>> + Send> {
+    let stream = UdpStream::bind(options)?.map_ok(|packet| packet.packet);
+    Ok(stream)
+}
+
+/// Creates a frame stream by receiving from UDP sockets.
+///
+/// It must be called within a tokio runtime.
+pub fn frame_xyz_stream_from_udp(
+    config: Config,
+    options: UdpOptions,
+) -> Result<impl Stream<Item = io::Result<FrameXyz>> + Send, Error> {
+    let packets = packet_stream_from_udp(options)?;
+    try_packet_stream_to_frame_xyz(config, packets)
+}
+
+/// Reads packets from a socket until an error occurs.
+fn socket_stream(
+    socket: UdpSocket,
+    sources: Vec<IpAddr>,
+) -> impl Stream<Item = io::Result<ReceivedPacket>> + Send {
+    stream::unfold(Some((socket, sources)), |state| async move {
+        let (socket, sources) = state?;
+        let mut buffer = [0u8; DATAGRAM_BUFFER_SIZE];
+
+        loop {
+            let (len, source) = match socket.recv_from(&mut buffer).await {
+                Ok(output) => output,
+                Err(err) => return Some((Err(err), None)),
+            };
+            let received_at = SystemTime::now();
+
+            if !sources.is_empty() && !sources.contains(&source.ip()) {
+                continue;
+            }
+            let Ok(packet) = Packet::from_slice(&buffer[..len]) else {
+                continue;
+            };
+
+            let packet = ReceivedPacket {
+                packet,
+                source,
+                received_at,
+            };
+            return Some((Ok(packet), Some((socket, sources))));
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PositionPacket;
+    use std::{mem, net::Ipv4Addr, time::Duration};
+    use tokio::time::timeout;
+
+    #[tokio::test]
+    async fn receive_over_loopback() -> io::Result<()> {
+        let mut stream = UdpStream::bind(UdpOptions {
+            bind_ip: Ipv4Addr::LOCALHOST.into(),
+            data_port: Some(0),
+            position_port: Some(0),
+            ..Default::default()
+        })?;
+        let sender = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).await?;
+
+        // datagrams of other sizes are discarded
+        sender
+            .send_to(&[0; 16], stream.position_addr().unwrap())
+            .await?;
+        let bytes = vec![0; mem::size_of::<PositionPacket>()];
+        sender
+            .send_to(&bytes, stream.position_addr().unwrap())
+            .await?;
+
+        let received = timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("timeout")
+            .unwrap()?;
+        assert!(received.packet.as_position().is_some());
+        assert_eq!(received.source, sender.local_addr()?);
+
+        Ok(())
+    }
+}
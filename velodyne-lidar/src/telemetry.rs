@@ -0,0 +1,391 @@
+//! Sensor status from position packets.
+//!
+//! [Telemetry] is a typed snapshot of the status fields in a
+//! [PositionPacket]. Use
+//! [packet_to_telemetry](crate::iter::packet_to_telemetry) to turn a
+//! packet stream into a time series, and [HealthMonitor] to raise
+//! [HealthEvent]s when the status changes.
+
+use crate::packet::{
+    LastAdcCalibrationReason, PacketError, PositionPacket, PpsStatus, ThermalStatus,
+};
+use measurements::{Temperature, TemperatureDelta};
+use std::time::Duration;
+
+/// The status reported by one position packet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Telemetry {
+    /// The time of hour when the packet is sent.
+    pub toh: Duration,
+    pub top_board_temperature: Temperature,
+    pub bottom_board_temperature: Temperature,
+    pub pps_status: PpsStatus,
+    pub thermal_status: ThermalStatus,
+    pub calibration: CalibrationStatus,
+    /// The temperature when the last thermal shutdown happened.
+    pub last_shutdown_temperature: Temperature,
+    pub temperature_at_power_up: Temperature,
+}
+
+/// The state of the ADC calibration.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CalibrationStatus {
+    pub in_progress: bool,
+    pub last_reason: LastAdcCalibrationReason,
+    /// The temperature when the last calibration ran.
+    pub last_temperature: Temperature,
+    /// The temperature change since the last calibration.
+    pub temperature_change: TemperatureDelta,
+    pub elapsed_since_last: Duration,
+    /// The temperature change exceeds the limit to calibrate again.
+    pub meet_delta_temperature: bool,
+    /// The elapsed time exceeds the limit to calibrate again.
+    pub meet_periodic_elapsed_time_limit: bool,
+}
+
+impl Telemetry {
+    /// Reads the status of a position packet.
+    ///
+    /// It fails if any status field is not recognized.
+    pub fn from_position_packet(packet: &PositionPacket) -> Result<Self, PacketError> {
+        let celsius = |value: u8| Temperature::from_celsius(value as f64);
+
+        // The status fields are known to be valid after the check.
+        packet.validate()?;
+
+        Ok(Self {
+            toh: packet.toh(),
+            top_board_temperature: celsius(packet.top_board_temperature),
+            bottom_board_temperature: celsius(packet.bottom_board_temperature),
+            pps_status: packet.pps_status().unwrap(),
+            thermal_status: packet.thermal_status().unwrap(),
+            calibration: CalibrationStatus {
+                in_progress: packet.calibration_in_progress(),
+                last_reason: packet.last_adc_calibration_reason().unwrap(),
+                last_temperature: celsius(packet.last_adc_calibration_temperature),
+                temperature_change: TemperatureDelta::from_celsius(
+                    packet.last_adc_calibration_temperature_change as f64,
+                ),
+                elapsed_since_last: Duration::from_secs(
+                    packet.seconds_since_last_adc_calibration as u64,
+                ),
+                meet_delta_temperature: packet.meet_delta_temperature(),
+                meet_periodic_elapsed_time_limit: packet.meet_periodic_elapsed_time_limit(),
+            },
+            last_shutdown_temperature: celsius(packet.last_shutdown_temperature),
+            temperature_at_power_up: celsius(packet.temperature_of_unit_at_power_up),
+        })
+    }
+
+    /// Gets the higher one of the board temperatures.
+    pub fn max_board_temperature(&self) -> Temperature {
+        if self.top_board_temperature >= self.bottom_board_temperature {
+            self.top_board_temperature
+        } else {
+            self.bottom_board_temperature
+        }
+    }
+}
+
+impl TryFrom<&PositionPacket> for Telemetry {
+    type Error = PacketError;
+
+    fn try_from(packet: &PositionPacket) -> Result<Self, Self::Error> {
+        Self::from_position_packet(packet)
+    }
+}
+
+/// A change in the sensor health detected by [HealthMonitor].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HealthEvent {
+    /// The PPS signal becomes locked.
+    PpsLocked,
+    /// The PPS signal was locked but is no longer.
+    PpsLost {
+        status: PpsStatus,
+    },
+    /// The sensor reports an error on the PPS signal.
+    PpsError,
+    /// The sensor shut down the laser due to the temperature.
+    ThermalShutdown {
+        temperature: Temperature,
+    },
+    /// The sensor recovered from a thermal shutdown.
+    ThermalRecovered,
+    /// The board temperature exceeds the configured limit.
+    OverTemperature {
+        temperature: Temperature,
+    },
+    /// The board temperature falls back below the limit minus the
+    /// hysteresis.
+    TemperatureNormal {
+        temperature: Temperature,
+    },
+    /// An ADC calibration starts. Data may be degraded meanwhile.
+    CalibrationStarted {
+        reason: LastAdcCalibrationReason,
+    },
+    CalibrationFinished,
+}
+
+/// Options for [HealthMonitor].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HealthMonitorOptions {
+    /// The board temperature to raise
+    /// [OverTemperature](HealthEvent::OverTemperature). `None`
+    /// disables the check.
+    pub max_board_temperature: Option<Temperature>,
+    /// How far the temperature must fall below the limit before
+    /// [TemperatureNormal](HealthEvent::TemperatureNormal) is raised.
+    pub temperature_hysteresis: TemperatureDelta,
+}
+
+impl Default for HealthMonitorOptions {
+    fn default() -> Self {
+        Self {
+            max_board_temperature: None,
+            temperature_hysteresis: TemperatureDelta::from_celsius(5.0),
+        }
+    }
+}
+
+/// Raises [HealthEvent]s when the status of the sensor changes.
+///
+/// Events are raised on changes only. For the first sample, only the
+/// abnormal states are reported, such as a thermal shutdown or a
+/// calibration in progress.
+#[derive(Debug, Clone, Default)]
+pub struct HealthMonitor {
+    options: HealthMonitorOptions,
+    last: Option<Telemetry>,
+    over_temperature: bool,
+}
+
+impl HealthMonitor {
+    pub fn new(options: HealthMonitorOptions) -> Self {
+        Self {
+            options,
+            last: None,
+            over_temperature: false,
+        }
+    }
+
+    /// Gets the latest telemetry.
+    pub fn last(&self) -> Option<&Telemetry> {
+        self.last.as_ref()
+    }
+
+    /// Checks if the latest telemetry reports no problem.
+    ///
+    /// It is true before any telemetry is pushed.
+    pub fn is_healthy(&self) -> bool {
+        let Some(last) = &self.last else {
+            return true;
+        };
+        last.pps_status != PpsStatus::Error
+            && last.thermal_status == ThermalStatus::Ok
+            && !self.over_temperature
+    }
+
+    /// Pushes the telemetry of a position packet and returns the
+    /// raised events.
+    ///
+    /// It fails if any status field of the packet is not recognized.
+    pub fn push_packet(
+        &mut self,
+        packet: &PositionPacket,
+    ) -> Result<Vec<HealthEvent>, PacketError> {
+        Ok(self.push(Telemetry::from_position_packet(packet)?))
+    }
+
+    /// Pushes a telemetry sample and returns the raised events.
+    pub fn push(&mut self, curr: Telemetry) -> Vec<HealthEvent> {
+        use HealthEvent as E;
+
+        let mut events = vec![];
+        let prev = self.last.replace(curr);
+
+        // PPS
+        let prev_pps = prev.map(|prev| prev.pps_status);
+        if prev_pps != Some(curr.pps_status) {
+            match curr.pps_status {
+                PpsStatus::Locked => events.push(E::PpsLocked),
+                PpsStatus::Error => events.push(E::PpsError),
+                status if prev_pps == Some(PpsStatus::Locked) => events.push(E::PpsLost { status }),
+                _ => {}
+            }
+        }
+
+        // thermal shutdown
+        let prev_thermal = prev.map(|prev| prev.thermal_status);
+        if prev_thermal != Some(curr.thermal_status) {
+            match curr.thermal_status {
+                ThermalStatus::ThermalShutdown => events.push(E::ThermalShutdown {
+                    temperature: curr.max_board_temperature(),
+                }),
+                ThermalStatus::Ok if prev_thermal.is_some() => events.push(E::ThermalRecovered),
+                ThermalStatus::Ok => {}
+            }
+        }
+
+        // board temperature
+        if let Some(limit) = self.options.max_board_temperature {
+            let temperature = curr.max_board_temperature();
+            if !self.over_temperature && temperature >= limit {
+                self.over_temperature = true;
+                events.push(E::OverTemperature { temperature });
+            } else if self.over_temperature
+                && temperature < limit - self.options.temperature_hysteresis
+            {
+                self.over_temperature = false;
+                events.push(E::TemperatureNormal { temperature });
+            }
+        }
+
+        // calibration
+        let prev_calibrating = prev.map(|prev| prev.calibration.in_progress);
+        match (prev_calibrating, curr.calibration.in_progress) {
+            (Some(false) | None, true) => events.push(E::CalibrationStarted {
+                reason: curr.calibration.last_reason,
+            }),
+            (Some(true), false) => events.push(E::CalibrationFinished),
+            _ => {}
+        }
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{capture::PacketReader, iter::try_packet_to_telemetry};
+
+    fn packet(pps_status: PpsStatus, temperature: u8) -> PositionPacket {
+        PositionPacket {
+            pps_status: pps_status as u8,
+            top_board_temperature: temperature,
+            bottom_board_temperature: temperature - 2,
+            ..PositionPacket::default()
+        }
+    }
+
+    #[test]
+    fn telemetry_from_capture() {
+        let packets = PacketReader::open("tests/velodyne_vlp32.pcap")
+            .unwrap()
+            .packets()
+            .map(|packet| packet.map(|packet| packet.packet));
+        let series: Vec<_> = try_packet_to_telemetry(packets)
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert!(!series.is_empty());
+        assert!(series.windows(2).all(|pair| pair[0].toh <= pair[1].toh));
+        assert!(series
+            .iter()
+            .all(|telemetry| telemetry.pps_status == PpsStatus::Abscent));
+    }
+
+    #[test]
+    fn pps_events() {
+        let mut monitor = HealthMonitor::default();
+        assert!(monitor.is_healthy());
+
+        assert_eq!(
+            monitor
+                .push_packet(&packet(PpsStatus::Abscent, 40))
+                .unwrap(),
+            vec![]
+        );
+        assert_eq!(
+            monitor
+                .push_packet(&packet(PpsStatus::Synchronizing, 40))
+                .unwrap(),
+            vec![]
+        );
+        assert_eq!(
+            monitor.push_packet(&packet(PpsStatus::Locked, 40)).unwrap(),
+            vec![HealthEvent::PpsLocked]
+        );
+        assert_eq!(
+            monitor.push_packet(&packet(PpsStatus::Locked, 40)).unwrap(),
+            vec![]
+        );
+        assert_eq!(
+            monitor
+                .push_packet(&packet(PpsStatus::Abscent, 40))
+                .unwrap(),
+            vec![HealthEvent::PpsLost {
+                status: PpsStatus::Abscent
+            }]
+        );
+        assert_eq!(
+            monitor.push_packet(&packet(PpsStatus::Error, 40)).unwrap(),
+            vec![HealthEvent::PpsError]
+        );
+        assert!(!monitor.is_healthy());
+    }
+
+    #[test]
+    fn thermal_and_calibration_events() {
+        let mut monitor = HealthMonitor::new(HealthMonitorOptions {
+            max_board_temperature: Some(Temperature::from_celsius(70.0)),
+            ..HealthMonitorOptions::default()
+        });
+
+        let mut calibrating = packet(PpsStatus::Abscent, 40);
+        calibrating.adc_calibration_bitmask = 0b0001;
+        calibrating.last_adc_calibration_reason = LastAdcCalibrationReason::PowerOn as u8;
+        assert_eq!(
+            monitor.push_packet(&calibrating).unwrap(),
+            vec![HealthEvent::CalibrationStarted {
+                reason: LastAdcCalibrationReason::PowerOn
+            }]
+        );
+        assert_eq!(
+            monitor
+                .push_packet(&packet(PpsStatus::Abscent, 40))
+                .unwrap(),
+            vec![HealthEvent::CalibrationFinished]
+        );
+
+        let hot = packet(PpsStatus::Abscent, 72);
+        assert_eq!(
+            monitor.push_packet(&hot).unwrap(),
+            vec![HealthEvent::OverTemperature {
+                temperature: Temperature::from_celsius(72.0)
+            }]
+        );
+        // within the hysteresis
+        assert_eq!(
+            monitor
+                .push_packet(&packet(PpsStatus::Abscent, 67))
+                .unwrap(),
+            vec![]
+        );
+
+        let mut shutdown = packet(PpsStatus::Abscent, 80);
+        shutdown.thermal_status = ThermalStatus::ThermalShutdown as u8;
+        assert_eq!(
+            monitor.push_packet(&shutdown).unwrap(),
+            vec![HealthEvent::ThermalShutdown {
+                temperature: Temperature::from_celsius(80.0)
+            }]
+        );
+        assert!(!monitor.is_healthy());
+
+        assert_eq!(
+            monitor
+                .push_packet(&packet(PpsStatus::Abscent, 50))
+                .unwrap(),
+            vec![
+                HealthEvent::ThermalRecovered,
+                HealthEvent::TemperatureNormal {
+                    temperature: Temperature::from_celsius(50.0)
+                }
+            ]
+        );
+        assert!(monitor.is_healthy());
+    }
+}
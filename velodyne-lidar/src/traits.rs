@@ -10,3 +10,6 @@ mod point_field;
 
 pub use firing_like::*;
 mod firing_like;
+
+pub use time_of_hour::*;
+mod time_of_hour;
@@ -1,7 +1,16 @@
 use crate::types::{
-    firing_block::{FiringBlockD16, FiringBlockD32, FiringBlockS16, FiringBlockS32},
-    firing_xyz::{FiringXyzD16, FiringXyzD32, FiringXyzS16, FiringXyzS32},
-    frame_xyz::{FrameXyzD16, FrameXyzD32, FrameXyzS16, FrameXyzS32},
+    firing_block::{
+        FiringBlockD128, FiringBlockD16, FiringBlockD32, FiringBlockD64, FiringBlockS128,
+        FiringBlockS16, FiringBlockS32, FiringBlockS64,
+    },
+    firing_xyz::{
+        FiringXyzD128, FiringXyzD16, FiringXyzD32, FiringXyzD64, FiringXyzS128, FiringXyzS16,
+        FiringXyzS32, FiringXyzS64,
+    },
+    frame_xyz::{
+        FrameXyzD128, FrameXyzD16, FrameXyzD32, FrameXyzD64, FrameXyzS128, FrameXyzS16,
+        FrameXyzS32, FrameXyzS64,
+    },
 };
 use measurements::Angle;
 use std::ops::Range;
@@ -43,6 +52,30 @@ impl<'a> AzimuthRange for FiringBlockD32<'a> {
     }
 }
 
+impl<'a> AzimuthRange for FiringBlockS64<'a> {
+    fn azimuth_range(&self) -> Range<Angle> {
+        self.azimuth_range.clone()
+    }
+}
+
+impl<'a> AzimuthRange for FiringBlockS128<'a> {
+    fn azimuth_range(&self) -> Range<Angle> {
+        self.azimuth_range.clone()
+    }
+}
+
+impl<'a> AzimuthRange for FiringBlockD64<'a> {
+    fn azimuth_range(&self) -> Range<Angle> {
+        self.azimuth_range.clone()
+    }
+}
+
+impl<'a> AzimuthRange for FiringBlockD128<'a> {
+    fn azimuth_range(&self) -> Range<Angle> {
+        self.azimuth_range.clone()
+    }
+}
+
 impl AzimuthRange for FiringXyzS16 {
     fn azimuth_range(&self) -> Range<Angle> {
         self.azimuth_range.clone()
@@ -67,6 +100,30 @@ impl AzimuthRange for FiringXyzD32 {
     }
 }
 
+impl AzimuthRange for FiringXyzS64 {
+    fn azimuth_range(&self) -> Range<Angle> {
+        self.azimuth_range.clone()
+    }
+}
+
+impl AzimuthRange for FiringXyzS128 {
+    fn azimuth_range(&self) -> Range<Angle> {
+        self.azimuth_range.clone()
+    }
+}
+
+impl AzimuthRange for FiringXyzD64 {
+    fn azimuth_range(&self) -> Range<Angle> {
+        self.azimuth_range.clone()
+    }
+}
+
+impl AzimuthRange for FiringXyzD128 {
+    fn azimuth_range(&self) -> Range<Angle> {
+        self.azimuth_range.clone()
+    }
+}
+
 impl AzimuthRange for FrameXyzS16 {
     fn azimuth_range(&self) -> Range<Angle> {
         let start = self.firings[0].azimuth_range().start;
@@ -98,3 +155,35 @@ impl AzimuthRange for FrameXyzD32 {
         start..end
     }
 }
+
+impl AzimuthRange for FrameXyzS64 {
+    fn azimuth_range(&self) -> Range<Angle> {
+        let start = self.firings[0].azimuth_range().start;
+        let end = self.firings.last().unwrap().azimuth_range().end;
+        start..end
+    }
+}
+
+impl AzimuthRange for FrameXyzS128 {
+    fn azimuth_range(&self) -> Range<Angle> {
+        let start = self.firings[0].azimuth_range().start;
+        let end = self.firings.last().unwrap().azimuth_range().end;
+        start..end
+    }
+}
+
+impl AzimuthRange for FrameXyzD64 {
+    fn azimuth_range(&self) -> Range<Angle> {
+        let start = self.firings[0].azimuth_range().start;
+        let end = self.firings.last().unwrap().azimuth_range().end;
+        start..end
+    }
+}
+
+impl AzimuthRange for FrameXyzD128 {
+    fn azimuth_range(&self) -> Range<Angle> {
+        let start = self.firings[0].azimuth_range().start;
+        let end = self.firings.last().unwrap().azimuth_range().end;
+        start..end
+    }
+}
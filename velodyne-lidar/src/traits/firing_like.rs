@@ -12,23 +12,39 @@ pub trait FiringLike {
 
     fn point_at(&self, index: usize) -> Option<Self::Point<'_>>;
 
-    fn time_iter(&self) -> TimeIterator {
+    /// Period between two consecutive points in the firing.
+    fn channel_period(&self) -> Duration {
+        CHANNEL_PERIOD
+    }
+
+    /// Time of a point relative to the start of the firing.
+    fn channel_offset(&self, index: usize) -> Duration {
+        self.channel_period() * index as u32
+    }
+
+    fn time_iter(&self) -> TimeIterator<'_, Self> {
         TimeIterator {
+            firing: self,
             index: 0,
             len: self.num_points(),
-            value: self.start_toh(),
         }
     }
 }
 
 #[derive(Debug, Clone)]
-pub struct TimeIterator {
+pub struct TimeIterator<'a, F>
+where
+    F: ?Sized,
+{
+    firing: &'a F,
     index: usize,
     len: usize,
-    value: Duration,
 }
 
-impl Iterator for TimeIterator {
+impl<'a, F> Iterator for TimeIterator<'a, F>
+where
+    F: FiringLike + ?Sized,
+{
     type Item = Duration;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -36,9 +52,8 @@ impl Iterator for TimeIterator {
             return None;
         }
 
-        let value = self.value;
+        let value = self.firing.start_toh() + self.firing.channel_offset(self.index);
         self.index += 1;
-        self.value += CHANNEL_PERIOD;
         Some(value)
     }
 }
@@ -0,0 +1,112 @@
+use crate::types::{
+    firing_block::{
+        FiringBlockD128, FiringBlockD16, FiringBlockD32, FiringBlockD64, FiringBlockS128,
+        FiringBlockS16, FiringBlockS32, FiringBlockS64,
+    },
+    firing_xyz::{
+        FiringXyzD128, FiringXyzD16, FiringXyzD32, FiringXyzD64, FiringXyzS128, FiringXyzS16,
+        FiringXyzS32, FiringXyzS64,
+    },
+};
+use std::time::Duration;
+
+/// Provides the time of the hour when the element is measured.
+pub trait TimeOfHour {
+    fn toh(&self) -> Duration;
+}
+
+impl<'a> TimeOfHour for FiringBlockS16<'a> {
+    fn toh(&self) -> Duration {
+        self.toh
+    }
+}
+
+impl<'a> TimeOfHour for FiringBlockS32<'a> {
+    fn toh(&self) -> Duration {
+        self.toh
+    }
+}
+
+impl<'a> TimeOfHour for FiringBlockD16<'a> {
+    fn toh(&self) -> Duration {
+        self.toh
+    }
+}
+
+impl<'a> TimeOfHour for FiringBlockD32<'a> {
+    fn toh(&self) -> Duration {
+        self.toh
+    }
+}
+
+impl<'a> TimeOfHour for FiringBlockS64<'a> {
+    fn toh(&self) -> Duration {
+        self.toh
+    }
+}
+
+impl<'a> TimeOfHour for FiringBlockS128<'a> {
+    fn toh(&self) -> Duration {
+        self.toh
+    }
+}
+
+impl<'a> TimeOfHour for FiringBlockD64<'a> {
+    fn toh(&self) -> Duration {
+        self.toh
+    }
+}
+
+impl<'a> TimeOfHour for FiringBlockD128<'a> {
+    fn toh(&self) -> Duration {
+        self.toh
+    }
+}
+
+impl TimeOfHour for FiringXyzS16 {
+    fn toh(&self) -> Duration {
+        self.toh
+    }
+}
+
+impl TimeOfHour for FiringXyzS32 {
+    fn toh(&self) -> Duration {
+        self.toh
+    }
+}
+
+impl TimeOfHour for FiringXyzD16 {
+    fn toh(&self) -> Duration {
+        self.toh
+    }
+}
+
+impl TimeOfHour for FiringXyzD32 {
+    fn toh(&self) -> Duration {
+        self.toh
+    }
+}
+
+impl TimeOfHour for FiringXyzS64 {
+    fn toh(&self) -> Duration {
+        self.toh
+    }
+}
+
+impl TimeOfHour for FiringXyzS128 {
+    fn toh(&self) -> Duration {
+        self.toh
+    }
+}
+
+impl TimeOfHour for FiringXyzD64 {
+    fn toh(&self) -> Duration {
+        self.toh
+    }
+}
+
+impl TimeOfHour for FiringXyzD128 {
+    fn toh(&self) -> Duration {
+        self.toh
+    }
+}
@@ -0,0 +1,548 @@
+//! Table-driven conversion from channels to points.
+//!
+//! Converting a channel to Cartesian coordinates takes the sine and
+//! cosine of the elevation and the azimuth of the point. The
+//! elevation is fixed per laser, and the azimuth is measured in
+//! 0.01 degree ticks. [XyzConverter] precomputes both into tables
+//! and avoids evaluating trigonometric functions per point.
+//!
+//! Points are interpolated between azimuth ticks, so the table is
+//! evaluated in one of the [Precision] modes.
+//!
+//! - [Precision::Exact] interpolates linearly between adjacent ticks
+//!   in double precision. The error of the sine and cosine is below
+//!   4e-9, which is 0.4 µm at 100 m. The results are the same as
+//!   [data_packet_to_frame_xyz](crate::iter::data_packet_to_frame_xyz)
+//!   in practice.
+//! - [Precision::Approximate] takes the nearest tick from a single
+//!   precision table. The azimuth error is at most half a tick, 8.8e-5
+//!   rad, and together with the rounding to `f32` it moves a point by
+//!   at most 9 mm at 100 m. It pays off with
+//!   [firing_xyz_f32](XyzConverter::firing_xyz_f32), which converts a
+//!   whole firing in a loop free of branches and divisions.
+//!
+//! Measured with `cargo bench --bench convert` on the bundled VLP-32C
+//! capture. Frame building converts all 35k points of the capture into
+//! frames, and the `xyz_f32` rows convert the same points to single
+//! precision coordinates only.
+//!
+//! | benchmark                                 |    time | speedup |
+//! |-------------------------------------------|--------:|--------:|
+//! | `frame_builder/direct`                    | 2.50 ms |   1.00x |
+//! | `frame_builder/trig_table`                | 2.01 ms |   1.24x |
+//! | `xyz_f32/exact_per_point`                 |  717 µs |   1.00x |
+//! | `xyz_f32/approximate_per_point`           |  418 µs |   1.72x |
+//! | `xyz_f32/approximate_per_firing`          |  261 µs |   2.74x |
+//!
+//! [Measurement]s are always in double precision, so
+//! [FrameBuilder::with_trig_table](crate::FrameBuilder::with_trig_table)
+//! builds frames with the exact tables.
+
+use crate::{
+    config::{Beam, BeamCorrection, Config, ConfigKind},
+    consts::AZIMUTH_COUNT_PER_REV,
+    convert::{correct_intensity, spherical_to_xyz_calibrated_sin_cos},
+    packet::Channel,
+    types::{format::FormatKind, measurements::Measurement},
+};
+use measurements::{Angle, Length};
+use once_cell::sync::Lazy;
+use std::f64::consts::PI;
+
+/// Number of distinct ticks in one revolution.
+const TICKS_PER_REV: usize = AZIMUTH_COUNT_PER_REV - 1;
+
+const TICKS_PER_RADIAN: f32 = (TICKS_PER_REV as f64 / (PI * 2.0)) as f32;
+
+static AZIMUTH_TABLE: Lazy<AzimuthTable> = Lazy::new(AzimuthTable::new);
+
+/// The precision of the azimuth table lookup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Precision {
+    /// Interpolates linearly between azimuth ticks.
+    #[default]
+    Exact,
+    /// Takes the nearest azimuth tick in single precision.
+    Approximate,
+}
+
+/// Sines and cosines of azimuth ticks.
+#[derive(Debug)]
+struct AzimuthTable {
+    /// The last entry repeats the first one for interpolation.
+    sin_cos: Vec<(f64, f64)>,
+    /// The same table in single precision.
+    sin_cos_f32: Vec<[f32; 2]>,
+}
+
+impl AzimuthTable {
+    fn new() -> Self {
+        let sin_cos: Vec<_> = (0..=TICKS_PER_REV)
+            .map(|tick| {
+                let tick = tick % TICKS_PER_REV;
+                let radians = PI * 2.0 * tick as f64 / TICKS_PER_REV as f64;
+                radians.sin_cos()
+            })
+            .collect();
+        let sin_cos_f32 = sin_cos
+            .iter()
+            .map(|&(sin, cos)| [sin as f32, cos as f32])
+            .collect();
+        Self {
+            sin_cos,
+            sin_cos_f32,
+        }
+    }
+
+    /// Converts an angle in radians to fractional ticks in
+    /// `[0, TICKS_PER_REV)`.
+    fn ticks(radians: f64) -> f64 {
+        wrap_radians(radians) * (TICKS_PER_REV as f64 / (PI * 2.0))
+    }
+
+    fn interpolate(&self, radians: f64) -> (f64, f64) {
+        let ticks = Self::ticks(radians);
+        let index = (ticks as usize).min(TICKS_PER_REV - 1);
+        let frac = ticks - index as f64;
+
+        let (sin0, cos0) = self.sin_cos[index];
+        let (sin1, cos1) = self.sin_cos[index + 1];
+        (sin0 + (sin1 - sin0) * frac, cos0 + (cos1 - cos0) * frac)
+    }
+
+    /// Looks up the nearest tick of an angle in `[0, 2π)`.
+    ///
+    /// Angles out of the range are clamped to stay in bounds.
+    fn nearest_f32(&self, radians: f32) -> [f32; 2] {
+        let index = ((radians * TICKS_PER_RADIAN + 0.5) as usize).min(TICKS_PER_REV);
+        self.sin_cos_f32[index]
+    }
+}
+
+/// Wraps an angle in radians to `[0, 2π)`.
+///
+/// Angles in the range are returned as is, which is the common case
+/// for points within a revolution.
+pub(crate) fn wrap_radians(radians: f64) -> f64 {
+    if (0.0..PI * 2.0).contains(&radians) {
+        radians
+    } else {
+        radians.rem_euclid(PI * 2.0)
+    }
+}
+
+/// Precomputed values of a laser.
+#[derive(Debug, Clone)]
+struct LaserTrig {
+    elevation_sin: f64,
+    elevation_cos: f64,
+    vertical_offset: Length,
+    horizontal_offset: Length,
+    correction: Option<BeamCorrection>,
+}
+
+/// Single precision coefficients of an uncalibrated laser.
+///
+/// A point at raw distance `d` and azimuth `(sin, cos)` is
+/// - `plane = d * plane_scale + plane_offset`
+/// - `x = plane * sin - horizontal_offset * cos`
+/// - `y = plane * cos + horizontal_offset * sin`
+/// - `z = d * z_scale + z_offset`
+#[derive(Debug, Clone, Copy)]
+struct LaserCoefs {
+    plane_scale: f32,
+    plane_offset: f32,
+    z_scale: f32,
+    z_offset: f32,
+    horizontal_offset: f32,
+}
+
+impl LaserCoefs {
+    fn xyz(&self, distance: u16, azimuth_sin: f32, azimuth_cos: f32) -> [f32; 3] {
+        let distance = distance as f32;
+        let plane = distance * self.plane_scale + self.plane_offset;
+        [
+            plane * azimuth_sin - self.horizontal_offset * azimuth_cos,
+            plane * azimuth_cos + self.horizontal_offset * azimuth_sin,
+            distance * self.z_scale + self.z_offset,
+        ]
+    }
+}
+
+/// Converts channels to [Measurement]s with precomputed trigonometric
+/// tables.
+///
+/// The azimuth table is shared by all converters, while the elevation
+/// values are computed per [Config].
+#[derive(Debug, Clone)]
+pub struct XyzConverter {
+    lasers: Vec<LaserTrig>,
+    coefs: Vec<LaserCoefs>,
+    /// Whether any laser has a correction.
+    calibrated: bool,
+    distance_resolution: Length,
+    precision: Precision,
+}
+
+impl XyzConverter {
+    pub fn new(config: &Config) -> Self {
+        Self::from_lasers(&config.lasers, config.distance_resolution)
+    }
+
+    pub(crate) fn from_config_kind(config: &ConfigKind) -> Self {
+        use FormatKind as K;
+
+        match config {
+            K::Single16(c) | K::Dual16(c) => Self::from_lasers(&c.lasers, c.distance_resolution),
+            K::Single32(c) | K::Dual32(c) => Self::from_lasers(&c.lasers, c.distance_resolution),
+            K::Single64(c) | K::Dual64(c) => Self::from_lasers(&c.lasers, c.distance_resolution),
+            K::Single128(c) | K::Dual128(c) => Self::from_lasers(&c.lasers, c.distance_resolution),
+        }
+    }
+
+    fn from_lasers(lasers: &[Beam], distance_resolution: Length) -> Self {
+        let lasers: Vec<_> = lasers
+            .iter()
+            .map(|beam| {
+                let (elevation_sin, elevation_cos) = beam.elevation.as_radians().sin_cos();
+                LaserTrig {
+                    elevation_sin,
+                    elevation_cos,
+                    vertical_offset: beam.vertical_offset,
+                    horizontal_offset: beam.horizontal_offset,
+                    correction: beam.correction.clone(),
+                }
+            })
+            .collect();
+
+        let resolution = distance_resolution.as_meters();
+        let coefs = lasers
+            .iter()
+            .map(|laser| {
+                let vertical_offset = laser.vertical_offset.as_meters();
+                LaserCoefs {
+                    plane_scale: (resolution * laser.elevation_cos) as f32,
+                    plane_offset: (-vertical_offset * laser.elevation_sin) as f32,
+                    z_scale: (resolution * laser.elevation_sin) as f32,
+                    z_offset: (vertical_offset * laser.elevation_cos) as f32,
+                    horizontal_offset: laser.horizontal_offset.as_meters() as f32,
+                }
+            })
+            .collect();
+        let calibrated = lasers.iter().any(|laser| laser.correction.is_some());
+
+        Self {
+            lasers,
+            coefs,
+            calibrated,
+            distance_resolution,
+            precision: Precision::default(),
+        }
+    }
+
+    pub fn with_precision(mut self, precision: Precision) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    pub fn precision(&self) -> Precision {
+        self.precision
+    }
+
+    pub fn num_lasers(&self) -> usize {
+        self.lasers.len()
+    }
+
+    /// Looks up the sine and cosine of an azimuth angle.
+    ///
+    /// It interpolates between ticks regardless of the precision.
+    pub fn azimuth_sin_cos(&self, azimuth: Angle) -> (f64, f64) {
+        AZIMUTH_TABLE.interpolate(azimuth.as_radians())
+    }
+
+    /// Gets the intensity of a channel of a laser with the intensity
+    /// correction of calibrated lasers applied.
+    ///
+    /// It panics if `laser_id` is out of bounds.
+    pub fn intensity(&self, laser_id: usize, channel: &Channel) -> u8 {
+        match &self.lasers[laser_id].correction {
+            Some(correction) if channel.distance != 0 => {
+                correct_intensity(channel.intensity, channel.distance, correction)
+            }
+            _ => channel.intensity,
+        }
+    }
+
+    /// Converts a channel of a laser to single precision coordinates
+    /// in meters and the intensity.
+    ///
+    /// The `azimuth` is in radians and must include the azimuth offset
+    /// of the laser. It panics if `laser_id` is out of bounds.
+    pub fn xyz_f32(&self, laser_id: usize, channel: &Channel, azimuth: f64) -> ([f32; 3], u8) {
+        let laser = &self.lasers[laser_id];
+
+        if laser.correction.is_some() {
+            let Measurement { intensity, xyz, .. } =
+                self.measurement(laser_id, channel, Angle::from_radians(azimuth));
+            let [x, y, z] = xyz.map(|value| value.as_meters() as f32);
+            return ([x, y, z], intensity);
+        }
+
+        if self.precision == Precision::Approximate {
+            let [azimuth_sin, azimuth_cos] =
+                AZIMUTH_TABLE.nearest_f32(wrap_radians(azimuth) as f32);
+            let xyz = self.coefs[laser_id].xyz(channel.distance, azimuth_sin, azimuth_cos);
+            return (xyz, channel.intensity);
+        }
+
+        let (azimuth_sin, azimuth_cos) = AZIMUTH_TABLE.interpolate(azimuth);
+        let distance = self.distance_resolution.as_meters() * channel.distance as f64;
+        let vertical_offset = laser.vertical_offset.as_meters();
+        let horizontal_offset = laser.horizontal_offset.as_meters();
+
+        let distance_plane = distance * laser.elevation_cos - vertical_offset * laser.elevation_sin;
+        let x = distance_plane * azimuth_sin - horizontal_offset * azimuth_cos;
+        let y = distance_plane * azimuth_cos + horizontal_offset * azimuth_sin;
+        let z = distance * laser.elevation_sin + vertical_offset * laser.elevation_cos;
+        ([x as f32, y as f32, z as f32], channel.intensity)
+    }
+
+    /// Converts the channels of a firing to single precision
+    /// coordinates in meters.
+    ///
+    /// The `i`-th channel belongs to laser `i` at `azimuths[i]` in
+    /// radians, which must include the azimuth offset of the laser and
+    /// lie in `[0, 2π)`. The coordinates are written to the slices of
+    /// `[x, y, z]`. Use [intensity](Self::intensity) for the corrected
+    /// intensities.
+    ///
+    /// With [Precision::Approximate] and no calibrated laser, it runs
+    /// the single precision lookup over the whole firing. Otherwise it
+    /// converts one point at a time like [xyz_f32](Self::xyz_f32).
+    ///
+    /// It panics if the slices have different lengths or there are
+    /// more channels than lasers.
+    pub fn firing_xyz_f32(&self, azimuths: &[f32], channels: &[Channel], xyz: [&mut [f32]; 3]) {
+        let len = channels.len();
+        let [xs, ys, zs] = xyz;
+        assert!(len <= self.lasers.len(), "more channels than lasers");
+        assert!(
+            azimuths.len() == len && xs.len() == len && ys.len() == len && zs.len() == len,
+            "the slices have different lengths"
+        );
+
+        if self.precision == Precision::Exact || self.calibrated {
+            for (laser_id, channel) in channels.iter().enumerate() {
+                let ([x, y, z], _) = self.xyz_f32(laser_id, channel, azimuths[laser_id] as f64);
+                xs[laser_id] = x;
+                ys[laser_id] = y;
+                zs[laser_id] = z;
+            }
+            return;
+        }
+
+        let table = &*AZIMUTH_TABLE;
+        let points = channels.iter().zip(azimuths).zip(&self.coefs);
+        let outputs = xs.iter_mut().zip(ys.iter_mut()).zip(zs.iter_mut());
+
+        for (((channel, &azimuth), coefs), ((x, y), z)) in points.zip(outputs) {
+            let [azimuth_sin, azimuth_cos] = table.nearest_f32(azimuth);
+            [*x, *y, *z] = coefs.xyz(channel.distance, azimuth_sin, azimuth_cos);
+        }
+    }
+
+    /// Converts a channel of a laser at an azimuth angle.
+    ///
+    /// The azimuth must include the azimuth offset of the laser. It
+    /// panics if `laser_id` is out of bounds.
+    pub fn measurement(&self, laser_id: usize, channel: &Channel, azimuth: Angle) -> Measurement {
+        let LaserTrig {
+            elevation_sin,
+            elevation_cos,
+            vertical_offset,
+            horizontal_offset,
+            ref correction,
+        } = self.lasers[laser_id];
+        let Channel {
+            distance: raw_distance,
+            intensity,
+        } = *channel;
+        let (azimuth_sin, azimuth_cos) = self.azimuth_sin_cos(azimuth);
+
+        match correction {
+            Some(correction) if raw_distance != 0 => {
+                let distance = self.distance_resolution * raw_distance as f64 + correction.distance;
+                let xyz = spherical_to_xyz_calibrated_sin_cos(
+                    distance,
+                    (elevation_sin, elevation_cos),
+                    (azimuth_sin, azimuth_cos),
+                    vertical_offset,
+                    horizontal_offset,
+                    correction,
+                );
+
+                Measurement {
+                    distance,
+                    intensity: correct_intensity(intensity, raw_distance, correction),
+                    xyz,
+                }
+            }
+            _ => {
+                let distance = self.distance_resolution * raw_distance as f64;
+                let distance_plane = distance * elevation_cos - vertical_offset * elevation_sin;
+                let x = distance_plane * azimuth_sin - horizontal_offset * azimuth_cos;
+                let y = distance_plane * azimuth_cos + horizontal_offset * azimuth_sin;
+                let z = distance * elevation_sin + vertical_offset * elevation_cos;
+
+                Measurement {
+                    distance,
+                    intensity,
+                    xyz: [x, y, z],
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{capture::PacketReader, convert::channel_to_measurement, FrameBuilder, Packet};
+
+    fn channels() -> [Channel; 4] {
+        [1, 500, 10_000, 50_000].map(|distance| Channel {
+            distance,
+            intensity: 7,
+        })
+    }
+
+    /// Azimuths in radians over a revolution, away from the ticks.
+    fn azimuths() -> impl Iterator<Item = f64> {
+        (0..5_000).map(|step| (step as f64 * 0.0737).to_radians())
+    }
+
+    fn expected(config: &Config, laser_id: usize, channel: &Channel, azimuth: f64) -> Measurement {
+        let laser = &config.lasers[laser_id];
+        channel_to_measurement(
+            channel,
+            laser,
+            Angle::from_radians(azimuth),
+            config.distance_resolution,
+        )
+    }
+
+    fn max_error(config: &Config) -> f64 {
+        let converter = XyzConverter::new(config);
+        let mut max_error: f64 = 0.0;
+
+        for channel in &channels() {
+            for azimuth in azimuths() {
+                for laser_id in 0..config.lasers.len() {
+                    let expect = expected(config, laser_id, channel, azimuth);
+                    let actual =
+                        converter.measurement(laser_id, channel, Angle::from_radians(azimuth));
+                    assert_eq!(actual.distance, expect.distance);
+                    assert_eq!(actual.intensity, expect.intensity);
+                    assert_eq!(converter.intensity(laser_id, channel), expect.intensity);
+
+                    for (lhs, rhs) in actual.xyz.iter().zip(&expect.xyz) {
+                        max_error = max_error.max((lhs.as_meters() - rhs.as_meters()).abs());
+                    }
+                }
+            }
+        }
+
+        max_error
+    }
+
+    fn max_error_f32(precision: Precision, config: &Config) -> f64 {
+        let converter = XyzConverter::new(config).with_precision(precision);
+        let num_lasers = config.lasers.len();
+        let mut max_error: f64 = 0.0;
+        let mut xyz = [(); 3].map(|_| vec![0.0; num_lasers]);
+
+        for channel in &channels() {
+            let channels = vec![*channel; num_lasers];
+
+            for azimuth in azimuths().map(wrap_radians) {
+                let azimuths = vec![azimuth as f32; num_lasers];
+                let [xs, ys, zs] = &mut xyz;
+                converter.firing_xyz_f32(&azimuths, &channels, [xs, ys, zs]);
+
+                let [xs, ys, zs] = &xyz;
+                let points = xs.iter().zip(ys).zip(zs).map(|((&x, &y), &z)| [x, y, z]);
+
+                for (laser_id, actual) in points.enumerate() {
+                    let azimuth = azimuth as f32 as f64;
+                    let expect = expected(config, laser_id, channel, azimuth);
+                    let (point, _) = converter.xyz_f32(laser_id, channel, azimuth);
+                    assert_eq!(actual, point);
+
+                    for (lhs, rhs) in actual.iter().zip(&expect.xyz) {
+                        let error = (*lhs as f64 - rhs.as_meters()).abs();
+                        max_error = max_error.max(error);
+                    }
+                }
+            }
+        }
+
+        max_error
+    }
+
+    #[test]
+    fn error_bounds() {
+        let config = Config::new_vlp_32c_strongest();
+        // The longest distance is 50000 * 4 mm = 200 m.
+        assert!(max_error(&config) < 1e-6);
+        // f32 has a relative precision of 6e-8, 12 µm at 200 m.
+        assert!(max_error_f32(Precision::Exact, &config) < 2e-5);
+        assert!(max_error_f32(Precision::Approximate, &config) < 200.0 * 9e-5);
+    }
+
+    #[test]
+    fn frame_builder_with_trig_table() {
+        let config = Config::new_vlp_32c_strongest();
+        let packets: Vec<_> = PacketReader::open("tests/velodyne_vlp32.pcap")
+            .unwrap()
+            .packets()
+            .filter_map(|packet| match packet.unwrap().packet {
+                Packet::Data(packet) => Some(packet),
+                Packet::Position(_) => None,
+            })
+            .collect();
+
+        let build = |mut builder: FrameBuilder| -> Vec<_> {
+            packets
+                .iter()
+                .flat_map(|packet| builder.push_packet(packet))
+                .flat_map(|frame| frame.into_point_iter())
+                .map(|point| point.try_into_single().unwrap())
+                .collect()
+        };
+        let direct = build(FrameBuilder::new(config.clone()).unwrap());
+        let table = build(FrameBuilder::new(config).unwrap().with_trig_table());
+
+        assert!(!direct.is_empty());
+        assert_eq!(direct.len(), table.len());
+        for (lhs, rhs) in direct.iter().zip(&table) {
+            assert_eq!(lhs.laser_id, rhs.laser_id);
+            assert_eq!(lhs.azimuth, rhs.azimuth);
+            for (lhs, rhs) in lhs.measurement.xyz.iter().zip(&rhs.measurement.xyz) {
+                assert!((lhs.as_meters() - rhs.as_meters()).abs() < 1e-6);
+            }
+        }
+    }
+
+    #[cfg(feature = "velodyne-params")]
+    #[test]
+    fn calibrated_error_bounds() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../velodyne-params/params/64e_s3-xiesc.yaml"
+        );
+        let config = Config::from_params_path(path, crate::packet::ReturnMode::Strongest).unwrap();
+        assert!(max_error(&config) < 1e-6);
+        // Calibrated lasers fall back to the exact conversion.
+        assert!(max_error_f32(Precision::Approximate, &config) < 1e-4);
+    }
+}
@@ -1,23 +1,34 @@
 //! Firings of block references.
 
 use crate::{
+    consts,
     convert::{
-        firing_block_to_xyz_d16, firing_block_to_xyz_d32, firing_block_to_xyz_s16,
-        firing_block_to_xyz_s32,
+        channel_offset_128, firing_block_to_xyz_d128, firing_block_to_xyz_d128_with,
+        firing_block_to_xyz_d16, firing_block_to_xyz_d16_with, firing_block_to_xyz_d32,
+        firing_block_to_xyz_d32_with, firing_block_to_xyz_d64, firing_block_to_xyz_d64_with,
+        firing_block_to_xyz_s128, firing_block_to_xyz_s128_with, firing_block_to_xyz_s16,
+        firing_block_to_xyz_s16_with, firing_block_to_xyz_s32, firing_block_to_xyz_s32_with,
+        firing_block_to_xyz_s64, firing_block_to_xyz_s64_with,
     },
-    packet::{Block, Channel},
+    packet::{Block, Channel, ProductID},
     traits::FiringLike,
     types::{
         channel::ChannelRefD,
-        firing_raw::{FiringRawD16, FiringRawD32, FiringRawS16, FiringRawS32},
-        firing_xyz::{FiringXyz, FiringXyzD16, FiringXyzD32, FiringXyzS16, FiringXyzS32},
+        firing_raw::{
+            FiringRawD128, FiringRawD16, FiringRawD32, FiringRawD64, FiringRawS128, FiringRawS16,
+            FiringRawS32, FiringRawS64,
+        },
+        firing_xyz::{
+            FiringXyz, FiringXyzD128, FiringXyzD16, FiringXyzD32, FiringXyzD64, FiringXyzS128,
+            FiringXyzS16, FiringXyzS32, FiringXyzS64,
+        },
         format::FormatKind,
+        measurements::Measurement,
     },
-    Config, Config16, Config32,
+    Config, Config128, Config16, Config32, Config64, Error,
 };
-use anyhow::{anyhow, Result};
 use measurements::Angle;
-use std::{ops::Range, time::Duration};
+use std::{array, ops::Range, time::Duration};
 
 use super::channel_array::{ChannelArrayD, ChannelArrayDRef, ChannelArraySRef};
 
@@ -41,10 +52,22 @@ impl<'a> FiringBlockS16<'a> {
     pub fn to_firing_xyz(&self, beams: &Config16) -> FiringXyzS16 {
         firing_block_to_xyz_s16(self, beams)
     }
+
+    /// Converts to a firing with a custom channel conversion, which
+    /// takes the laser ID, the channel and the azimuth.
+    pub fn to_firing_xyz_with<M>(&self, beams: &Config16, measure: M) -> FiringXyzS16
+    where
+        M: Fn(usize, &Channel, Angle) -> Measurement,
+    {
+        firing_block_to_xyz_s16_with(self, beams, measure)
+    }
 }
 
 impl<'a> FiringLike for FiringBlockS16<'a> {
-    type Point<'p> = &'p Channel where Self: 'p;
+    type Point<'p>
+        = &'p Channel
+    where
+        Self: 'p;
 
     fn start_toh(&self) -> Duration {
         self.toh
@@ -63,6 +86,8 @@ impl<'a> FiringLike for FiringBlockS16<'a> {
 pub struct FiringBlockS32<'a> {
     pub toh: Duration,
     pub azimuth_range: Range<Angle>,
+    /// The sensor model, which determines the timing of channels.
+    pub product_id: ProductID,
     pub block: &'a Block,
     pub channels: ChannelArraySRef<'a, 32>,
 }
@@ -79,15 +104,31 @@ impl<'a> FiringBlockS32<'a> {
     pub fn to_firing_xyz(&self, beams: &Config32) -> FiringXyzS32 {
         firing_block_to_xyz_s32(self, beams)
     }
+
+    /// Converts to a firing with a custom channel conversion, which
+    /// takes the laser ID, the channel and the azimuth.
+    pub fn to_firing_xyz_with<M>(&self, beams: &Config32, measure: M) -> FiringXyzS32
+    where
+        M: Fn(usize, &Channel, Angle) -> Measurement,
+    {
+        firing_block_to_xyz_s32_with(self, beams, measure)
+    }
 }
 
 impl<'a> FiringLike for FiringBlockS32<'a> {
-    type Point<'p> = &'p Channel where Self: 'p;
+    type Point<'p>
+        = &'p Channel
+    where
+        Self: 'p;
 
     fn start_toh(&self) -> Duration {
         self.toh
     }
 
+    fn channel_period(&self) -> Duration {
+        self.product_id.channel_period()
+    }
+
     fn num_points(&self) -> usize {
         self.channels.len()
     }
@@ -122,6 +163,15 @@ impl<'a> FiringBlockD16<'a> {
         firing_block_to_xyz_d16(self, beams)
     }
 
+    /// Converts to a firing with a custom channel conversion, which
+    /// takes the laser ID, the channel and the azimuth.
+    pub fn to_firing_xyz_with<M>(&self, beams: &Config16, measure: M) -> FiringXyzD16
+    where
+        M: Fn(usize, &Channel, Angle) -> Measurement,
+    {
+        firing_block_to_xyz_d16_with(self, beams, measure)
+    }
+
     pub fn strongest_part(&self) -> FiringBlockS16<'a> {
         let Self {
             toh,
@@ -162,7 +212,10 @@ impl<'a> FiringBlockD16<'a> {
 }
 
 impl<'a> FiringLike for FiringBlockD16<'a> {
-    type Point<'p> = ChannelRefD<'p> where Self: 'p;
+    type Point<'p>
+        = ChannelRefD<'p>
+    where
+        Self: 'p;
 
     fn start_toh(&self) -> Duration {
         self.toh
@@ -183,6 +236,8 @@ impl<'a> FiringLike for FiringBlockD16<'a> {
 pub struct FiringBlockD32<'a> {
     pub toh: Duration,
     pub azimuth_range: Range<Angle>,
+    /// The sensor model, which determines the timing of channels.
+    pub product_id: ProductID,
     pub block_strongest: &'a Block,
     pub block_last: &'a Block,
     pub channels: ChannelArrayDRef<'a, 32>,
@@ -204,10 +259,20 @@ impl<'a> FiringBlockD32<'a> {
         firing_block_to_xyz_d32(self, beams)
     }
 
+    /// Converts to a firing with a custom channel conversion, which
+    /// takes the laser ID, the channel and the azimuth.
+    pub fn to_firing_xyz_with<M>(&self, beams: &Config32, measure: M) -> FiringXyzD32
+    where
+        M: Fn(usize, &Channel, Angle) -> Measurement,
+    {
+        firing_block_to_xyz_d32_with(self, beams, measure)
+    }
+
     pub fn strongest_part(&self) -> FiringBlockS32<'a> {
         let Self {
             toh,
             ref azimuth_range,
+            product_id,
             block_strongest: block,
             channels:
                 ChannelArrayDRef {
@@ -220,6 +285,7 @@ impl<'a> FiringBlockD32<'a> {
         FiringBlockS32 {
             toh,
             azimuth_range: azimuth_range.clone(),
+            product_id,
             block,
             channels,
         }
@@ -229,6 +295,7 @@ impl<'a> FiringBlockD32<'a> {
         let Self {
             toh,
             ref azimuth_range,
+            product_id,
             block_last: block,
             channels: ChannelArrayDRef { last: channels, .. },
             ..
@@ -237,6 +304,7 @@ impl<'a> FiringBlockD32<'a> {
         FiringBlockS32 {
             toh,
             azimuth_range: azimuth_range.clone(),
+            product_id,
             block,
             channels,
         }
@@ -244,12 +312,19 @@ impl<'a> FiringBlockD32<'a> {
 }
 
 impl<'a> FiringLike for FiringBlockD32<'a> {
-    type Point<'p> = ChannelRefD<'p> where Self: 'p;
+    type Point<'p>
+        = ChannelRefD<'p>
+    where
+        Self: 'p;
 
     fn start_toh(&self) -> Duration {
         self.toh
     }
 
+    fn channel_period(&self) -> Duration {
+        self.product_id.channel_period()
+    }
+
     fn num_points(&self) -> usize {
         self.channels.strongest.len()
     }
@@ -261,12 +336,315 @@ impl<'a> FiringLike for FiringBlockD32<'a> {
     }
 }
 
-pub type FiringBlock<'a> =
-    FormatKind<FiringBlockS16<'a>, FiringBlockS32<'a>, FiringBlockD16<'a>, FiringBlockD32<'a>>;
+/// An HDL-64E firing spread over an upper block and a lower block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FiringBlockS64<'a> {
+    pub toh: Duration,
+    pub azimuth_range: Range<Angle>,
+    pub blocks: [&'a Block; 2],
+}
+
+impl<'a> FiringBlockS64<'a> {
+    /// Gets the channel of a laser.
+    pub fn channel(&self, laser_id: usize) -> Option<&'a Channel> {
+        self.blocks.get(laser_id / 32)?.channels.get(laser_id % 32)
+    }
+
+    pub fn channel_iter(&self) -> impl Iterator<Item = &'a Channel> + Clone + Sync + Send {
+        self.blocks.into_iter().flat_map(|block| &block.channels)
+    }
+
+    pub fn to_firing_raw(&self) -> FiringRawS64 {
+        FiringRawS64 {
+            toh: self.toh,
+            azimuth_range: self.azimuth_range.clone(),
+            channels: array::from_fn(|index| *self.channel(index).unwrap()),
+        }
+    }
+
+    pub fn to_firing_xyz(&self, beams: &Config64) -> FiringXyzS64 {
+        firing_block_to_xyz_s64(self, beams)
+    }
+
+    /// Converts to a firing with a custom channel conversion, which
+    /// takes the laser ID, the channel and the azimuth.
+    pub fn to_firing_xyz_with<M>(&self, beams: &Config64, measure: M) -> FiringXyzS64
+    where
+        M: Fn(usize, &Channel, Angle) -> Measurement,
+    {
+        firing_block_to_xyz_s64_with(self, beams, measure)
+    }
+}
+
+impl<'a> FiringLike for FiringBlockS64<'a> {
+    type Point<'p>
+        = &'p Channel
+    where
+        Self: 'p;
+
+    fn start_toh(&self) -> Duration {
+        self.toh
+    }
+
+    fn num_points(&self) -> usize {
+        64
+    }
+
+    fn point_at(&self, index: usize) -> Option<Self::Point<'_>> {
+        self.channel(index)
+    }
+}
+
+/// An HDL-64E firing in dual return mode spread over 4 blocks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FiringBlockD64<'a> {
+    pub toh: Duration,
+    pub azimuth_range: Range<Angle>,
+    pub blocks_strongest: [&'a Block; 2],
+    pub blocks_last: [&'a Block; 2],
+}
+
+impl<'a> FiringBlockD64<'a> {
+    pub fn to_firing_raw(&self) -> FiringRawD64 {
+        let strongest = self.strongest_part();
+        let last = self.last_part();
+
+        FiringRawD64 {
+            toh: self.toh,
+            azimuth_range: self.azimuth_range.clone(),
+            channels: ChannelArrayD {
+                strongest: array::from_fn(|index| *strongest.channel(index).unwrap()),
+                last: array::from_fn(|index| *last.channel(index).unwrap()),
+            },
+        }
+    }
+
+    pub fn to_firing_xyz(&self, beams: &Config64) -> FiringXyzD64 {
+        firing_block_to_xyz_d64(self, beams)
+    }
+
+    /// Converts to a firing with a custom channel conversion, which
+    /// takes the laser ID, the channel and the azimuth.
+    pub fn to_firing_xyz_with<M>(&self, beams: &Config64, measure: M) -> FiringXyzD64
+    where
+        M: Fn(usize, &Channel, Angle) -> Measurement,
+    {
+        firing_block_to_xyz_d64_with(self, beams, measure)
+    }
+
+    pub fn strongest_part(&self) -> FiringBlockS64<'a> {
+        FiringBlockS64 {
+            toh: self.toh,
+            azimuth_range: self.azimuth_range.clone(),
+            blocks: self.blocks_strongest,
+        }
+    }
+
+    pub fn last_part(&self) -> FiringBlockS64<'a> {
+        FiringBlockS64 {
+            toh: self.toh,
+            azimuth_range: self.azimuth_range.clone(),
+            blocks: self.blocks_last,
+        }
+    }
+}
+
+impl<'a> FiringLike for FiringBlockD64<'a> {
+    type Point<'p>
+        = ChannelRefD<'p>
+    where
+        Self: 'p;
+
+    fn start_toh(&self) -> Duration {
+        self.toh
+    }
+
+    fn num_points(&self) -> usize {
+        64
+    }
+
+    fn point_at(&self, index: usize) -> Option<Self::Point<'_>> {
+        let strongest = self
+            .blocks_strongest
+            .get(index / 32)?
+            .channels
+            .get(index % 32)?;
+        let last = self.blocks_last.get(index / 32)?.channels.get(index % 32)?;
+        Some(ChannelRefD { strongest, last })
+    }
+}
+
+/// A VLS-128 firing spread over 4 blocks, one for each laser bank.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FiringBlockS128<'a> {
+    pub toh: Duration,
+    pub azimuth_range: Range<Angle>,
+    pub blocks: [&'a Block; 4],
+}
+
+impl<'a> FiringBlockS128<'a> {
+    /// Gets the channel of a laser.
+    pub fn channel(&self, laser_id: usize) -> Option<&'a Channel> {
+        self.blocks.get(laser_id / 32)?.channels.get(laser_id % 32)
+    }
+
+    pub fn channel_iter(&self) -> impl Iterator<Item = &'a Channel> + Clone + Sync + Send {
+        self.blocks.into_iter().flat_map(|block| &block.channels)
+    }
+
+    pub fn to_firing_raw(&self) -> FiringRawS128 {
+        FiringRawS128 {
+            toh: self.toh,
+            azimuth_range: self.azimuth_range.clone(),
+            channels: array::from_fn(|index| *self.channel(index).unwrap()),
+        }
+    }
+
+    pub fn to_firing_xyz(&self, beams: &Config128) -> FiringXyzS128 {
+        firing_block_to_xyz_s128(self, beams)
+    }
+
+    /// Converts to a firing with a custom channel conversion, which
+    /// takes the laser ID, the channel and the azimuth.
+    pub fn to_firing_xyz_with<M>(&self, beams: &Config128, measure: M) -> FiringXyzS128
+    where
+        M: Fn(usize, &Channel, Angle) -> Measurement,
+    {
+        firing_block_to_xyz_s128_with(self, beams, measure)
+    }
+}
+
+impl<'a> FiringLike for FiringBlockS128<'a> {
+    type Point<'p>
+        = &'p Channel
+    where
+        Self: 'p;
+
+    fn start_toh(&self) -> Duration {
+        self.toh
+    }
+
+    fn channel_period(&self) -> Duration {
+        consts::vls_128::CHANNEL_PERIOD
+    }
+
+    fn channel_offset(&self, index: usize) -> Duration {
+        channel_offset_128(index)
+    }
+
+    fn num_points(&self) -> usize {
+        128
+    }
+
+    fn point_at(&self, index: usize) -> Option<Self::Point<'_>> {
+        self.channel(index)
+    }
+}
+
+/// A VLS-128 firing in dual return mode spread over 8 blocks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FiringBlockD128<'a> {
+    pub toh: Duration,
+    pub azimuth_range: Range<Angle>,
+    pub blocks_strongest: [&'a Block; 4],
+    pub blocks_last: [&'a Block; 4],
+}
+
+impl<'a> FiringBlockD128<'a> {
+    pub fn to_firing_raw(&self) -> FiringRawD128 {
+        let strongest = self.strongest_part();
+        let last = self.last_part();
+
+        FiringRawD128 {
+            toh: self.toh,
+            azimuth_range: self.azimuth_range.clone(),
+            channels: ChannelArrayD {
+                strongest: array::from_fn(|index| *strongest.channel(index).unwrap()),
+                last: array::from_fn(|index| *last.channel(index).unwrap()),
+            },
+        }
+    }
+
+    pub fn to_firing_xyz(&self, beams: &Config128) -> FiringXyzD128 {
+        firing_block_to_xyz_d128(self, beams)
+    }
+
+    /// Converts to a firing with a custom channel conversion, which
+    /// takes the laser ID, the channel and the azimuth.
+    pub fn to_firing_xyz_with<M>(&self, beams: &Config128, measure: M) -> FiringXyzD128
+    where
+        M: Fn(usize, &Channel, Angle) -> Measurement,
+    {
+        firing_block_to_xyz_d128_with(self, beams, measure)
+    }
+
+    pub fn strongest_part(&self) -> FiringBlockS128<'a> {
+        FiringBlockS128 {
+            toh: self.toh,
+            azimuth_range: self.azimuth_range.clone(),
+            blocks: self.blocks_strongest,
+        }
+    }
+
+    pub fn last_part(&self) -> FiringBlockS128<'a> {
+        FiringBlockS128 {
+            toh: self.toh,
+            azimuth_range: self.azimuth_range.clone(),
+            blocks: self.blocks_last,
+        }
+    }
+}
+
+impl<'a> FiringLike for FiringBlockD128<'a> {
+    type Point<'p>
+        = ChannelRefD<'p>
+    where
+        Self: 'p;
+
+    fn start_toh(&self) -> Duration {
+        self.toh
+    }
+
+    fn channel_period(&self) -> Duration {
+        consts::vls_128::CHANNEL_PERIOD
+    }
+
+    fn channel_offset(&self, index: usize) -> Duration {
+        channel_offset_128(index)
+    }
+
+    fn num_points(&self) -> usize {
+        128
+    }
+
+    fn point_at(&self, index: usize) -> Option<Self::Point<'_>> {
+        let strongest = self
+            .blocks_strongest
+            .get(index / 32)?
+            .channels
+            .get(index % 32)?;
+        let last = self.blocks_last.get(index / 32)?.channels.get(index % 32)?;
+        Some(ChannelRefD { strongest, last })
+    }
+}
+
+pub type FiringBlock<'a> = FormatKind<
+    FiringBlockS16<'a>,
+    FiringBlockS32<'a>,
+    FiringBlockS64<'a>,
+    FiringBlockS128<'a>,
+    FiringBlockD16<'a>,
+    FiringBlockD32<'a>,
+    FiringBlockD64<'a>,
+    FiringBlockD128<'a>,
+>;
 
 impl<'a> FiringBlock<'a> {
-    pub fn to_firing_xyz(&self, beams: &Config) -> Result<FiringXyz> {
-        let err = || anyhow!("TODO");
+    pub fn to_firing_xyz(&self, beams: &Config) -> Result<FiringXyz, Error> {
+        let err = || Error::ConfigMismatch {
+            expect: self.format(),
+            num_lasers: beams.lasers.len(),
+        };
 
         use FormatKind as F;
 
@@ -287,12 +665,40 @@ impl<'a> FiringBlock<'a> {
                 let beams: Config32 = beams.clone().try_into().map_err(|_| err())?;
                 inner.to_firing_xyz(&beams).into()
             }
+            F::Single64(inner) => {
+                let beams: Config64 = beams.clone().try_into().map_err(|_| err())?;
+                inner.to_firing_xyz(&beams).into()
+            }
+            F::Single128(inner) => {
+                let beams: Config128 = beams.clone().try_into().map_err(|_| err())?;
+                inner.to_firing_xyz(&beams).into()
+            }
+            F::Dual64(inner) => {
+                let beams: Config64 = beams.clone().try_into().map_err(|_| err())?;
+                inner.to_firing_xyz(&beams).into()
+            }
+            F::Dual128(inner) => {
+                let beams: Config128 = beams.clone().try_into().map_err(|_| err())?;
+                inner.to_firing_xyz(&beams).into()
+            }
         };
 
         Ok(output)
     }
 }
 
+impl<'a> From<FiringBlockD64<'a>> for FiringBlock<'a> {
+    fn from(v: FiringBlockD64<'a>) -> Self {
+        Self::Dual64(v)
+    }
+}
+
+impl<'a> From<FiringBlockD128<'a>> for FiringBlock<'a> {
+    fn from(v: FiringBlockD128<'a>) -> Self {
+        Self::Dual128(v)
+    }
+}
+
 impl<'a> From<FiringBlockD32<'a>> for FiringBlock<'a> {
     fn from(v: FiringBlockD32<'a>) -> Self {
         Self::Dual32(v)
@@ -316,3 +722,15 @@ impl<'a> From<FiringBlockS16<'a>> for FiringBlock<'a> {
         Self::Single16(v)
     }
 }
+
+impl<'a> From<FiringBlockS64<'a>> for FiringBlock<'a> {
+    fn from(v: FiringBlockS64<'a>) -> Self {
+        Self::Single64(v)
+    }
+}
+
+impl<'a> From<FiringBlockS128<'a>> for FiringBlock<'a> {
+    fn from(v: FiringBlockS128<'a>) -> Self {
+        Self::Single128(v)
+    }
+}
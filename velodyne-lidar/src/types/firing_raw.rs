@@ -18,6 +18,20 @@ pub struct FiringRawS32 {
     pub channels: ChannelArrayS<32>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FiringRawS64 {
+    pub toh: Duration,
+    pub azimuth_range: Range<Angle>,
+    pub channels: ChannelArrayS<64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FiringRawS128 {
+    pub toh: Duration,
+    pub azimuth_range: Range<Angle>,
+    pub channels: ChannelArrayS<128>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FiringRawD16 {
     pub toh: Duration,
@@ -32,11 +46,34 @@ pub struct FiringRawD32 {
     pub channels: ChannelArrayD<32>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FiringRawD64 {
+    pub toh: Duration,
+    pub azimuth_range: Range<Angle>,
+    pub channels: ChannelArrayD<64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FiringRawD128 {
+    pub toh: Duration,
+    pub azimuth_range: Range<Angle>,
+    pub channels: ChannelArrayD<128>,
+}
+
 pub use kind::*;
 mod kind {
     use super::*;
 
-    pub type FiringRaw = FormatKind<FiringRawS16, FiringRawS32, FiringRawD16, FiringRawD32>;
+    pub type FiringRaw = FormatKind<
+        FiringRawS16,
+        FiringRawS32,
+        FiringRawS64,
+        FiringRawS128,
+        FiringRawD16,
+        FiringRawD32,
+        FiringRawD64,
+        FiringRawD128,
+    >;
 
     impl FiringRaw {
         pub fn time(&self) -> Duration {
@@ -45,10 +82,38 @@ mod kind {
                 FiringRaw::Single32(me) => me.toh,
                 FiringRaw::Dual16(me) => me.toh,
                 FiringRaw::Dual32(me) => me.toh,
+                FiringRaw::Single64(me) => me.toh,
+                FiringRaw::Single128(me) => me.toh,
+                FiringRaw::Dual64(me) => me.toh,
+                FiringRaw::Dual128(me) => me.toh,
             }
         }
     }
 
+    impl From<FiringRawD64> for FiringRaw {
+        fn from(v: FiringRawD64) -> Self {
+            Self::Dual64(v)
+        }
+    }
+
+    impl From<FiringRawD128> for FiringRaw {
+        fn from(v: FiringRawD128) -> Self {
+            Self::Dual128(v)
+        }
+    }
+
+    impl From<FiringRawS64> for FiringRaw {
+        fn from(v: FiringRawS64) -> Self {
+            Self::Single64(v)
+        }
+    }
+
+    impl From<FiringRawS128> for FiringRaw {
+        fn from(v: FiringRawS128) -> Self {
+            Self::Single128(v)
+        }
+    }
+
     impl From<FiringRawD32> for FiringRaw {
         fn from(v: FiringRawD32) -> Self {
             Self::Dual32(v)
@@ -80,8 +145,16 @@ use super::channel_array::{ChannelArrayD, ChannelArrayS};
 mod ref_kind {
     use super::*;
 
-    pub type FiringRawRef<'a> =
-        FormatKind<&'a FiringRawS16, &'a FiringRawS32, &'a FiringRawD16, &'a FiringRawD32>;
+    pub type FiringRawRef<'a> = FormatKind<
+        &'a FiringRawS16,
+        &'a FiringRawS32,
+        &'a FiringRawS64,
+        &'a FiringRawS128,
+        &'a FiringRawD16,
+        &'a FiringRawD32,
+        &'a FiringRawD64,
+        &'a FiringRawD128,
+    >;
 
     impl<'a> FiringRawRef<'a> {
         pub fn time(&self) -> Duration {
@@ -90,10 +163,38 @@ mod ref_kind {
                 FiringRawRef::Single32(me) => me.toh,
                 FiringRawRef::Dual16(me) => me.toh,
                 FiringRawRef::Dual32(me) => me.toh,
+                FiringRawRef::Single64(me) => me.toh,
+                FiringRawRef::Single128(me) => me.toh,
+                FiringRawRef::Dual64(me) => me.toh,
+                FiringRawRef::Dual128(me) => me.toh,
             }
         }
     }
 
+    impl<'a> From<&'a FiringRawD64> for FiringRawRef<'a> {
+        fn from(v: &'a FiringRawD64) -> Self {
+            Self::Dual64(v)
+        }
+    }
+
+    impl<'a> From<&'a FiringRawD128> for FiringRawRef<'a> {
+        fn from(v: &'a FiringRawD128) -> Self {
+            Self::Dual128(v)
+        }
+    }
+
+    impl<'a> From<&'a FiringRawS64> for FiringRawRef<'a> {
+        fn from(v: &'a FiringRawS64) -> Self {
+            Self::Single64(v)
+        }
+    }
+
+    impl<'a> From<&'a FiringRawS128> for FiringRawRef<'a> {
+        fn from(v: &'a FiringRawS128) -> Self {
+            Self::Single128(v)
+        }
+    }
+
     impl<'a> From<&'a FiringRawD32> for FiringRawRef<'a> {
         fn from(v: &'a FiringRawD32) -> Self {
             Self::Dual32(v)
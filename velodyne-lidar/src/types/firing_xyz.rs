@@ -5,14 +5,18 @@ use crate::types::{
     point::{PointD, PointS},
 };
 use measurements::Angle;
+use serde::{Deserialize, Serialize};
+use serde_big_array::BigArray;
 use std::{ops::Range, time::Duration};
 
 macro_rules! declare_firing_xyz {
     ($name:ident, $size:expr, $point:path) => {
-        #[derive(Debug, Clone, PartialEq, Eq)]
+        #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
         pub struct $name {
             pub toh: Duration,
+            #[serde(with = "crate::serde_units::angle_range")]
             pub azimuth_range: Range<Angle>,
+            #[serde(with = "BigArray")]
             pub points: [$point; $size],
         }
     };
@@ -20,12 +24,19 @@ macro_rules! declare_firing_xyz {
 
 declare_firing_xyz!(FiringXyzS16, 16, PointS);
 declare_firing_xyz!(FiringXyzS32, 32, PointS);
+declare_firing_xyz!(FiringXyzS64, 64, PointS);
+declare_firing_xyz!(FiringXyzS128, 128, PointS);
 declare_firing_xyz!(FiringXyzD16, 16, PointD);
 declare_firing_xyz!(FiringXyzD32, 32, PointD);
+declare_firing_xyz!(FiringXyzD64, 64, PointD);
+declare_firing_xyz!(FiringXyzD128, 128, PointD);
 
 pub use kind::*;
 mod kind {
-    use super::{FiringXyzD16, FiringXyzD32, FiringXyzS16, FiringXyzS32};
+    use super::{
+        FiringXyzD128, FiringXyzD16, FiringXyzD32, FiringXyzD64, FiringXyzS128, FiringXyzS16,
+        FiringXyzS32, FiringXyzS64,
+    };
     use crate::{
         traits::BoxIterator,
         types::{
@@ -35,7 +46,16 @@ mod kind {
     };
     use std::time::Duration;
 
-    pub type FiringXyz = FormatKind<FiringXyzS16, FiringXyzS32, FiringXyzD16, FiringXyzD32>;
+    pub type FiringXyz = FormatKind<
+        FiringXyzS16,
+        FiringXyzS32,
+        FiringXyzS64,
+        FiringXyzS128,
+        FiringXyzD16,
+        FiringXyzD32,
+        FiringXyzD64,
+        FiringXyzD128,
+    >;
 
     impl FiringXyz {
         pub fn time(&self) -> Duration {
@@ -44,6 +64,10 @@ mod kind {
                 FiringXyz::Single32(me) => me.toh,
                 FiringXyz::Dual16(me) => me.toh,
                 FiringXyz::Dual32(me) => me.toh,
+                FiringXyz::Single64(me) => me.toh,
+                FiringXyz::Single128(me) => me.toh,
+                FiringXyz::Dual64(me) => me.toh,
+                FiringXyz::Dual128(me) => me.toh,
             }
         }
 
@@ -53,6 +77,10 @@ mod kind {
                 FiringXyz::Single32(me) => Box::new(me.points.iter().map(PointRef::from)),
                 FiringXyz::Dual16(me) => Box::new(me.points.iter().map(PointRef::from)),
                 FiringXyz::Dual32(me) => Box::new(me.points.iter().map(PointRef::from)),
+                FiringXyz::Single64(me) => Box::new(me.points.iter().map(PointRef::from)),
+                FiringXyz::Single128(me) => Box::new(me.points.iter().map(PointRef::from)),
+                FiringXyz::Dual64(me) => Box::new(me.points.iter().map(PointRef::from)),
+                FiringXyz::Dual128(me) => Box::new(me.points.iter().map(PointRef::from)),
             }
         }
 
@@ -62,10 +90,38 @@ mod kind {
                 FiringXyz::Single32(me) => Box::new(me.points.into_iter().map(Point::from)),
                 FiringXyz::Dual16(me) => Box::new(me.points.into_iter().map(Point::from)),
                 FiringXyz::Dual32(me) => Box::new(me.points.into_iter().map(Point::from)),
+                FiringXyz::Single64(me) => Box::new(me.points.into_iter().map(Point::from)),
+                FiringXyz::Single128(me) => Box::new(me.points.into_iter().map(Point::from)),
+                FiringXyz::Dual64(me) => Box::new(me.points.into_iter().map(Point::from)),
+                FiringXyz::Dual128(me) => Box::new(me.points.into_iter().map(Point::from)),
             }
         }
     }
 
+    impl From<FiringXyzD64> for FiringXyz {
+        fn from(v: FiringXyzD64) -> Self {
+            Self::Dual64(v)
+        }
+    }
+
+    impl From<FiringXyzD128> for FiringXyz {
+        fn from(v: FiringXyzD128) -> Self {
+            Self::Dual128(v)
+        }
+    }
+
+    impl From<FiringXyzS64> for FiringXyz {
+        fn from(v: FiringXyzS64) -> Self {
+            Self::Single64(v)
+        }
+    }
+
+    impl From<FiringXyzS128> for FiringXyz {
+        fn from(v: FiringXyzS128) -> Self {
+            Self::Single128(v)
+        }
+    }
+
     impl From<FiringXyzD32> for FiringXyz {
         fn from(v: FiringXyzD32) -> Self {
             Self::Dual32(v)
@@ -96,8 +152,16 @@ mod ref_kind {
     use super::*;
     use crate::{traits::BoxIterator, types::point::PointRef};
 
-    pub type FiringXyzRef<'a> =
-        FormatKind<&'a FiringXyzS16, &'a FiringXyzS32, &'a FiringXyzD16, &'a FiringXyzD32>;
+    pub type FiringXyzRef<'a> = FormatKind<
+        &'a FiringXyzS16,
+        &'a FiringXyzS32,
+        &'a FiringXyzS64,
+        &'a FiringXyzS128,
+        &'a FiringXyzD16,
+        &'a FiringXyzD32,
+        &'a FiringXyzD64,
+        &'a FiringXyzD128,
+    >;
 
     impl<'a> FiringXyzRef<'a> {
         pub fn time(&self) -> Duration {
@@ -106,6 +170,23 @@ mod ref_kind {
                 FiringXyzRef::Single32(me) => me.toh,
                 FiringXyzRef::Dual16(me) => me.toh,
                 FiringXyzRef::Dual32(me) => me.toh,
+                FiringXyzRef::Single64(me) => me.toh,
+                FiringXyzRef::Single128(me) => me.toh,
+                FiringXyzRef::Dual64(me) => me.toh,
+                FiringXyzRef::Dual128(me) => me.toh,
+            }
+        }
+
+        pub fn azimuth_range(&self) -> Range<Angle> {
+            match self {
+                FiringXyzRef::Single16(me) => me.azimuth_range.clone(),
+                FiringXyzRef::Single32(me) => me.azimuth_range.clone(),
+                FiringXyzRef::Dual16(me) => me.azimuth_range.clone(),
+                FiringXyzRef::Dual32(me) => me.azimuth_range.clone(),
+                FiringXyzRef::Single64(me) => me.azimuth_range.clone(),
+                FiringXyzRef::Single128(me) => me.azimuth_range.clone(),
+                FiringXyzRef::Dual64(me) => me.azimuth_range.clone(),
+                FiringXyzRef::Dual128(me) => me.azimuth_range.clone(),
             }
         }
 
@@ -115,10 +196,38 @@ mod ref_kind {
                 FiringXyzRef::Single32(me) => Box::new(me.points.iter().map(PointRef::from)),
                 FiringXyzRef::Dual16(me) => Box::new(me.points.iter().map(PointRef::from)),
                 FiringXyzRef::Dual32(me) => Box::new(me.points.iter().map(PointRef::from)),
+                FiringXyzRef::Single64(me) => Box::new(me.points.iter().map(PointRef::from)),
+                FiringXyzRef::Single128(me) => Box::new(me.points.iter().map(PointRef::from)),
+                FiringXyzRef::Dual64(me) => Box::new(me.points.iter().map(PointRef::from)),
+                FiringXyzRef::Dual128(me) => Box::new(me.points.iter().map(PointRef::from)),
             }
         }
     }
 
+    impl<'a> From<&'a FiringXyzD64> for FiringXyzRef<'a> {
+        fn from(v: &'a FiringXyzD64) -> Self {
+            Self::Dual64(v)
+        }
+    }
+
+    impl<'a> From<&'a FiringXyzD128> for FiringXyzRef<'a> {
+        fn from(v: &'a FiringXyzD128) -> Self {
+            Self::Dual128(v)
+        }
+    }
+
+    impl<'a> From<&'a FiringXyzS64> for FiringXyzRef<'a> {
+        fn from(v: &'a FiringXyzS64) -> Self {
+            Self::Single64(v)
+        }
+    }
+
+    impl<'a> From<&'a FiringXyzS128> for FiringXyzRef<'a> {
+        fn from(v: &'a FiringXyzS128) -> Self {
+            Self::Single128(v)
+        }
+    }
+
     impl<'a> From<&'a FiringXyzD32> for FiringXyzRef<'a> {
         fn from(v: &'a FiringXyzD32) -> Self {
             Self::Dual32(v)
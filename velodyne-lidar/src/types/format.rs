@@ -1,20 +1,26 @@
 //! Define the formats of point arrangements.
 
 use crate::{
+    consts::{self, FIRING_PERIOD},
     packet::{ProductID, ReturnMode},
     traits::{AzimuthRange, PointField},
+    Error,
 };
-use anyhow::Result;
 use measurements::Angle;
-use std::ops::Range;
+use serde::{Deserialize, Serialize};
+use std::{ops::Range, time::Duration};
 
 /// An enumeration of point arrangement formats.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Format {
     Single16,
     Single32,
+    Single64,
+    Single128,
     Dual16,
     Dual32,
+    Dual64,
+    Dual128,
 }
 
 impl Format {
@@ -27,6 +33,10 @@ impl Format {
             (16, Dual) => Dual16,
             (32, Strongest | Last) => Single32,
             (32, Dual) => Dual32,
+            (64, Strongest | Last) => Single64,
+            (128, Strongest | Last) => Single128,
+            (64, Dual) => Dual64,
+            (128, Dual) => Dual128,
             _ => return None,
         })
     }
@@ -36,36 +46,105 @@ impl Format {
     }
 
     pub fn try_from_model(product_id: ProductID, return_mode: ReturnMode) -> Option<Format> {
-        Self::try_new(product_id.num_lines(), return_mode)
+        Self::try_new(product_id.num_lines()?, return_mode)
     }
 
-    pub fn from_model(product_id: ProductID, return_mode: ReturnMode) -> Format {
-        Self::try_from_model(product_id, return_mode).unwrap()
+    /// Gets the format of a model in a return mode.
+    ///
+    /// It fails if the model is not supported, for example Velarray.
+    pub fn from_model(product_id: ProductID, return_mode: ReturnMode) -> Result<Format, Error> {
+        Self::try_from_model(product_id, return_mode).ok_or(Error::UnsupportedProduct(product_id))
+    }
+
+    /// Gets the number of lasers.
+    pub fn num_lines(&self) -> usize {
+        use Format::*;
+
+        match self {
+            Single16 | Dual16 => 16,
+            Single32 | Dual32 => 32,
+            Single64 | Dual64 => 64,
+            Single128 | Dual128 => 128,
+        }
+    }
+
+    pub fn is_dual(&self) -> bool {
+        use Format::*;
+
+        matches!(self, Dual16 | Dual32 | Dual64 | Dual128)
+    }
+
+    /// Gets the number of firings in one data packet.
+    pub fn firings_per_packet(&self) -> usize {
+        use Format::*;
+
+        match self {
+            Single16 => 24,
+            Dual16 | Single32 => 12,
+            Dual32 | Single64 => 6,
+            Dual64 | Single128 => 3,
+            Dual128 => 1,
+        }
+    }
+
+    /// Gets the period between two consecutive firings.
+    ///
+    /// The 32-line formats depend on the model, which is assumed to be
+    /// VLP-32C if `product_id` is `None`.
+    pub fn firing_period(&self, product_id: Option<ProductID>) -> Duration {
+        use Format::*;
+
+        match self {
+            Single16 | Dual16 => FIRING_PERIOD,
+            Single32 | Dual32 => product_id.unwrap_or(ProductID::VLP32C).firing_period(),
+            Single64 => consts::hdl_64e::FIRING_PERIOD,
+            Dual64 => consts::hdl_64e::DUAL_FIRING_PERIOD,
+            Single128 | Dual128 => consts::vls_128::FIRING_PERIOD,
+        }
+    }
+
+    /// Gets the period between two consecutive data packets.
+    pub fn packet_period(&self, product_id: Option<ProductID>) -> Duration {
+        self.firing_period(product_id) * self.firings_per_packet() as u32
     }
 }
 
 /// An enumeration of point arrangement formats with additional generics.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum FormatKind<S16, S32, D16, D32> {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FormatKind<S16, S32, S64, S128, D16, D32, D64, D128> {
     Single16(S16),
     Single32(S32),
+    Single64(S64),
+    Single128(S128),
     Dual16(D16),
     Dual32(D32),
+    Dual64(D64),
+    Dual128(D128),
 }
 
-impl<S16, S32, D16, D32> FormatKind<S16, S32, D16, D32> {
+impl<S16, S32, S64, S128, D16, D32, D64, D128>
+    FormatKind<S16, S32, S64, S128, D16, D32, D64, D128>
+{
     pub fn from_format_default(format: Format) -> Self
     where
         S16: Default,
         S32: Default,
+        S64: Default,
+        S128: Default,
         D16: Default,
         D32: Default,
+        D64: Default,
+        D128: Default,
     {
         match format {
             Format::Single16 => Self::Single16(S16::default()),
             Format::Single32 => Self::Single32(S32::default()),
+            Format::Single64 => Self::Single64(S64::default()),
+            Format::Single128 => Self::Single128(S128::default()),
             Format::Dual16 => Self::Dual16(D16::default()),
             Format::Dual32 => Self::Dual32(D32::default()),
+            Format::Dual64 => Self::Dual64(D64::default()),
+            Format::Dual128 => Self::Dual128(D128::default()),
         }
     }
 
@@ -73,8 +152,12 @@ impl<S16, S32, D16, D32> FormatKind<S16, S32, D16, D32> {
         match self {
             FormatKind::Single16(_) => Format::Single16,
             FormatKind::Single32(_) => Format::Single32,
+            FormatKind::Single64(_) => Format::Single64,
+            FormatKind::Single128(_) => Format::Single128,
             FormatKind::Dual16(_) => Format::Dual16,
             FormatKind::Dual32(_) => Format::Dual32,
+            FormatKind::Dual64(_) => Format::Dual64,
+            FormatKind::Dual128(_) => Format::Dual128,
         }
     }
 
@@ -86,6 +169,14 @@ impl<S16, S32, D16, D32> FormatKind<S16, S32, D16, D32> {
         Self::Single32(from)
     }
 
+    pub fn from_s64(from: S64) -> Self {
+        Self::Single64(from)
+    }
+
+    pub fn from_s128(from: S128) -> Self {
+        Self::Single128(from)
+    }
+
     pub fn from_d16(from: D16) -> Self {
         Self::Dual16(from)
     }
@@ -94,6 +185,14 @@ impl<S16, S32, D16, D32> FormatKind<S16, S32, D16, D32> {
         Self::Dual32(from)
     }
 
+    pub fn from_d64(from: D64) -> Self {
+        Self::Dual64(from)
+    }
+
+    pub fn from_d128(from: D128) -> Self {
+        Self::Dual128(from)
+    }
+
     pub fn try_into_s16(self) -> Result<S16, Self> {
         match self {
             Self::Single16(s16) => Ok(s16),
@@ -103,21 +202,49 @@ impl<S16, S32, D16, D32> FormatKind<S16, S32, D16, D32> {
 
     pub fn try_into_s32(self) -> Result<S32, Self> {
         match self {
-            Self::Single32(s16) => Ok(s16),
+            Self::Single32(s32) => Ok(s32),
+            _ => Err(self),
+        }
+    }
+
+    pub fn try_into_s64(self) -> Result<S64, Self> {
+        match self {
+            Self::Single64(s64) => Ok(s64),
+            _ => Err(self),
+        }
+    }
+
+    pub fn try_into_s128(self) -> Result<S128, Self> {
+        match self {
+            Self::Single128(s128) => Ok(s128),
             _ => Err(self),
         }
     }
 
     pub fn try_into_d16(self) -> Result<D16, Self> {
         match self {
-            Self::Dual16(s16) => Ok(s16),
+            Self::Dual16(d16) => Ok(d16),
             _ => Err(self),
         }
     }
 
     pub fn try_into_d32(self) -> Result<D32, Self> {
         match self {
-            Self::Dual32(s16) => Ok(s16),
+            Self::Dual32(d32) => Ok(d32),
+            _ => Err(self),
+        }
+    }
+
+    pub fn try_into_d64(self) -> Result<D64, Self> {
+        match self {
+            Self::Dual64(d64) => Ok(d64),
+            _ => Err(self),
+        }
+    }
+
+    pub fn try_into_d128(self) -> Result<D128, Self> {
+        match self {
+            Self::Dual128(d128) => Ok(d128),
             _ => Err(self),
         }
     }
@@ -136,6 +263,20 @@ impl<S16, S32, D16, D32> FormatKind<S16, S32, D16, D32> {
         }
     }
 
+    pub fn as_s64(&self) -> Option<&S64> {
+        match self {
+            Self::Single64(s64) => Some(s64),
+            _ => None,
+        }
+    }
+
+    pub fn as_s128(&self) -> Option<&S128> {
+        match self {
+            Self::Single128(s128) => Some(s128),
+            _ => None,
+        }
+    }
+
     pub fn as_d16(&self) -> Option<&D16> {
         match self {
             Self::Dual16(d16) => Some(d16),
@@ -150,6 +291,20 @@ impl<S16, S32, D16, D32> FormatKind<S16, S32, D16, D32> {
         }
     }
 
+    pub fn as_d64(&self) -> Option<&D64> {
+        match self {
+            Self::Dual64(d64) => Some(d64),
+            _ => None,
+        }
+    }
+
+    pub fn as_d128(&self) -> Option<&D128> {
+        match self {
+            Self::Dual128(d128) => Some(d128),
+            _ => None,
+        }
+    }
+
     pub fn as_s16_mut(&mut self) -> Option<&mut S16> {
         match self {
             Self::Single16(s16) => Some(s16),
@@ -164,6 +319,20 @@ impl<S16, S32, D16, D32> FormatKind<S16, S32, D16, D32> {
         }
     }
 
+    pub fn as_s64_mut(&mut self) -> Option<&mut S64> {
+        match self {
+            Self::Single64(s64) => Some(s64),
+            _ => None,
+        }
+    }
+
+    pub fn as_s128_mut(&mut self) -> Option<&mut S128> {
+        match self {
+            Self::Single128(s128) => Some(s128),
+            _ => None,
+        }
+    }
+
     pub fn as_d16_mut(&mut self) -> Option<&mut D16> {
         match self {
             Self::Dual16(d16) => Some(d16),
@@ -177,65 +346,129 @@ impl<S16, S32, D16, D32> FormatKind<S16, S32, D16, D32> {
             _ => None,
         }
     }
+
+    pub fn as_d64_mut(&mut self) -> Option<&mut D64> {
+        match self {
+            Self::Dual64(d64) => Some(d64),
+            _ => None,
+        }
+    }
+
+    pub fn as_d128_mut(&mut self) -> Option<&mut D128> {
+        match self {
+            Self::Dual128(d128) => Some(d128),
+            _ => None,
+        }
+    }
 }
 
-impl<S16, S32, D16, D32> AzimuthRange for FormatKind<S16, S32, D16, D32>
+impl<S16, S32, S64, S128, D16, D32, D64, D128> AzimuthRange
+    for FormatKind<S16, S32, S64, S128, D16, D32, D64, D128>
 where
     S16: AzimuthRange,
     S32: AzimuthRange,
+    S64: AzimuthRange,
+    S128: AzimuthRange,
     D16: AzimuthRange,
     D32: AzimuthRange,
+    D64: AzimuthRange,
+    D128: AzimuthRange,
 {
     fn azimuth_range(&self) -> Range<Angle> {
         match self {
             FormatKind::Single16(inner) => inner.azimuth_range(),
             FormatKind::Single32(inner) => inner.azimuth_range(),
+            FormatKind::Single64(inner) => inner.azimuth_range(),
+            FormatKind::Single128(inner) => inner.azimuth_range(),
             FormatKind::Dual16(inner) => inner.azimuth_range(),
             FormatKind::Dual32(inner) => inner.azimuth_range(),
+            FormatKind::Dual64(inner) => inner.azimuth_range(),
+            FormatKind::Dual128(inner) => inner.azimuth_range(),
         }
     }
 }
 
-impl<S16, S32, D16, D32> Iterator for FormatKind<S16, S32, D16, D32>
+impl<S16, S32, S64, S128, D16, D32, D64, D128> Iterator
+    for FormatKind<S16, S32, S64, S128, D16, D32, D64, D128>
 where
     S16: Iterator,
     S32: Iterator,
+    S64: Iterator,
+    S128: Iterator,
     D16: Iterator,
     D32: Iterator,
+    D64: Iterator,
+    D128: Iterator,
 {
-    type Item = FormatKind<S16::Item, S32::Item, D16::Item, D32::Item>;
+    type Item = FormatKind<
+        S16::Item,
+        S32::Item,
+        S64::Item,
+        S128::Item,
+        D16::Item,
+        D32::Item,
+        D64::Item,
+        D128::Item,
+    >;
 
     fn next(&mut self) -> Option<Self::Item> {
         let item = match self {
             FormatKind::Single16(iter) => FormatKind::from_s16(iter.next()?),
             FormatKind::Single32(iter) => FormatKind::from_s32(iter.next()?),
+            FormatKind::Single64(iter) => FormatKind::from_s64(iter.next()?),
+            FormatKind::Single128(iter) => FormatKind::from_s128(iter.next()?),
             FormatKind::Dual16(iter) => FormatKind::from_d16(iter.next()?),
             FormatKind::Dual32(iter) => FormatKind::from_d32(iter.next()?),
+            FormatKind::Dual64(iter) => FormatKind::from_d64(iter.next()?),
+            FormatKind::Dual128(iter) => FormatKind::from_d128(iter.next()?),
         };
         Some(item)
     }
 }
 
-impl<S16, S32, D16, D32> PointField for FormatKind<S16, S32, D16, D32>
+impl<S16, S32, S64, S128, D16, D32, D64, D128> PointField
+    for FormatKind<S16, S32, S64, S128, D16, D32, D64, D128>
 where
     S16: PointField,
     S32: PointField,
+    S64: PointField,
+    S128: PointField,
     D16: PointField,
     D32: PointField,
+    D64: PointField,
+    D128: PointField,
 {
-    type Point<'a> = FormatKind<S16::Point<'a>, S32::Point<'a>, D16::Point<'a>, D32::Point<'a>>
+    type Point<'a>
+        = FormatKind<
+        S16::Point<'a>,
+        S32::Point<'a>,
+        S64::Point<'a>,
+        S128::Point<'a>,
+        D16::Point<'a>,
+        D32::Point<'a>,
+        D64::Point<'a>,
+        D128::Point<'a>,
+    >
     where
         S16: 'a,
         S32: 'a,
+        S64: 'a,
+        S128: 'a,
         D16: 'a,
-        D32: 'a;
+        D32: 'a,
+        D64: 'a,
+        D128: 'a;
 
     fn nrows(&self) -> usize {
         match self {
             FormatKind::Single16(inner) => inner.nrows(),
             FormatKind::Single32(inner) => inner.nrows(),
+            FormatKind::Single64(inner) => inner.nrows(),
+            FormatKind::Single128(inner) => inner.nrows(),
             FormatKind::Dual16(inner) => inner.nrows(),
             FormatKind::Dual32(inner) => inner.nrows(),
+            FormatKind::Dual64(inner) => inner.nrows(),
+            FormatKind::Dual128(inner) => inner.nrows(),
         }
     }
 
@@ -243,8 +476,12 @@ where
         match self {
             FormatKind::Single16(inner) => inner.ncols(),
             FormatKind::Single32(inner) => inner.ncols(),
+            FormatKind::Single64(inner) => inner.ncols(),
+            FormatKind::Single128(inner) => inner.ncols(),
             FormatKind::Dual16(inner) => inner.ncols(),
             FormatKind::Dual32(inner) => inner.ncols(),
+            FormatKind::Dual64(inner) => inner.ncols(),
+            FormatKind::Dual128(inner) => inner.ncols(),
         }
     }
 
@@ -252,8 +489,12 @@ where
         let point = match self {
             FormatKind::Single16(inner) => FormatKind::from_s16(inner.point_at(row, col)?),
             FormatKind::Single32(inner) => FormatKind::from_s32(inner.point_at(row, col)?),
+            FormatKind::Single64(inner) => FormatKind::from_s64(inner.point_at(row, col)?),
+            FormatKind::Single128(inner) => FormatKind::from_s128(inner.point_at(row, col)?),
             FormatKind::Dual16(inner) => FormatKind::from_d16(inner.point_at(row, col)?),
             FormatKind::Dual32(inner) => FormatKind::from_d32(inner.point_at(row, col)?),
+            FormatKind::Dual64(inner) => FormatKind::from_d64(inner.point_at(row, col)?),
+            FormatKind::Dual128(inner) => FormatKind::from_d128(inner.point_at(row, col)?),
         };
         Some(point)
     }
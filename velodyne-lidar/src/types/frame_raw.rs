@@ -9,13 +9,25 @@ use crate::{
     traits::{BoxIterator, PointField},
     types::{
         channel::{ChannelD, ChannelKind, ChannelRefD},
-        firing_raw::{FiringRawD16, FiringRawD32, FiringRawS16, FiringRawS32},
+        firing_raw::{
+            FiringRawD128, FiringRawD16, FiringRawD32, FiringRawD64, FiringRawS128, FiringRawS16,
+            FiringRawS32, FiringRawS64,
+        },
         format::FormatKind,
     },
 };
 use itertools::izip;
 
-pub type FrameRaw = FormatKind<FrameRawS16, FrameRawS32, FrameRawD16, FrameRawD32>;
+pub type FrameRaw = FormatKind<
+    FrameRawS16,
+    FrameRawS32,
+    FrameRawS64,
+    FrameRawS128,
+    FrameRawD16,
+    FrameRawD32,
+    FrameRawD64,
+    FrameRawD128,
+>;
 
 impl FrameRaw {
     pub fn firing_iter(&self) -> impl Iterator<Item = FiringRawRef<'_>> + Clone + Sync + Send {
@@ -24,6 +36,10 @@ impl FrameRaw {
             FrameRaw::Single32(me) => FormatKind::from_s32(me.firings.iter()),
             FrameRaw::Dual16(me) => FormatKind::from_d16(me.firings.iter()),
             FrameRaw::Dual32(me) => FormatKind::from_d32(me.firings.iter()),
+            FrameRaw::Single64(me) => FormatKind::from_s64(me.firings.iter()),
+            FrameRaw::Single128(me) => FormatKind::from_s128(me.firings.iter()),
+            FrameRaw::Dual64(me) => FormatKind::from_d64(me.firings.iter()),
+            FrameRaw::Dual128(me) => FormatKind::from_d128(me.firings.iter()),
         }
     }
 
@@ -33,6 +49,10 @@ impl FrameRaw {
             FrameRaw::Single32(me) => FormatKind::from_s32(me.firings.into_iter()),
             FrameRaw::Dual16(me) => FormatKind::from_d16(me.firings.into_iter()),
             FrameRaw::Dual32(me) => FormatKind::from_d32(me.firings.into_iter()),
+            FrameRaw::Single64(me) => FormatKind::from_s64(me.firings.into_iter()),
+            FrameRaw::Single128(me) => FormatKind::from_s128(me.firings.into_iter()),
+            FrameRaw::Dual64(me) => FormatKind::from_d64(me.firings.into_iter()),
+            FrameRaw::Dual128(me) => FormatKind::from_d128(me.firings.into_iter()),
         }
     }
 
@@ -42,6 +62,10 @@ impl FrameRaw {
             Self::Single32(frame) => Box::new(frame.into_channel_iter().map(ChannelKind::from)),
             Self::Dual16(frame) => Box::new(frame.into_channel_iter().map(ChannelKind::from)),
             Self::Dual32(frame) => Box::new(frame.into_channel_iter().map(ChannelKind::from)),
+            Self::Single64(frame) => Box::new(frame.into_channel_iter().map(ChannelKind::from)),
+            Self::Single128(frame) => Box::new(frame.into_channel_iter().map(ChannelKind::from)),
+            Self::Dual64(frame) => Box::new(frame.into_channel_iter().map(ChannelKind::from)),
+            Self::Dual128(frame) => Box::new(frame.into_channel_iter().map(ChannelKind::from)),
         }
     }
 
@@ -67,10 +91,54 @@ impl FrameRaw {
                     .into_indexed_channel_iter()
                     .map(|(index, point)| (index, ChannelKind::from(point))),
             ),
+            Self::Single64(frame) => Box::new(
+                frame
+                    .into_indexed_channel_iter()
+                    .map(|(index, point)| (index, ChannelKind::from(point))),
+            ),
+            Self::Single128(frame) => Box::new(
+                frame
+                    .into_indexed_channel_iter()
+                    .map(|(index, point)| (index, ChannelKind::from(point))),
+            ),
+            Self::Dual64(frame) => Box::new(
+                frame
+                    .into_indexed_channel_iter()
+                    .map(|(index, point)| (index, ChannelKind::from(point))),
+            ),
+            Self::Dual128(frame) => Box::new(
+                frame
+                    .into_indexed_channel_iter()
+                    .map(|(index, point)| (index, ChannelKind::from(point))),
+            ),
         }
     }
 }
 
+impl From<FrameRawD64> for FrameRaw {
+    fn from(v: FrameRawD64) -> Self {
+        Self::Dual64(v)
+    }
+}
+
+impl From<FrameRawD128> for FrameRaw {
+    fn from(v: FrameRawD128) -> Self {
+        Self::Dual128(v)
+    }
+}
+
+impl From<FrameRawS64> for FrameRaw {
+    fn from(v: FrameRawS64) -> Self {
+        Self::Single64(v)
+    }
+}
+
+impl From<FrameRawS128> for FrameRaw {
+    fn from(v: FrameRawS128) -> Self {
+        Self::Single128(v)
+    }
+}
+
 impl From<FrameRawD16> for FrameRaw {
     fn from(v: FrameRawD16) -> Self {
         Self::Dual16(v)
@@ -197,5 +265,9 @@ macro_rules! declare_type_dual {
 
 declare_type_single!(FrameRawS16, FiringRawS16, 16);
 declare_type_single!(FrameRawS32, FiringRawS32, 32);
+declare_type_single!(FrameRawS64, FiringRawS64, 64);
+declare_type_single!(FrameRawS128, FiringRawS128, 128);
 declare_type_dual!(FrameRawD16, FiringRawD16, 16);
 declare_type_dual!(FrameRawD32, FiringRawD32, 32);
+declare_type_dual!(FrameRawD64, FiringRawD64, 64);
+declare_type_dual!(FrameRawD128, FiringRawD128, 128);
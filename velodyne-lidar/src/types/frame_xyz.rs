@@ -2,8 +2,12 @@
 
 pub use frame_kind::*;
 mod frame_kind {
-    use super::{FrameXyzD16, FrameXyzD32, FrameXyzS16, FrameXyzS32};
+    use super::{
+        FrameXyzD128, FrameXyzD16, FrameXyzD32, FrameXyzD64, FrameXyzS128, FrameXyzS16,
+        FrameXyzS32, FrameXyzS64,
+    };
     use crate::{
+        diagnostics::FrameStats,
         traits::BoxIterator,
         types::{
             firing_xyz::{FiringXyz, FiringXyzRef},
@@ -12,15 +16,43 @@ mod frame_kind {
         },
     };
 
-    pub type FrameXyz = FormatKind<FrameXyzS16, FrameXyzS32, FrameXyzD16, FrameXyzD32>;
+    pub type FrameXyz = FormatKind<
+        FrameXyzS16,
+        FrameXyzS32,
+        FrameXyzS64,
+        FrameXyzS128,
+        FrameXyzD16,
+        FrameXyzD32,
+        FrameXyzD64,
+        FrameXyzD128,
+    >;
 
     impl FrameXyz {
+        /// Gets the completeness stats recorded when the frame was
+        /// built.
+        pub fn stats(&self) -> &FrameStats {
+            match self {
+                FrameXyz::Single16(me) => &me.stats,
+                FrameXyz::Single32(me) => &me.stats,
+                FrameXyz::Dual16(me) => &me.stats,
+                FrameXyz::Dual32(me) => &me.stats,
+                FrameXyz::Single64(me) => &me.stats,
+                FrameXyz::Single128(me) => &me.stats,
+                FrameXyz::Dual64(me) => &me.stats,
+                FrameXyz::Dual128(me) => &me.stats,
+            }
+        }
+
         pub fn firing_iter(&self) -> impl Iterator<Item = FiringXyzRef<'_>> + Clone + Sync + Send {
             match self {
                 FrameXyz::Single16(me) => FormatKind::from_s16(me.firings.iter()),
                 FrameXyz::Single32(me) => FormatKind::from_s32(me.firings.iter()),
                 FrameXyz::Dual16(me) => FormatKind::from_d16(me.firings.iter()),
                 FrameXyz::Dual32(me) => FormatKind::from_d32(me.firings.iter()),
+                FrameXyz::Single64(me) => FormatKind::from_s64(me.firings.iter()),
+                FrameXyz::Single128(me) => FormatKind::from_s128(me.firings.iter()),
+                FrameXyz::Dual64(me) => FormatKind::from_d64(me.firings.iter()),
+                FrameXyz::Dual128(me) => FormatKind::from_d128(me.firings.iter()),
             }
         }
 
@@ -30,6 +62,10 @@ mod frame_kind {
                 FrameXyz::Single32(me) => FormatKind::from_s32(me.firings.into_iter()),
                 FrameXyz::Dual16(me) => FormatKind::from_d16(me.firings.into_iter()),
                 FrameXyz::Dual32(me) => FormatKind::from_d32(me.firings.into_iter()),
+                FrameXyz::Single64(me) => FormatKind::from_s64(me.firings.into_iter()),
+                FrameXyz::Single128(me) => FormatKind::from_s128(me.firings.into_iter()),
+                FrameXyz::Dual64(me) => FormatKind::from_d64(me.firings.into_iter()),
+                FrameXyz::Dual128(me) => FormatKind::from_d128(me.firings.into_iter()),
             }
         }
 
@@ -39,6 +75,10 @@ mod frame_kind {
                 Self::Single32(frame) => Box::new(frame.into_point_iter().map(Point::from)),
                 Self::Dual16(frame) => Box::new(frame.into_point_iter().map(Point::from)),
                 Self::Dual32(frame) => Box::new(frame.into_point_iter().map(Point::from)),
+                Self::Single64(frame) => Box::new(frame.into_point_iter().map(Point::from)),
+                Self::Single128(frame) => Box::new(frame.into_point_iter().map(Point::from)),
+                Self::Dual64(frame) => Box::new(frame.into_point_iter().map(Point::from)),
+                Self::Dual128(frame) => Box::new(frame.into_point_iter().map(Point::from)),
             }
         }
 
@@ -64,10 +104,54 @@ mod frame_kind {
                         .into_indexed_point_iter()
                         .map(|(index, point)| (index, Point::from(point))),
                 ),
+                Self::Single64(frame) => Box::new(
+                    frame
+                        .into_indexed_point_iter()
+                        .map(|(index, point)| (index, Point::from(point))),
+                ),
+                Self::Single128(frame) => Box::new(
+                    frame
+                        .into_indexed_point_iter()
+                        .map(|(index, point)| (index, Point::from(point))),
+                ),
+                Self::Dual64(frame) => Box::new(
+                    frame
+                        .into_indexed_point_iter()
+                        .map(|(index, point)| (index, Point::from(point))),
+                ),
+                Self::Dual128(frame) => Box::new(
+                    frame
+                        .into_indexed_point_iter()
+                        .map(|(index, point)| (index, Point::from(point))),
+                ),
             }
         }
     }
 
+    impl From<FrameXyzD64> for FrameXyz {
+        fn from(v: FrameXyzD64) -> Self {
+            Self::Dual64(v)
+        }
+    }
+
+    impl From<FrameXyzD128> for FrameXyz {
+        fn from(v: FrameXyzD128) -> Self {
+            Self::Dual128(v)
+        }
+    }
+
+    impl From<FrameXyzS64> for FrameXyz {
+        fn from(v: FrameXyzS64) -> Self {
+            Self::Single64(v)
+        }
+    }
+
+    impl From<FrameXyzS128> for FrameXyz {
+        fn from(v: FrameXyzS128) -> Self {
+            Self::Single128(v)
+        }
+    }
+
     impl From<FrameXyzD16> for FrameXyz {
         fn from(v: FrameXyzD16) -> Self {
             Self::Dual16(v)
@@ -96,18 +180,24 @@ mod frame_kind {
 pub use frame_types::*;
 mod frame_types {
     use crate::{
+        diagnostics::FrameStats,
         traits::PointField,
         types::{
-            firing_xyz::{FiringXyzD16, FiringXyzD32, FiringXyzS16, FiringXyzS32},
+            firing_xyz::{
+                FiringXyzD128, FiringXyzD16, FiringXyzD32, FiringXyzD64, FiringXyzS128,
+                FiringXyzS16, FiringXyzS32, FiringXyzS64,
+            },
             point::{PointD, PointS},
         },
     };
+    use serde::{Deserialize, Serialize};
 
     macro_rules! declare_type {
         ($name:ident, $firing:ident, $nrows:expr, $point:ident) => {
-            #[derive(Debug, Clone)]
+            #[derive(Debug, Clone, Serialize, Deserialize)]
             pub struct $name {
                 pub firings: Vec<$firing>,
+                pub stats: FrameStats,
             }
 
             impl PointField for $name {
@@ -151,6 +241,10 @@ mod frame_types {
 
     declare_type!(FrameXyzS16, FiringXyzS16, 16, PointS);
     declare_type!(FrameXyzS32, FiringXyzS32, 32, PointS);
+    declare_type!(FrameXyzS64, FiringXyzS64, 64, PointS);
+    declare_type!(FrameXyzS128, FiringXyzS128, 128, PointS);
     declare_type!(FrameXyzD16, FiringXyzD16, 16, PointD);
     declare_type!(FrameXyzD32, FiringXyzD32, 32, PointD);
+    declare_type!(FrameXyzD64, FiringXyzD64, 64, PointD);
+    declare_type!(FrameXyzD128, FiringXyzD128, 128, PointD);
 }
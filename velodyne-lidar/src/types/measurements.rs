@@ -1,15 +1,18 @@
 use measurements::Length;
+use serde::{Deserialize, Serialize};
 
 /// Point in strongest or last return mode.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Measurement {
+    #[serde(with = "crate::serde_units::length")]
     pub distance: Length,
     pub intensity: u8,
+    #[serde(with = "crate::serde_units::xyz")]
     pub xyz: [Length; 3],
 }
 
 /// Point in strongest or last return mode.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct MeasurementDual {
     pub strongest: Measurement,
     pub last: Measurement,
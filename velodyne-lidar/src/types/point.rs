@@ -1,20 +1,23 @@
 //! Point types for laser measurements.
 use crate::types::measurements::{Measurement, MeasurementDual};
 use measurements::Angle;
+use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PointS {
     pub laser_id: usize,
     pub toh: Duration,
+    #[serde(with = "crate::serde_units::angle")]
     pub azimuth: Angle,
     pub measurement: Measurement,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PointD {
     pub laser_id: usize,
     pub toh: Duration,
+    #[serde(with = "crate::serde_units::angle")]
     pub azimuth: Angle,
     pub measurements: MeasurementDual,
 }
@@ -0,0 +1,379 @@
+//! Absolute UTC timestamps from the time of hour.
+//!
+//! Sensors timestamp packets with the microseconds since the top of
+//! the hour, which is ambiguous across hours. [UtcResolver] keeps
+//! track of the current hour, which is learned from the GPRMC
+//! sentences in position packets or given by the user, and turns
+//! times of hour into [DateTime]s.
+
+use crate::{
+    packet::{PositionPacket, PpsStatus},
+    types::frame_xyz::FrameXyz,
+    DataPacket, Packet,
+};
+use chrono::{DateTime, Duration as ChronoDuration, NaiveDateTime, Timelike, Utc};
+use std::time::Duration;
+
+/// Resolves times of hour to UTC times.
+///
+/// The resolver remembers the last resolved time. Each time of hour
+/// is placed in the hour that brings it closest to that time, so
+/// resolving packets in order follows the hour rollovers as long as
+/// consecutive packets are less than 30 minutes apart.
+#[derive(Debug, Clone, Default)]
+pub struct UtcResolver {
+    anchor: Option<DateTime<Utc>>,
+    pps_status: Option<PpsStatus>,
+    require_pps_lock: bool,
+}
+
+impl UtcResolver {
+    /// Creates a resolver that waits for the time from position
+    /// packets.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a resolver for data starting in the hour that
+    /// contains `hour_base`.
+    pub fn with_hour_base(hour_base: DateTime<Utc>) -> Self {
+        let top = truncate_to_hour(hour_base);
+        Self {
+            anchor: Some(top + ChronoDuration::minutes(30)),
+            ..Self::default()
+        }
+    }
+
+    /// Only trusts the GPRMC time of position packets reporting a
+    /// locked PPS signal if enabled.
+    ///
+    /// Without a PPS lock, the time of hour is counted by the
+    /// sensor's own clock and may drift from the GPS time.
+    pub fn require_pps_lock(mut self, enabled: bool) -> Self {
+        self.require_pps_lock = enabled;
+        self
+    }
+
+    /// Gets the PPS status of the latest position packet.
+    pub fn pps_status(&self) -> Option<PpsStatus> {
+        self.pps_status
+    }
+
+    /// Checks if the resolver knows the current hour.
+    pub fn is_resolved(&self) -> bool {
+        self.anchor.is_some()
+    }
+
+    /// Updates the current hour from a position packet and returns
+    /// the time of the packet.
+    ///
+    /// It returns `None` if the packet has no valid GPRMC sentence,
+    /// or if the PPS is not locked when
+    /// [require_pps_lock()](Self::require_pps_lock) is enabled. In
+    /// that case, the time is still resolved from the current hour
+    /// if known.
+    pub fn push_position(&mut self, packet: &PositionPacket) -> Option<DateTime<Utc>> {
+        let pps_status = packet.pps_status();
+        self.pps_status = pps_status;
+
+        let trusted = !self.require_pps_lock || pps_status == Some(PpsStatus::Locked);
+        let rmc_time = trusted
+            .then(|| parse_gprmc(packet.nmea_sentence()))
+            .flatten();
+
+        match rmc_time {
+            Some(rmc_time) => {
+                // The GPRMC time marks the latest PPS pulse, which is
+                // within a second before the packet.
+                let time = utc_from_toh(packet.toh(), rmc_time);
+                self.anchor = Some(time);
+                Some(time)
+            }
+            None => self.resolve(packet.toh()),
+        }
+    }
+
+    /// Resolves a time of hour and advances the resolver to it.
+    pub fn resolve(&mut self, toh: Duration) -> Option<DateTime<Utc>> {
+        let time = self.peek(toh)?;
+        self.anchor = Some(time);
+        Some(time)
+    }
+
+    /// Resolves a time of hour without advancing the resolver.
+    pub fn peek(&self, toh: Duration) -> Option<DateTime<Utc>> {
+        Some(utc_from_toh(toh, self.anchor?))
+    }
+
+    /// Resolves the time of a data packet.
+    pub fn resolve_data_packet(&mut self, packet: &DataPacket) -> Option<DateTime<Utc>> {
+        self.resolve(packet.toh())
+    }
+
+    /// Resolves the time of a packet, learning the hour from position
+    /// packets.
+    pub fn resolve_packet(&mut self, packet: &Packet) -> Option<DateTime<Utc>> {
+        match packet {
+            Packet::Data(packet) => self.resolve_data_packet(packet),
+            Packet::Position(packet) => self.push_position(packet),
+        }
+    }
+
+    /// Resolves the times of all points in a frame in the order of
+    /// [firing_iter()](FrameXyz::firing_iter) and
+    /// [point_iter()](crate::types::firing_xyz::FiringXyzRef::point_iter),
+    /// without advancing the resolver.
+    ///
+    /// The first point is resolved by the resolver and the rest are
+    /// resolved relative to the first one, so a frame crossing the
+    /// top of the hour gets increasing times.
+    pub fn resolve_frame(&self, frame: &FrameXyz) -> Option<Vec<DateTime<Utc>>> {
+        let mut times = frame.firing_iter().flat_map(|firing| {
+            firing
+                .point_iter()
+                .map(|point| point.time())
+                .collect::<Vec<_>>()
+        });
+        let Some(first) = times.next() else {
+            return Some(vec![]);
+        };
+        let first = self.peek(first)?;
+
+        let output = [first]
+            .into_iter()
+            .chain(times.map(|toh| utc_from_toh(toh, first)))
+            .collect();
+        Some(output)
+    }
+}
+
+/// Places a time of hour in the hour that brings it closest to
+/// `near`.
+pub fn utc_from_toh(toh: Duration, near: DateTime<Utc>) -> DateTime<Utc> {
+    let toh = ChronoDuration::from_std(toh).unwrap_or_else(|_| ChronoDuration::zero());
+    let hour = ChronoDuration::hours(1);
+    let top = truncate_to_hour(near);
+
+    [top - hour, top, top + hour]
+        .into_iter()
+        .map(|top| top + toh)
+        .min_by_key(|time| (*time - near).abs())
+        .unwrap()
+}
+
+/// Parses the UTC date and time of an RMC sentence with a valid fix.
+///
+/// The talker ID is not checked, so `$GNRMC` is accepted as well.
+/// The sentence is rejected if its checksum does not match.
+///
+/// It uses the [nmea] parser if the `nmea` feature is enabled.
+/// Otherwise, a minimal parser reading the time and date fields is
+/// used, so that the resolver works without the feature.
+pub fn parse_gprmc(sentence: &[u8]) -> Option<DateTime<Utc>> {
+    let sentence = std::str::from_utf8(sentence).ok()?.trim();
+    parse_rmc(sentence)
+}
+
+#[cfg(feature = "nmea")]
+fn parse_rmc(sentence: &str) -> Option<DateTime<Utc>> {
+    use nmea::{sentences::rmc::RmcStatusOfFix, ParseResult};
+
+    let ParseResult::RMC(rmc) = nmea::parse_str(sentence).ok()? else {
+        return None;
+    };
+    if rmc.status_of_fix == RmcStatusOfFix::Invalid {
+        return None;
+    }
+    Some(NaiveDateTime::new(rmc.fix_date?, rmc.fix_time?).and_utc())
+}
+
+#[cfg(not(feature = "nmea"))]
+fn parse_rmc(sentence: &str) -> Option<DateTime<Utc>> {
+    use chrono::{NaiveDate, NaiveTime};
+
+    let sentence = sentence.strip_prefix('$')?;
+    let (sentence, checksum) = sentence.split_once('*')?;
+    let checksum = u8::from_str_radix(checksum, 16).ok()?;
+    if sentence.bytes().fold(0, |sum, byte| sum ^ byte) != checksum {
+        return None;
+    }
+    let mut fields = sentence.split(',');
+
+    let kind = fields.next()?;
+    if kind.len() != 5 || !kind.ends_with("RMC") {
+        return None;
+    }
+    let time = fields.next()?;
+    let status = fields.next()?;
+    if !matches!(status, "A" | "D") {
+        return None;
+    }
+    let date = fields.nth(6)?;
+
+    let time = {
+        let (hms, fraction) = time.split_once('.').unwrap_or((time, ""));
+        if hms.len() != 6 || !hms.is_ascii() {
+            return None;
+        }
+        let hour = hms[0..2].parse().ok()?;
+        let minute = hms[2..4].parse().ok()?;
+        let second = hms[4..6].parse().ok()?;
+        let micro = if fraction.is_empty() {
+            0
+        } else {
+            let digits: String = fraction.chars().chain("000000".chars()).take(6).collect();
+            digits.parse().ok()?
+        };
+        NaiveTime::from_hms_micro_opt(hour, minute, second, micro)?
+    };
+    let date = {
+        if date.len() != 6 || !date.is_ascii() {
+            return None;
+        }
+        let day = date[0..2].parse().ok()?;
+        let month = date[2..4].parse().ok()?;
+        // two-digit years from 83 on are in the 1900s, when NMEA 0183
+        // was released
+        let year = match date[4..6].parse().ok()? {
+            year @ 83..=99 => 1900 + year,
+            year => 2000 + year,
+        };
+        NaiveDate::from_ymd_opt(year, month, day)?
+    };
+
+    Some(NaiveDateTime::new(date, time).and_utc())
+}
+
+fn truncate_to_hour(time: DateTime<Utc>) -> DateTime<Utc> {
+    time.with_minute(0)
+        .and_then(|time| time.with_second(0))
+        .and_then(|time| time.with_nanosecond(0))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        capture::PacketReader,
+        packet::{Channel, DataPacketSynthesizer, ProductID, ReturnMode},
+        Config, FrameBuilder,
+    };
+    use chrono::TimeZone;
+
+    fn utc(y: i32, mo: u32, d: u32, h: u32, mi: u32, s: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, mo, d, h, mi, s).unwrap()
+    }
+
+    #[test]
+    fn parse_rmc_sentence() {
+        let sentence =
+            b"$GPRMC,214616,A,3708.3443,N,12139.4299,W,009.7,040.6,111212,013.8,E,D*0E\r\n";
+        assert_eq!(parse_gprmc(sentence), Some(utc(2012, 12, 11, 21, 46, 16)));
+
+        let sentence = b"$GNRMC,001031.25,A,4404.13993,N,12118.86023,W,0.146,,100117,,,A*7C";
+        let expect = utc(2017, 1, 10, 0, 10, 31) + ChronoDuration::milliseconds(250);
+        assert_eq!(parse_gprmc(sentence), Some(expect));
+
+        // a bad checksum
+        let sentence = b"$GNRMC,001031.25,A,4404.13993,N,12118.86023,W,0.146,,100117,,,A*7B";
+        assert_eq!(parse_gprmc(sentence), None);
+
+        let sentence = b"$GPRMC,214616,V,,,,,,,111212,,,N*55";
+        assert_eq!(parse_gprmc(sentence), None);
+        assert_eq!(parse_gprmc(b"$GPGGA,214616,3708.3443,N"), None);
+    }
+
+    #[test]
+    fn hour_rollover() {
+        let near = utc(2024, 1, 1, 23, 59, 50);
+        let time = utc_from_toh(Duration::from_secs(5), near);
+        assert_eq!(time, utc(2024, 1, 2, 0, 0, 5));
+        let time = utc_from_toh(Duration::from_secs(3595), utc(2024, 1, 2, 0, 0, 1));
+        assert_eq!(time, utc(2024, 1, 1, 23, 59, 55));
+
+        let mut resolver = UtcResolver::with_hour_base(utc(2024, 1, 1, 23, 0, 0));
+        assert_eq!(
+            resolver.resolve(Duration::from_secs(3599)),
+            Some(utc(2024, 1, 1, 23, 59, 59))
+        );
+        assert_eq!(
+            resolver.resolve(Duration::from_secs(1)),
+            Some(utc(2024, 1, 2, 0, 0, 1))
+        );
+        assert_eq!(
+            resolver.peek(Duration::from_secs(3599)),
+            Some(utc(2024, 1, 1, 23, 59, 59))
+        );
+
+        // the hour base covers the whole hour
+        let resolver = UtcResolver::with_hour_base(utc(2024, 1, 1, 23, 0, 0));
+        assert_eq!(
+            resolver.peek(Duration::from_secs(3500)),
+            Some(utc(2024, 1, 1, 23, 58, 20))
+        );
+    }
+
+    #[test]
+    fn resolve_from_position_packets() {
+        let mut resolver = UtcResolver::new();
+        let mut times = vec![];
+
+        for packet in PacketReader::open("tests/velodyne_vlp32.pcap")
+            .unwrap()
+            .packets()
+        {
+            let packet = packet.unwrap().packet;
+            let is_data = matches!(packet, Packet::Data(_));
+            let time = resolver.resolve_packet(&packet);
+            if is_data && resolver.is_resolved() {
+                times.push(time.unwrap());
+            }
+        }
+
+        assert_eq!(resolver.pps_status(), Some(PpsStatus::Abscent));
+        assert!(!times.is_empty());
+        assert!(times.windows(2).all(|pair| pair[0] <= pair[1]));
+        // the capture is recorded at 2012-12-11 21:46:17 UTC
+        let start = utc(2012, 12, 11, 21, 46, 17);
+        assert!(times[0] >= start && times[0] < start + ChronoDuration::seconds(1));
+
+        let mut resolver = UtcResolver::new().require_pps_lock(true);
+        let mut packets = PacketReader::open("tests/velodyne_vlp32.pcap")
+            .unwrap()
+            .packets();
+        let position = packets
+            .find_map(|packet| packet.unwrap().packet.try_into_position().ok())
+            .unwrap();
+        assert_eq!(resolver.push_position(&position), None);
+        assert!(!resolver.is_resolved());
+    }
+
+    #[test]
+    fn frame_across_hour() {
+        let start_toh = Duration::from_secs(3600) - Duration::from_millis(50);
+        let packets =
+            DataPacketSynthesizer::new(ProductID::VLP16, ReturnMode::Strongest, 600.0, |_, _| {
+                Channel {
+                    distance: 1000,
+                    intensity: 100,
+                }
+            })
+            .unwrap()
+            .start_toh(start_toh)
+            .take(60);
+        let mut builder = FrameBuilder::new(Config::new_vlp_16_strongest()).unwrap();
+        packets.for_each(|packet| {
+            builder.push_packet(&packet);
+        });
+        let frame = builder.finish().unwrap();
+
+        let resolver = UtcResolver::with_hour_base(utc(2024, 6, 30, 23, 0, 0));
+        let times = resolver.resolve_frame(&frame).unwrap();
+
+        assert_eq!(times.len(), frame.firing_iter().count() * 16);
+        assert!(times[0] < utc(2024, 7, 1, 0, 0, 0));
+        assert!(*times.last().unwrap() > utc(2024, 7, 1, 0, 0, 0));
+        assert!(times.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+}
@@ -5,6 +5,8 @@ use thiserror::Error;
 pub enum Error {
     #[error("I/O error: {0}")]
     IO(io::Error),
+    #[error("YAML error: {0}")]
+    Yaml(serde_yaml::Error),
     #[error("Invalid parameters: {0}")]
     InvalidParams(String),
 }
@@ -14,13 +14,24 @@ pub struct SerializedLaser {
     pub dist_correction_y: f64,
     pub focal_distance: f64,
     pub focal_slope: f64,
+    #[serde(default)]
     pub horiz_offset_correction: f64,
     pub laser_id: u32,
+    #[serde(default = "default_max_intensity")]
+    pub max_intensity: f64,
+    #[serde(default)]
+    pub min_intensity: f64,
     pub rot_correction: f64,
+    #[serde(default)]
+    pub two_pt_correction_available: bool,
     pub vert_correction: f64,
     pub vert_offset_correction: f64,
 }
 
+fn default_max_intensity() -> f64 {
+    255.0
+}
+
 #[cfg(test)]
 mod tests {
     use super::SerializedVelodyneParams;
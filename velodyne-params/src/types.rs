@@ -5,6 +5,7 @@ use crate::{
 use itertools::Itertools;
 use measurements::Length;
 use serde::{Deserialize, Serialize};
+use std::{fs::File, io::prelude::*, path::Path};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(
@@ -16,6 +17,24 @@ pub struct VelodyneParams {
     pub lasers: Vec<Laser>,
 }
 
+impl VelodyneParams {
+    /// Loads parameters from a YAML file.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let file = File::open(path).map_err(Error::IO)?;
+        Self::from_reader(file)
+    }
+
+    /// Loads parameters from YAML data in a reader.
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, Error> {
+        serde_yaml::from_reader(reader).map_err(Error::Yaml)
+    }
+
+    /// Parses parameters from a YAML string.
+    pub fn from_yaml_str(text: &str) -> Result<Self, Error> {
+        serde_yaml::from_str(text).map_err(Error::Yaml)
+    }
+}
+
 impl TryFrom<SerializedVelodyneParams> for VelodyneParams {
     type Error = Error;
 
@@ -75,7 +94,10 @@ pub struct Laser {
     pub focal_distance: f64,
     pub focal_slope: f64,
     pub horiz_offset_correction: f64,
+    pub max_intensity: f64,
+    pub min_intensity: f64,
     pub rot_correction: f64,
+    pub two_pt_correction_available: bool,
     pub vert_correction: f64,
     pub vert_offset_correction: f64,
 }
@@ -92,7 +114,10 @@ impl TryFrom<SerializedLaser> for Laser {
             focal_slope,
             horiz_offset_correction,
             laser_id,
+            max_intensity,
+            min_intensity,
             rot_correction,
+            two_pt_correction_available,
             vert_correction,
             vert_offset_correction,
         } = orig;
@@ -105,7 +130,10 @@ impl TryFrom<SerializedLaser> for Laser {
             focal_distance,
             focal_slope,
             horiz_offset_correction,
+            max_intensity,
+            min_intensity,
             rot_correction,
+            two_pt_correction_available,
             vert_correction,
             vert_offset_correction,
         })
@@ -122,7 +150,10 @@ impl From<Laser> for SerializedLaser {
             focal_slope,
             horiz_offset_correction,
             laser_id,
+            max_intensity,
+            min_intensity,
             rot_correction,
+            two_pt_correction_available,
             vert_correction,
             vert_offset_correction,
         } = orig;
@@ -135,7 +166,10 @@ impl From<Laser> for SerializedLaser {
             focal_distance,
             focal_slope,
             horiz_offset_correction,
+            max_intensity,
+            min_intensity,
             rot_correction,
+            two_pt_correction_available,
             vert_correction,
             vert_offset_correction,
         }
@@ -1,9 +1,6 @@
 use anyhow::{ensure, Result};
 use itertools::{izip, Itertools};
-use pcap::Capture;
-use velodyne_lidar::{config::Config, consts, DataPacket};
-
-const UDP_HEADER_SIZE: usize = 42;
+use velodyne_lidar::{config::Config, consts};
 
 #[test]
 fn velodyne_vlp_16_pcap_file() -> Result<()> {
@@ -37,7 +34,7 @@ fn velodyne_vlp_16_pcap_file() -> Result<()> {
     // convert to point cloud
     {
         let config = Config::new_vlp_16_strongest();
-        velodyne_lidar::iter::data_packet_to_frame_xyz(config, data_packets.into_iter())?.count();
+        velodyne_lidar::iter::data_packet_to_frame_xyz(config, data_packets)?.count();
     }
 
     Ok(())
@@ -45,20 +42,11 @@ fn velodyne_vlp_16_pcap_file() -> Result<()> {
 
 #[test]
 fn velodyne_vlp_32_pcap_file() -> Result<()> {
-    let mut cap = Capture::from_file("test_files/velodyne_vlp32.pcap")?;
-    cap.filter("udp", true)?;
-
-    let data_packets: Vec<_> = itertools::unfold(cap, |cap| {
-        Some(loop {
-            let packet = cap.next_packet().ok()?;
-            let slice = &packet.data[UDP_HEADER_SIZE..];
-
-            if let Ok(packet) = DataPacket::from_slice(slice) {
-                break *packet;
-            }
-        })
-    })
-    .collect();
+    let data_packets: Vec<_> =
+        velodyne_lidar::iter::packet_iter_from_file("test_files/velodyne_vlp32.pcap")?
+            .map_ok(|packet| packet.try_into_data().ok())
+            .flatten_ok()
+            .try_collect()?;
 
     // timestamp test
     {
@@ -84,7 +72,7 @@ fn velodyne_vlp_32_pcap_file() -> Result<()> {
     // convert to point cloud
     {
         let config = Config::new_vlp_32c_strongest();
-        velodyne_lidar::iter::data_packet_to_frame_xyz(config, data_packets.into_iter())?.count();
+        velodyne_lidar::iter::data_packet_to_frame_xyz(config, data_packets)?.count();
     }
 
     Ok(())